@@ -0,0 +1,413 @@
+/*
+Static detection of out-of-bounds array accesses and failing assertions.
+
+A forward pass tracks the stack variables whose value is a known constant and
+the arrays whose length is a known constant. Whenever both the index of an
+access x[e] and the length of x are known and the index is not below the
+length, the access is reported. Nothing is reported that could be in bounds
+on some run that reaches it, so a report means the access always fails.
+
+if e then s1 else s2   keeps the facts both branches agree on
+while e do s           keeps the facts the body does not change, and forgets
+                       everything the body assigns if it has a break or continue
+for x := a to b do s   like while, with x unknown
+do s while e           like while, except that s is known to run once
+s1 [] s2, s1 [p] s2    like if, without a condition
+s1 || s2               checks each branch without facts about the variables
+                       the other one assigns, since it may do so at any time
+proc f(...) { s }      checks s knowing nothing about the parameters
+assume x = e           x is known from here on if e is, and so after assert x = e
+
+An assert whose condition is known to be false is reported as well, since it
+fails whenever it is reached.
+*/
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::imp::printExpression;
+use crate::imp::escapesLoop;
+use crate::imp::printStatement;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+#[derive(Clone, Default, PartialEq, Debug)]
+struct Facts {
+    // stack variables with a known value
+    values: HashMap<String, i32>,
+    // array variables with a known length
+    lengths: HashMap<String, i32>,
+    // variables another branch of a parallel composition may assign at any time
+    shared: HashSet<String>,
+}
+
+impl Facts {
+    fn intersect(&mut self, other: &Facts) {
+        self.values.retain(|x, n| other.values.get(x) == Some(n));
+        self.lengths.retain(|x, n| other.lengths.get(x) == Some(n));
+    }
+}
+
+// the value of exp, if it is the same on every run reaching it
+fn constant(exp: &Expression, facts: &Facts) -> Option<i32> {
+    match exp {
+        Expression::StackVar { x } => facts.values.get(x).copied(),
+        Expression::NatConstant { n } => Some(*n),
+        Expression::ArrayLength { x } => facts.lengths.get(x).copied(),
+        Expression::Add { ex1, ex2 } => constant(ex1, facts)?.checked_add(constant(ex2, facts)?),
+        Expression::Sub { ex1, ex2 } => {
+            let (n1, n2) = (constant(ex1, facts)?, constant(ex2, facts)?);
+            Some(if n2 > n1 { 0 } else { n1 - n2 })
+        }
+        Expression::Mul { ex1, ex2 } => constant(ex1, facts)?.checked_mul(constant(ex2, facts)?),
+        Expression::Div { ex1, ex2 } => constant(ex1, facts)?.checked_div(constant(ex2, facts)?),
+        Expression::Mod { ex1, ex2 } => constant(ex1, facts)?.checked_rem(constant(ex2, facts)?),
+        _ => None,
+    }
+}
+
+// the truth value of a condition, if it is the same on every run reaching it
+fn truth(exp: &Expression, facts: &Facts) -> Option<bool> {
+    let compare = |ex1: &Expression, ex2: &Expression, op: fn(i32, i32) -> bool| {
+        Some(op(constant(ex1, facts)?, constant(ex2, facts)?))
+    };
+    match exp {
+        Expression::BoolConstant { b } => Some(*b),
+        Expression::Negation { ex1 } => truth(ex1, facts).map(|b| !b),
+        Expression::Conjunction { ex1, ex2 } => match (truth(ex1, facts), truth(ex2, facts)) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        Expression::Disjunction { ex1, ex2 } => match (truth(ex1, facts), truth(ex2, facts)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        Expression::Comparision { ex1, ex2 } => compare(ex1, ex2, |a, b| a <= b),
+        Expression::LessThan { ex1, ex2 } => compare(ex1, ex2, |a, b| a < b),
+        Expression::GreaterEqual { ex1, ex2 } => compare(ex1, ex2, |a, b| a >= b),
+        Expression::GreaterThan { ex1, ex2 } => compare(ex1, ex2, |a, b| a > b),
+        Expression::Equal { ex1, ex2 } => compare(ex1, ex2, |a, b| a == b),
+        Expression::NotEqual { ex1, ex2 } => compare(ex1, ex2, |a, b| a != b),
+        _ => None,
+    }
+}
+
+// what is known once `condition` is taken to hold
+fn assumeHolds(condition: &Expression, facts: &mut Facts) {
+    match condition {
+        Expression::Conjunction { ex1, ex2 } => {
+            assumeHolds(ex1, facts);
+            assumeHolds(ex2, facts);
+        }
+        Expression::Equal { ex1, ex2 } => match (&**ex1, &**ex2) {
+            (Expression::StackVar { x }, e) | (e, Expression::StackVar { x }) => {
+                if let Some(n) = constant(e, facts) {
+                    setValue(x, Some(n), facts);
+                }
+            }
+            _ => (),
+        },
+        _ => (),
+    }
+}
+
+fn checkAccess(
+    x: &String,
+    index: &Expression,
+    facts: &Facts,
+    st: &Statement,
+    out: &mut Vec<String>,
+) {
+    if let (Some(i), Some(n)) = (constant(index, facts), facts.lengths.get(x)) {
+        if i < 0 || i >= *n {
+            out.push(format!(
+                "Index {} = {} is out of bounds for array {} of length {} in `{}`",
+                printExpression(index.clone()).trim_end(),
+                i,
+                x,
+                n,
+                printStatement(st.clone())
+            ));
+        }
+    }
+}
+
+// reports every array read inside exp
+fn checkExp(exp: &Expression, facts: &Facts, st: &Statement, out: &mut Vec<String>) {
+    match exp {
+        Expression::ArrayRead { x, index } => {
+            checkExp(index, facts, st, out);
+            checkAccess(x, index, facts, st, out);
+        }
+        Expression::Negation { ex1 } => checkExp(ex1, facts, st, out),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => {
+            checkExp(ex1, facts, st, out);
+            checkExp(ex2, facts, st, out);
+        }
+        Expression::StackVar { .. }
+        | Expression::HeapRead { .. }
+        | Expression::NatConstant { .. }
+        | Expression::BoolConstant { .. }
+        | Expression::FieldRead { .. }
+        | Expression::ArrayLength { .. } => (),
+    }
+}
+
+fn setValue(x: &String, value: Option<i32>, facts: &mut Facts) {
+    match value {
+        Some(n) if !facts.shared.contains(x) => facts.values.insert(x.clone(), n),
+        Some(_) => facts.values.remove(x),
+        None => facts.values.remove(x),
+    };
+}
+
+fn setLength(x: &String, length: Option<i32>, facts: &mut Facts) {
+    match length {
+        Some(n) if !facts.shared.contains(x) => facts.lengths.insert(x.clone(), n),
+        Some(_) => facts.lengths.remove(x),
+        None => facts.lengths.remove(x),
+    };
+}
+
+// every variable st may assign
+fn assignedVariables(st: &Statement, out: &mut HashSet<String>) {
+    match st {
+        Statement::StackAssignment { x, .. }
+        | Statement::AnnotatedAssignment { x, .. }
+        | Statement::Read { x, .. }
+        | Statement::Havoc { x }
+        | Statement::Coin { x, .. }
+        | Statement::Call { x: Some(x), .. }
+        | Statement::HeapNew { x, .. }
+        | Statement::RecordNew { x, .. }
+        | Statement::ArrayNew { x, .. }
+        | Statement::HeapAlias { x, .. } => {
+            out.insert(x.clone());
+        }
+        Statement::Sequence { st1, st2 } => {
+            assignedVariables(st1, out);
+            assignedVariables(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | Statement::Choice {
+            st1: then_branch,
+            st2: else_branch,
+        }
+        | Statement::Parallel {
+            st1: then_branch,
+            st2: else_branch,
+        }
+        | Statement::ProbChoice {
+            st1: then_branch,
+            st2: else_branch,
+            ..
+        } => {
+            assignedVariables(then_branch, out);
+            assignedVariables(else_branch, out);
+        }
+        Statement::For { x, st, .. } => {
+            out.insert(x.clone());
+            assignedVariables(st, out);
+        }
+        Statement::While { st, .. } | Statement::DoWhile { st, .. } | Statement::Atomic { st } => {
+            assignedVariables(st, out)
+        }
+        _ => (),
+    }
+}
+
+// drops what is known about every variable st may assign
+fn forgetAssigned(st: &Statement, facts: &mut Facts) {
+    let mut assigned = HashSet::new();
+    assignedVariables(st, &mut assigned);
+    for x in assigned.iter() {
+        setValue(x, None, facts);
+        setLength(x, None, facts);
+    }
+}
+
+// facts for checking one branch of a parallel composition whose other branch is `other`
+fn sharedWith(other: &Statement, facts: &Facts) -> Facts {
+    let mut branch = facts.clone();
+    let mut assigned = HashSet::new();
+    assignedVariables(other, &mut assigned);
+    for x in assigned.into_iter() {
+        branch.values.remove(&x);
+        branch.lengths.remove(&x);
+        branch.shared.insert(x);
+    }
+    branch
+}
+
+// shrinks facts to what holds every time the loop body starts and after the loop
+fn loopInvariant(body: &Statement, facts: &mut Facts) {
+    // a break or continue can leave the body half done
+    if escapesLoop(body) {
+        forgetAssigned(body, facts);
+    }
+    // drop facts until one more iteration of the body keeps all of them
+    loop {
+        let mut after = facts.clone();
+        checkStatement(body, &mut after, &mut Vec::new());
+        let before = facts.clone();
+        facts.intersect(&after);
+        if *facts == before {
+            break;
+        }
+    }
+}
+
+fn checkStatement(st: &Statement, facts: &mut Facts, out: &mut Vec<String>) {
+    match st {
+        Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => {
+            checkExp(ex1, facts, st, out);
+            let value = constant(ex1, facts);
+            setValue(x, value, facts);
+        }
+        Statement::HeapUpdate { ex1, .. } | Statement::FieldUpdate { ex1, .. } => {
+            checkExp(ex1, facts, st, out)
+        }
+        Statement::HeapNew { x, ex1 } => {
+            checkExp(ex1, facts, st, out);
+            setLength(x, None, facts);
+        }
+        Statement::RecordNew { x, fields } => {
+            for (_, e) in fields {
+                checkExp(e, facts, st, out);
+            }
+            setLength(x, None, facts);
+        }
+        Statement::HeapAlias { x, y } => {
+            let length = facts.lengths.get(y).copied();
+            setLength(x, length, facts);
+        }
+        Statement::ArrayNew { x, size, ex1 } => {
+            checkExp(size, facts, st, out);
+            checkExp(ex1, facts, st, out);
+            let length = constant(size, facts);
+            setLength(x, length, facts);
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            checkExp(index, facts, st, out);
+            checkExp(ex1, facts, st, out);
+            checkAccess(x, index, facts, st, out);
+        }
+        Statement::Sequence { st1, st2 } => {
+            checkStatement(st1, facts, out);
+            checkStatement(st2, facts, out);
+        }
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            checkExp(condition, facts, st, out);
+            let mut then_facts = facts.clone();
+            checkStatement(then_branch, &mut then_facts, out);
+            checkStatement(else_branch, facts, out);
+            facts.intersect(&then_facts);
+        }
+        Statement::Skip | Statement::Declaration { .. } => (),
+        Statement::While { condition, st: body } => {
+            loopInvariant(body, facts);
+            checkExp(condition, facts, st, out);
+            checkStatement(body, &mut facts.clone(), out);
+        }
+        Statement::For { x, from, to, st: body } => {
+            checkExp(from, facts, st, out);
+            checkExp(to, facts, st, out);
+            setValue(x, None, facts);
+            loopInvariant(body, facts);
+            checkStatement(body, &mut facts.clone(), out);
+        }
+        Statement::DoWhile { st: body, condition } => {
+            loopInvariant(body, facts);
+            let mut after = facts.clone();
+            checkStatement(body, &mut after, out);
+            // a continue reaches the condition with the body half done, and facts already forgot what it assigns
+            if escapesLoop(body) {
+                checkExp(condition, facts, st, out);
+            } else {
+                checkExp(condition, &after, st, out);
+                *facts = after;
+            }
+        }
+        Statement::Break | Statement::Continue => (),
+        Statement::Choice { st1, st2 } | Statement::ProbChoice { st1, st2, .. } => {
+            let mut first = facts.clone();
+            checkStatement(st1, &mut first, out);
+            checkStatement(st2, facts, out);
+            facts.intersect(&first);
+        }
+        Statement::Havoc { x } | Statement::Coin { x, .. } => setValue(x, None, facts),
+        // each branch keeps what it learns about the variables only it assigns
+        Statement::Parallel { st1, st2 } => {
+            let mut first = sharedWith(st2, facts);
+            let mut second = sharedWith(st1, facts);
+            checkStatement(st1, &mut first, out);
+            checkStatement(st2, &mut second, out);
+            forgetAssigned(st1, facts);
+            forgetAssigned(st2, facts);
+            facts.values.extend(first.values.into_iter().chain(second.values));
+            facts.lengths.extend(first.lengths.into_iter().chain(second.lengths));
+        }
+        Statement::Atomic { st: body } => checkStatement(body, facts, out),
+        Statement::Procedure { body, ret, .. } => {
+            let mut local = Facts::default();
+            checkStatement(body, &mut local, out);
+            if let Some((_, ex1)) = ret {
+                checkExp(ex1, &local, st, out);
+            }
+        }
+        Statement::Call { x, args, .. } => {
+            for e in args {
+                checkExp(e, facts, st, out);
+            }
+            if let Some(x) = x {
+                setValue(x, None, facts);
+            }
+        }
+        Statement::Assert { condition } => {
+            checkExp(condition, facts, st, out);
+            if truth(condition, facts) == Some(false) {
+                out.push(format!(
+                    "Assertion `{}` fails whenever it is reached",
+                    printStatement(st.clone())
+                ));
+            }
+            assumeHolds(condition, facts);
+        }
+        Statement::Assume { condition } => {
+            checkExp(condition, facts, st, out);
+            assumeHolds(condition, facts);
+        }
+        Statement::Print { ex1 } => checkExp(ex1, facts, st, out),
+        Statement::Read { x, .. } => setValue(x, None, facts),
+    }
+}
+
+// every array access and assertion in st that fails whenever it is reached
+pub fn checkBounds(st: &Statement) -> Vec<String> {
+    let mut out = Vec::new();
+    checkStatement(st, &mut Facts::default(), &mut out);
+    out
+}
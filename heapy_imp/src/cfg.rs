@@ -0,0 +1,542 @@
+/*
+Control-flow graphs of Heapy IMP programs.
+
+A basic block holds the statements that run one after the other without any
+control flow of their own: assignments, heap updates, allocations, aliasing,
+declarations, calls, assert, assume, print, read, havoc and coin. It ends in a
+terminator saying where control goes next:
+
+goto b                        always to block b
+branch e ? b1 : b2            to b1 if e holds and to b2 otherwise
+choice b1 [p] b2              to either of them, with probability p for b1 in s1 [p] s2
+exit                          only the exit block, which is empty
+
+The tree of a statement is flattened as follows:
+
+s1; s2                   s1 continues into s2
+if e then s1 else s2     branch into s1 and s2, both join afterwards
+while e do s             a header block branching into s or out of the loop, s going back to it
+do s while e             s first, then a block branching back into s or out of the loop
+for x := a to b do s     x#next := a; x#to := b;
+                         while x#next <= x#to do { x := x#next; s; x#next := x#next + 1 }
+                         with the bounds kept in the fresh variables x#next and x#to
+break, continue          goto after the innermost loop, or to where it checks its condition,
+                         leaving the statements after them in a block without predecessors
+s1 [] s2, s1 [p] s2      choice into s1 and s2, both join afterwards
+atomic { s }             s, as the graph has no interleaving anyway
+s1 || s2                 stays a single statement of its block
+proc f(...) { s }        does not show up at all, its body gets a graph of its own
+
+Block 0 is the entry. Blocks with no way to reach the exit, e.g. inside a loop
+that never ends, have no post-dominator, and unreachable blocks have no dominator.
+Loops are the natural loops of the back edges, i.e. the edges going to a block
+that dominates where they come from, merged when they share their header.
+*/
+
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::Expression;
+use crate::imp::Statement;
+use crate::rational::printRational;
+use crate::rational::Rational;
+
+#[derive(Clone)]
+pub enum Terminator {
+    Goto(usize),
+    Branch {
+        condition: Expression,
+        then_block: usize,
+        else_block: usize,
+    },
+    // p is the probability of the first block, for a probabilistic choice
+    Choice {
+        first: usize,
+        second: usize,
+        p: Option<Rational>,
+    },
+    Exit,
+}
+
+#[derive(Clone)]
+pub struct Block {
+    pub statements: Vec<Statement>,
+    pub terminator: Terminator,
+}
+
+#[derive(Clone)]
+pub struct Cfg {
+    pub blocks: Vec<Block>,
+    pub entry: usize,
+    pub exit: usize,
+}
+
+// the condition an edge is taken under, if any
+#[derive(Clone)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    // the condition of the branch and whether it holds on this edge
+    pub condition: Option<(Expression, bool)>,
+}
+
+// the loops we are in: where a break and where a continue goes
+struct Builder {
+    blocks: Vec<Block>,
+    loops: Vec<(usize, usize)>,
+}
+
+impl Builder {
+    fn newBlock(&mut self) -> usize {
+        self.blocks.push(Block {
+            statements: Vec::new(),
+            terminator: Terminator::Exit,
+        });
+        self.blocks.len() - 1
+    }
+
+    fn goto(&mut self, from: usize, to: usize) {
+        self.blocks[from].terminator = Terminator::Goto(to);
+    }
+
+    // adds st to the graph with control arriving in block current, returning the block control leaves from
+    fn build(&mut self, st: &Statement, current: usize) -> usize {
+        match st {
+            Statement::Sequence { st1, st2 } => {
+                let middle = self.build(st1, current);
+                self.build(st2, middle)
+            }
+            Statement::IfThenElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let (then_block, else_block) = (self.newBlock(), self.newBlock());
+                self.blocks[current].terminator = Terminator::Branch {
+                    condition: condition.clone(),
+                    then_block,
+                    else_block,
+                };
+                let then_end = self.build(then_branch, then_block);
+                let else_end = self.build(else_branch, else_block);
+                let join = self.newBlock();
+                self.goto(then_end, join);
+                self.goto(else_end, join);
+                join
+            }
+            Statement::Choice { st1, st2 } | Statement::ProbChoice { st1, st2, .. } => {
+                let (first, second) = (self.newBlock(), self.newBlock());
+                let p = match st {
+                    Statement::ProbChoice { p, .. } => Some(*p),
+                    _ => None,
+                };
+                self.blocks[current].terminator = Terminator::Choice { first, second, p };
+                let first_end = self.build(st1, first);
+                let second_end = self.build(st2, second);
+                let join = self.newBlock();
+                self.goto(first_end, join);
+                self.goto(second_end, join);
+                join
+            }
+            Statement::While { condition, st: body } => {
+                let header = self.newBlock();
+                self.goto(current, header);
+                let (body_block, after) = (self.newBlock(), self.newBlock());
+                self.blocks[header].terminator = Terminator::Branch {
+                    condition: condition.clone(),
+                    then_block: body_block,
+                    else_block: after,
+                };
+                self.loops.push((after, header));
+                let body_end = self.build(body, body_block);
+                self.loops.pop();
+                self.goto(body_end, header);
+                after
+            }
+            Statement::DoWhile { st: body, condition } => {
+                let body_block = self.newBlock();
+                self.goto(current, body_block);
+                let (check, after) = (self.newBlock(), self.newBlock());
+                self.loops.push((after, check));
+                let body_end = self.build(body, body_block);
+                self.loops.pop();
+                self.goto(body_end, check);
+                self.blocks[check].terminator = Terminator::Branch {
+                    condition: condition.clone(),
+                    then_block: body_block,
+                    else_block: after,
+                };
+                after
+            }
+            Statement::For { x, from, to, st: body } => {
+                let (next, bound) = (forNext(x), forBound(x));
+                let var = |x: &String| Expression::StackVar { x: x.clone() };
+                self.blocks[current].statements.push(Statement::StackAssignment {
+                    x: next.clone(),
+                    ex1: from.clone(),
+                });
+                self.blocks[current].statements.push(Statement::StackAssignment {
+                    x: bound.clone(),
+                    ex1: to.clone(),
+                });
+                let header = self.newBlock();
+                self.goto(current, header);
+                let (body_block, latch, after) = (self.newBlock(), self.newBlock(), self.newBlock());
+                self.blocks[header].terminator = Terminator::Branch {
+                    condition: Expression::Comparision {
+                        ex1: Box::new(var(&next)),
+                        ex2: Box::new(var(&bound)),
+                    },
+                    then_block: body_block,
+                    else_block: after,
+                };
+                self.blocks[body_block].statements.push(Statement::StackAssignment {
+                    x: x.clone(),
+                    ex1: var(&next),
+                });
+                self.loops.push((after, latch));
+                let body_end = self.build(body, body_block);
+                self.loops.pop();
+                self.goto(body_end, latch);
+                self.blocks[latch].statements.push(Statement::StackAssignment {
+                    x: next.clone(),
+                    ex1: Expression::Add {
+                        ex1: Box::new(var(&next)),
+                        ex2: Box::new(Expression::NatConstant { n: 1 }),
+                    },
+                });
+                self.goto(latch, header);
+                after
+            }
+            Statement::Break | Statement::Continue => {
+                if let Some((after, check)) = self.loops.last().copied() {
+                    let target = if let Statement::Break = st { after } else { check };
+                    self.goto(current, target);
+                }
+                self.newBlock()
+            }
+            Statement::Atomic { st } => self.build(st, current),
+            Statement::Skip | Statement::Procedure { .. } => current,
+            _ => {
+                self.blocks[current].statements.push(st.clone());
+                current
+            }
+        }
+    }
+}
+
+// the fresh variables holding the next value and the upper bound of the loop variable x of a for loop
+pub fn forNext(x: &String) -> String {
+    x.clone() + "#next"
+}
+
+pub fn forBound(x: &String) -> String {
+    x.clone() + "#to"
+}
+
+// the graph of st, not including the bodies of the procedures it declares
+pub fn buildCfg(st: &Statement) -> Cfg {
+    let mut builder = Builder {
+        blocks: Vec::new(),
+        loops: Vec::new(),
+    };
+    let entry = builder.newBlock();
+    let end = builder.build(st, entry);
+    let exit = builder.newBlock();
+    builder.goto(end, exit);
+    Cfg {
+        blocks: builder.blocks,
+        entry,
+        exit,
+    }
+}
+
+// the graph of the body of every procedure declared anywhere in st
+pub fn procedureCfgs(st: &Statement) -> Vec<(String, Cfg)> {
+    let mut out = Vec::new();
+    collectProcedureCfgs(st, &mut out);
+    out
+}
+
+fn collectProcedureCfgs(st: &Statement, out: &mut Vec<(String, Cfg)>) {
+    match st {
+        Statement::Procedure { name, body, .. } => {
+            out.push((name.clone(), buildCfg(body)));
+            collectProcedureCfgs(body, out);
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => {
+            collectProcedureCfgs(st1, out);
+            collectProcedureCfgs(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collectProcedureCfgs(then_branch, out);
+            collectProcedureCfgs(else_branch, out);
+        }
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => collectProcedureCfgs(st, out),
+        _ => (),
+    }
+}
+
+pub fn successors(cfg: &Cfg, b: usize) -> Vec<usize> {
+    match &cfg.blocks[b].terminator {
+        Terminator::Goto(to) => vec![*to],
+        Terminator::Branch {
+            then_block,
+            else_block,
+            ..
+        } => vec![*then_block, *else_block],
+        Terminator::Choice { first, second, .. } => vec![*first, *second],
+        Terminator::Exit => vec![],
+    }
+}
+
+pub fn predecessors(cfg: &Cfg) -> Vec<Vec<usize>> {
+    let mut preds = vec![Vec::new(); cfg.blocks.len()];
+    for b in 0..cfg.blocks.len() {
+        for s in successors(cfg, b) {
+            preds[s].push(b);
+        }
+    }
+    preds
+}
+
+// every edge of the graph, with the condition it is taken under
+pub fn edges(cfg: &Cfg) -> Vec<Edge> {
+    let mut out = Vec::new();
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        match &block.terminator {
+            Terminator::Branch {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                out.push(Edge {
+                    from: b,
+                    to: *then_block,
+                    condition: Some((condition.clone(), true)),
+                });
+                out.push(Edge {
+                    from: b,
+                    to: *else_block,
+                    condition: Some((condition.clone(), false)),
+                });
+            }
+            _ => {
+                for to in successors(cfg, b) {
+                    out.push(Edge {
+                        from: b,
+                        to,
+                        condition: None,
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+// the blocks reachable from root following `next`
+fn reachableFrom(root: usize, next: &Vec<Vec<usize>>) -> Vec<bool> {
+    let mut seen = vec![false; next.len()];
+    let mut todo = vec![root];
+    while let Some(b) = todo.pop() {
+        if !seen[b] {
+            seen[b] = true;
+            todo.extend(next[b].iter().copied());
+        }
+    }
+    seen
+}
+
+// the immediate dominator of every block, in the graph given by `next` and `prev` from root
+#[derive(Clone, PartialEq, Debug)]
+pub struct DominatorTree {
+    pub root: usize,
+    // None for the root and for blocks not reachable from it
+    pub idom: Vec<Option<usize>>,
+    pub reachable: Vec<bool>,
+}
+
+impl DominatorTree {
+    // whether every path from the root to b goes through a, which includes a = b
+    pub fn dominates(&self, a: usize, b: usize) -> bool {
+        if !self.reachable[b] {
+            return false;
+        }
+        let mut current = Some(b);
+        while let Some(c) = current {
+            if c == a {
+                return true;
+            }
+            current = self.idom[c];
+        }
+        false
+    }
+}
+
+fn dominatorTree(root: usize, next: &Vec<Vec<usize>>, prev: &Vec<Vec<usize>>) -> DominatorTree {
+    let n = next.len();
+    let reachable = reachableFrom(root, next);
+    // every block starts dominated by all blocks, then the sets shrink to the fixpoint
+    let mut dom: Vec<Vec<bool>> = (0..n).map(|b| (0..n).map(|a| a == b || b != root).collect()).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in (0..n).filter(|b| *b != root && reachable[*b]) {
+            let mut meet = vec![true; n];
+            for p in prev[b].iter().filter(|p| reachable[**p]) {
+                for a in 0..n {
+                    meet[a] = meet[a] && dom[*p][a];
+                }
+            }
+            meet[b] = true;
+            if meet != dom[b] {
+                dom[b] = meet;
+                changed = true;
+            }
+        }
+    }
+    // the immediate dominator is the strict dominator with the most dominators itself
+    let count = |a: usize| dom[a].iter().filter(|d| **d).count();
+    let idom = (0..n)
+        .map(|b| {
+            if b == root || !reachable[b] {
+                None
+            } else {
+                (0..n).filter(|a| *a != b && dom[b][*a]).max_by_key(|a| count(*a))
+            }
+        })
+        .collect();
+    DominatorTree {
+        root,
+        idom,
+        reachable,
+    }
+}
+
+pub fn dominators(cfg: &Cfg) -> DominatorTree {
+    let next: Vec<Vec<usize>> = (0..cfg.blocks.len()).map(|b| successors(cfg, b)).collect();
+    dominatorTree(cfg.entry, &next, &predecessors(cfg))
+}
+
+// dominators of the reversed graph, from the exit
+pub fn postDominators(cfg: &Cfg) -> DominatorTree {
+    let next: Vec<Vec<usize>> = (0..cfg.blocks.len()).map(|b| successors(cfg, b)).collect();
+    dominatorTree(cfg.exit, &predecessors(cfg), &next)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Loop {
+    pub header: usize,
+    // every block of the loop, including the header and those of nested loops, in order
+    pub blocks: Vec<usize>,
+    // the innermost loop around this one and the loops directly inside it, as positions in the forest
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+// the natural loops of cfg, outer loops before the loops nested in them
+pub fn loopForest(cfg: &Cfg) -> Vec<Loop> {
+    let dom = dominators(cfg);
+    let preds = predecessors(cfg);
+    let mut loops: Vec<Loop> = Vec::new();
+    for edge in edges(cfg) {
+        if !dom.dominates(edge.to, edge.from) {
+            continue;
+        }
+        // everything that reaches the back edge without passing the header
+        let mut inside = vec![false; cfg.blocks.len()];
+        inside[edge.to] = true;
+        let mut todo = vec![edge.from];
+        while let Some(b) = todo.pop() {
+            if !inside[b] {
+                inside[b] = true;
+                todo.extend(preds[b].iter().copied());
+            }
+        }
+        let blocks: Vec<usize> = (0..cfg.blocks.len()).filter(|b| inside[*b]).collect();
+        match loops.iter_mut().find(|l| l.header == edge.to) {
+            Some(l) => {
+                l.blocks.extend(blocks);
+                l.blocks.sort();
+                l.blocks.dedup();
+            }
+            None => loops.push(Loop {
+                header: edge.to,
+                blocks,
+                parent: None,
+                children: Vec::new(),
+            }),
+        }
+    }
+    loops.sort_by_key(|l| std::cmp::Reverse(l.blocks.len()));
+    // the parent of a loop is the smallest one before it holding its header
+    for i in 0..loops.len() {
+        let parent = (0..i)
+            .rev()
+            .find(|j| loops[*j].blocks.contains(&loops[i].header));
+        loops[i].parent = parent;
+        if let Some(j) = parent {
+            loops[j].children.push(i);
+        }
+    }
+    loops
+}
+
+// how many loops of the forest block b is in
+pub fn loopDepth(forest: &Vec<Loop>, b: usize) -> usize {
+    forest.iter().filter(|l| l.blocks.contains(&b)).count()
+}
+
+// what an edge is taken under: its condition, the probability of a probabilistic choice, or
+// [] for a nondeterministic one, and nothing for a goto
+pub fn edgeLabel(cfg: &Cfg, edge: &Edge) -> String {
+    match (&edge.condition, &cfg.blocks[edge.from].terminator) {
+        (Some((condition, true)), _) => printExpression(condition.clone()),
+        (Some((condition, false)), _) => String::from("not ") + &printExpression(condition.clone()),
+        (None, Terminator::Choice { first, p: Some(p), .. }) if *first == edge.to => printRational(*p),
+        (None, Terminator::Choice { p: Some(p), .. }) => printRational(Rational::one().sub(p).unwrap_or(*p)),
+        (None, Terminator::Choice { p: None, .. }) => String::from("[]"),
+        (None, _) => String::new(),
+    }
+}
+
+fn escapeDot(text: String) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// the graph in the DOT language of Graphviz
+pub fn printDot(cfg: &Cfg) -> String {
+    let mut out = String::from("digraph cfg {\n    node [shape=box];\n");
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        let mut label = format!("b{}", b);
+        if b == cfg.entry {
+            label += " (entry)";
+        }
+        if b == cfg.exit {
+            label += " (exit)";
+        }
+        for st in block.statements.iter() {
+            label = label + "\\n" + &escapeDot(printStatement(st.clone()));
+        }
+        out += &format!("    b{} [label=\"{}\"];\n", b, label);
+    }
+    for edge in edges(cfg) {
+        let label = escapeDot(edgeLabel(cfg, &edge));
+        if label.is_empty() {
+            out += &format!("    b{} -> b{};\n", edge.from, edge.to);
+        } else {
+            out += &format!("    b{} -> b{} [label=\"{}\"];\n", edge.from, edge.to, label);
+        }
+    }
+    out + "}\n"
+}
@@ -0,0 +1,427 @@
+/*
+Constant propagation and folding.
+
+A forward pass over the tree of the program tracks the stack variables whose
+value is a known nat or bool, replaces reads of them by their value and folds
+every operator whose operands are all constants. Folding runs the operator of
+the interpreter, so an expression that would fail at run time, e.g. by
+dividing by zero or overflowing, is left as it is and still fails.
+
+if e then s1 else s2   becomes s1 or s2 if e folds to a constant, and otherwise
+                       keeps the values both branches agree on
+while e do s           becomes skip if e folds to false before the loop, and
+                       otherwise forgets everything s may assign
+for x := a to b do s   like while, with x unknown, and skip if a > b
+do s while e           forgets everything s may assign
+s1 [] s2, s1 [p] s2    like if, without a condition
+s1 || s2               folds each branch without the values of the variables
+                       the other one may assign at any time
+proc f(...) { s }      folds s knowing nothing about the parameters
+
+Assigning anything but a constant, reading, havoc, coin, calls and allocations
+make their variable unknown. Skips left in a sequence are dropped.
+*/
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::eval::evalExp;
+use crate::eval::State;
+use crate::eval::Value;
+use crate::imp::definedVariables;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+#[derive(Clone, Default)]
+struct Facts {
+    // stack variables with a known value
+    values: HashMap<String, Value>,
+    // variables another branch of a parallel composition may assign at any time
+    shared: HashSet<String>,
+}
+
+impl Facts {
+    fn intersect(&mut self, other: &Facts) {
+        self.values.retain(|x, v| other.values.get(x) == Some(v));
+    }
+
+    fn set(&mut self, x: &String, exp: &Expression) {
+        match constantValue(exp) {
+            Some(v) if !self.shared.contains(x) => {
+                self.values.insert(x.clone(), v);
+            }
+            _ => {
+                self.values.remove(x);
+            }
+        }
+    }
+
+    fn forget(&mut self, st: &Statement) {
+        for x in definedVariables(st) {
+            self.values.remove(&x);
+        }
+    }
+}
+
+fn constantValue(exp: &Expression) -> Option<Value> {
+    match exp {
+        Expression::NatConstant { n } => Some(Value::Nat(*n)),
+        Expression::BoolConstant { b } => Some(Value::Bool(*b)),
+        _ => None,
+    }
+}
+
+fn isConstant(exp: &Expression) -> bool {
+    constantValue(exp).is_some()
+}
+
+// exp with its operator applied if both operands are constants
+fn foldOperator(exp: Expression, ex1: &Expression, ex2: &Expression) -> Expression {
+    if !isConstant(ex1) || !isConstant(ex2) {
+        return exp;
+    }
+    match evalExp(&exp, &State::default()) {
+        Ok(Value::Nat(n)) => Expression::NatConstant { n },
+        Ok(Value::Bool(b)) => Expression::BoolConstant { b },
+        _ => exp,
+    }
+}
+
+fn fold(exp: &Expression, facts: &Facts) -> Expression {
+    let both = |ex1: &Expression, ex2: &Expression| (Box::new(fold(ex1, facts)), Box::new(fold(ex2, facts)));
+    let folded = match exp {
+        Expression::StackVar { x } => match facts.values.get(x) {
+            Some(Value::Nat(n)) => Expression::NatConstant { n: *n },
+            Some(Value::Bool(b)) => Expression::BoolConstant { b: *b },
+            _ => exp.clone(),
+        },
+        Expression::ArrayRead { x, index } => Expression::ArrayRead {
+            x: x.clone(),
+            index: Box::new(fold(index, facts)),
+        },
+        Expression::Negation { ex1 } => {
+            return match fold(ex1, facts) {
+                Expression::BoolConstant { b } => Expression::BoolConstant { b: !b },
+                ex1 => Expression::Negation { ex1: Box::new(ex1) },
+            }
+        }
+        Expression::Add { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Add { ex1, ex2 }
+        }
+        Expression::Sub { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Sub { ex1, ex2 }
+        }
+        Expression::Mul { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Mul { ex1, ex2 }
+        }
+        Expression::Div { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Div { ex1, ex2 }
+        }
+        Expression::Mod { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Mod { ex1, ex2 }
+        }
+        Expression::Conjunction { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Conjunction { ex1, ex2 }
+        }
+        Expression::Disjunction { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Disjunction { ex1, ex2 }
+        }
+        Expression::Implication { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Implication { ex1, ex2 }
+        }
+        Expression::Comparision { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Comparision { ex1, ex2 }
+        }
+        Expression::LessThan { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::LessThan { ex1, ex2 }
+        }
+        Expression::GreaterEqual { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::GreaterEqual { ex1, ex2 }
+        }
+        Expression::GreaterThan { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::GreaterThan { ex1, ex2 }
+        }
+        Expression::Equal { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::Equal { ex1, ex2 }
+        }
+        Expression::NotEqual { ex1, ex2 } => {
+            let (ex1, ex2) = both(ex1, ex2);
+            Expression::NotEqual { ex1, ex2 }
+        }
+        Expression::HeapRead { .. }
+        | Expression::FieldRead { .. }
+        | Expression::ArrayLength { .. }
+        | Expression::NatConstant { .. }
+        | Expression::BoolConstant { .. } => exp.clone(),
+    };
+    match &folded {
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => foldOperator(folded.clone(), ex1, ex2),
+        _ => folded,
+    }
+}
+
+fn sequence(st1: Statement, st2: Statement) -> Statement {
+    match (st1, st2) {
+        (Statement::Skip, st2) => st2,
+        (st1, Statement::Skip) => st1,
+        (st1, st2) => Statement::Sequence {
+            st1: Box::new(st1),
+            st2: Box::new(st2),
+        },
+    }
+}
+
+fn foldStatement(st: &Statement, facts: &mut Facts) -> Statement {
+    match st {
+        Statement::StackAssignment { x, ex1 } => {
+            let ex1 = fold(ex1, facts);
+            facts.set(x, &ex1);
+            Statement::StackAssignment { x: x.clone(), ex1 }
+        }
+        Statement::AnnotatedAssignment { x, tp, ex1 } => {
+            let ex1 = fold(ex1, facts);
+            facts.set(x, &ex1);
+            Statement::AnnotatedAssignment {
+                x: x.clone(),
+                tp: tp.clone(),
+                ex1,
+            }
+        }
+        Statement::HeapUpdate { x, ex1 } => Statement::HeapUpdate {
+            x: x.clone(),
+            ex1: fold(ex1, facts),
+        },
+        Statement::HeapNew { x, ex1 } => {
+            let ex1 = fold(ex1, facts);
+            facts.forget(st);
+            Statement::HeapNew { x: x.clone(), ex1 }
+        }
+        Statement::RecordNew { x, fields } => {
+            let fields = fields.iter().map(|(f, e)| (f.clone(), fold(e, facts))).collect();
+            facts.forget(st);
+            Statement::RecordNew { x: x.clone(), fields }
+        }
+        Statement::FieldUpdate { x, field, ex1 } => Statement::FieldUpdate {
+            x: x.clone(),
+            field: field.clone(),
+            ex1: fold(ex1, facts),
+        },
+        Statement::ArrayNew { x, size, ex1 } => {
+            let (size, ex1) = (fold(size, facts), fold(ex1, facts));
+            facts.forget(st);
+            Statement::ArrayNew { x: x.clone(), size, ex1 }
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => Statement::ArrayUpdate {
+            x: x.clone(),
+            index: fold(index, facts),
+            ex1: fold(ex1, facts),
+        },
+        Statement::Call { x, name, args } => {
+            let args = args.iter().map(|e| fold(e, facts)).collect();
+            facts.forget(st);
+            Statement::Call {
+                x: x.clone(),
+                name: name.clone(),
+                args,
+            }
+        }
+        Statement::Assert { condition } => Statement::Assert {
+            condition: fold(condition, facts),
+        },
+        Statement::Assume { condition } => Statement::Assume {
+            condition: fold(condition, facts),
+        },
+        Statement::Print { ex1 } => Statement::Print { ex1: fold(ex1, facts) },
+        Statement::HeapAlias { .. }
+        | Statement::Read { .. }
+        | Statement::Havoc { .. }
+        | Statement::Coin { .. }
+        | Statement::Declaration { .. } => {
+            facts.forget(st);
+            st.clone()
+        }
+        Statement::Skip | Statement::Break | Statement::Continue => st.clone(),
+        Statement::Sequence { st1, st2 } => {
+            let st1 = foldStatement(st1, facts);
+            let st2 = foldStatement(st2, facts);
+            sequence(st1, st2)
+        }
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => match fold(condition, facts) {
+            Expression::BoolConstant { b: true } => foldStatement(then_branch, facts),
+            Expression::BoolConstant { b: false } => foldStatement(else_branch, facts),
+            condition => {
+                let mut else_facts = facts.clone();
+                let then_branch = foldStatement(then_branch, facts);
+                let else_branch = foldStatement(else_branch, &mut else_facts);
+                facts.intersect(&else_facts);
+                Statement::IfThenElse {
+                    condition,
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                }
+            }
+        },
+        Statement::Choice { st1, st2 } | Statement::ProbChoice { st1, st2, .. } => {
+            let mut second = facts.clone();
+            let new1 = foldStatement(st1, facts);
+            let new2 = foldStatement(st2, &mut second);
+            facts.intersect(&second);
+            match st {
+                Statement::ProbChoice { p, .. } => Statement::ProbChoice {
+                    st1: Box::new(new1),
+                    st2: Box::new(new2),
+                    p: *p,
+                },
+                _ => Statement::Choice {
+                    st1: Box::new(new1),
+                    st2: Box::new(new2),
+                },
+            }
+        }
+        Statement::While { condition, st: body } => {
+            if let Expression::BoolConstant { b: false } = fold(condition, facts) {
+                return Statement::Skip;
+            }
+            facts.forget(body);
+            let condition = fold(condition, facts);
+            let body = foldStatement(body, &mut facts.clone());
+            Statement::While {
+                condition,
+                st: Box::new(body),
+            }
+        }
+        Statement::DoWhile { st: body, condition } => {
+            facts.forget(body);
+            let body = foldStatement(body, &mut facts.clone());
+            Statement::DoWhile {
+                st: Box::new(body),
+                condition: fold(condition, facts),
+            }
+        }
+        Statement::For { x, from, to, st: body } => {
+            let (from, to) = (fold(from, facts), fold(to, facts));
+            if let (Some(Value::Nat(n1)), Some(Value::Nat(n2))) = (constantValue(&from), constantValue(&to)) {
+                if n1 > n2 {
+                    return Statement::Skip;
+                }
+            }
+            facts.forget(st);
+            let body = foldStatement(body, &mut facts.clone());
+            Statement::For {
+                x: x.clone(),
+                from,
+                to,
+                st: Box::new(body),
+            }
+        }
+        Statement::Parallel { st1, st2 } => {
+            let mut first = facts.clone();
+            let mut second = facts.clone();
+            for x in definedVariables(st2) {
+                first.values.remove(&x);
+                first.shared.insert(x);
+            }
+            for x in definedVariables(st1) {
+                second.values.remove(&x);
+                second.shared.insert(x);
+            }
+            let st1 = foldStatement(st1, &mut first);
+            let st2 = foldStatement(st2, &mut second);
+            facts.forget(st);
+            Statement::Parallel {
+                st1: Box::new(st1),
+                st2: Box::new(st2),
+            }
+        }
+        Statement::Atomic { st } => Statement::Atomic {
+            st: Box::new(foldStatement(st, facts)),
+        },
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => {
+            let mut inner = Facts::default();
+            let body = foldStatement(body, &mut inner);
+            Statement::Procedure {
+                name: name.clone(),
+                params: params.clone(),
+                body: Box::new(body),
+                ret: ret.as_ref().map(|(tp, e)| (tp.clone(), fold(e, &inner))),
+            }
+        }
+    }
+}
+
+// exp with every operator on constants folded
+pub fn foldExpression(exp: &Expression) -> Expression {
+    fold(exp, &Facts::default())
+}
+
+// whether evaluating exp cannot fail, given the stack variables surely assigned or None to take
+// them all as assigned: an addition or multiplication may overflow unless it folds to a constant
+pub fn cannotFail(exp: &Expression, assigned: Option<&BTreeSet<String>>) -> bool {
+    match exp {
+        Expression::StackVar { x } => assigned.map(|a| a.contains(x)).unwrap_or(true),
+        Expression::NatConstant { .. } | Expression::BoolConstant { .. } => true,
+        Expression::HeapRead { .. }
+        | Expression::FieldRead { .. }
+        | Expression::ArrayRead { .. }
+        | Expression::ArrayLength { .. } => false,
+        Expression::Add { .. } | Expression::Mul { .. } => {
+            matches!(foldExpression(exp), Expression::NatConstant { .. })
+        }
+        Expression::Div { ex1, ex2 } | Expression::Mod { ex1, ex2 } => match **ex2 {
+            Expression::NatConstant { n } => n != 0 && cannotFail(ex1, assigned),
+            _ => false,
+        },
+        Expression::Negation { ex1 } => cannotFail(ex1, assigned),
+        Expression::Sub { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => cannotFail(ex1, assigned) && cannotFail(ex2, assigned),
+    }
+}
+
+// st with known values of stack variables propagated, constants folded and branches on them decided
+pub fn foldConstants(st: &Statement) -> Statement {
+    foldStatement(st, &mut Facts::default())
+}
@@ -0,0 +1,287 @@
+/*
+A framework for monotone dataflow analyses over control-flow graphs.
+
+An analysis picks a lattice of facts and a direction, says what holds at the
+boundary (the entry of a forward analysis, the exit of a backward one) and
+how every statement and every condition edge transforms a fact. The solver
+then computes the least fixpoint with a worklist:
+
+forward    in(b) = boundary ⊔ ⨆ edge(p → b, out(p))   out(b) = transfer(s_n, ..., transfer(s_1, in(b)))
+backward   out(b) = boundary ⊔ ⨆ edge(b → s, in(s))    in(b) = transfer(s_1, ..., transfer(s_n, out(b)))
+
+where the boundary only joins in at the entry or exit block. At the headers of
+loops the new fact is widened into the old one, so lattices of infinite height
+terminate as long as their widening does. From there the solver descends again,
+recomputing every block and narrowing the old fact at loop headers by the new
+one, until nothing changes, to win back what widening gave away. Blocks the analysis never gets to,
+i.e. unreachable ones when forward and ones that cannot reach the exit when
+backward, keep bottom.
+
+A solution holds the fact at every program point, i.e. before every statement
+of every block and after the last one, always in the order the program runs.
+*/
+
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+
+use crate::cfg::loopForest;
+use crate::cfg::predecessors;
+use crate::cfg::successors;
+use crate::cfg::Cfg;
+use crate::cfg::Terminator;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+pub trait Lattice: Clone + PartialEq {
+    fn bottom() -> Self;
+    fn join(&self, other: &Self) -> Self;
+    fn leq(&self, other: &Self) -> bool;
+
+    // an upper bound of self and next that keeps ascending chains finite
+    fn widen(&self, next: &Self) -> Self {
+        self.join(next)
+    }
+
+    // something between next and self, for next below self, that keeps descending chains finite
+    fn narrow(&self, _next: &Self) -> Self {
+        self.clone()
+    }
+}
+
+// sets ordered by inclusion, for analyses asking whether something may happen
+impl<T: Ord + Clone> Lattice for BTreeSet<T> {
+    fn bottom() -> Self {
+        BTreeSet::new()
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.union(other).cloned().collect()
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.is_subset(other)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+pub trait Analysis {
+    type Fact: Lattice;
+
+    fn direction(&self) -> Direction;
+
+    // what holds at the entry of the graph for a forward analysis, at the exit for a backward one
+    fn boundary(&self) -> Self::Fact;
+
+    // the fact after st from the one before it, or before st from the one after it when backward
+    fn transfer(&self, st: &Statement, at: Point, fact: &Self::Fact) -> Self::Fact;
+
+    // the fact along the edge of a branch on which condition evaluates to holds
+    fn edge(&self, _condition: &Expression, _holds: bool, fact: &Self::Fact) -> Self::Fact {
+        fact.clone()
+    }
+}
+
+// the position of a statement: its block and its index in the block
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Point {
+    pub block: usize,
+    pub index: usize,
+}
+
+// the fact at every program point: points[b][i] holds before statement i of block b,
+// and the last one of a block after all its statements
+#[derive(Clone, PartialEq, Debug)]
+pub struct Solution<F> {
+    pub points: Vec<Vec<F>>,
+}
+
+impl<F> Solution<F> {
+    pub fn at(&self, block: usize, index: usize) -> &F {
+        &self.points[block][index]
+    }
+
+    pub fn blockEntry(&self, block: usize) -> &F {
+        &self.points[block][0]
+    }
+
+    pub fn blockExit(&self, block: usize) -> &F {
+        self.points[block].last().unwrap()
+    }
+}
+
+// the fact along the edge from block `from` to block `to`, given the fact flowing in at one end
+fn edgeFact<A: Analysis>(analysis: &A, cfg: &Cfg, from: usize, to: usize, fact: &A::Fact) -> A::Fact {
+    match &cfg.blocks[from].terminator {
+        Terminator::Branch {
+            condition,
+            then_block,
+            else_block,
+        } if then_block != else_block => analysis.edge(condition, *then_block == to, fact),
+        _ => fact.clone(),
+    }
+}
+
+// the facts at every point of block b, from the one at its start (forward) or end (backward)
+fn blockPoints<A: Analysis>(analysis: &A, cfg: &Cfg, b: usize, fact: A::Fact) -> Vec<A::Fact> {
+    let statements = &cfg.blocks[b].statements;
+    let mut points = vec![fact];
+    match analysis.direction() {
+        Direction::Forward => {
+            for (index, st) in statements.iter().enumerate() {
+                let next = analysis.transfer(st, Point { block: b, index }, points.last().unwrap());
+                points.push(next);
+            }
+        }
+        Direction::Backward => {
+            for (index, st) in statements.iter().enumerate().rev() {
+                let next = analysis.transfer(st, Point { block: b, index }, points.last().unwrap());
+                points.push(next);
+            }
+            points.reverse();
+        }
+    }
+    points
+}
+
+// blocks in reverse postorder from root, following next, then the ones not reached
+fn reversePostorder(root: usize, next: &Vec<Vec<usize>>) -> Vec<usize> {
+    let mut seen = vec![false; next.len()];
+    let mut order = Vec::new();
+    // each entry is a block and how many of its successors were looked at
+    let mut stack = vec![(root, 0)];
+    seen[root] = true;
+    while let Some((b, i)) = stack.pop() {
+        if i < next[b].len() {
+            stack.push((b, i + 1));
+            let s = next[b][i];
+            if !seen[s] {
+                seen[s] = true;
+                stack.push((s, 0));
+            }
+        } else {
+            order.push(b);
+        }
+    }
+    order.reverse();
+    order.extend((0..next.len()).filter(|b| !seen[*b]));
+    order
+}
+
+// the fact flowing into block b in the direction of the analysis, from the sources visited so far
+fn inflow<A: Analysis>(
+    analysis: &A,
+    cfg: &Cfg,
+    b: usize,
+    sources: &Vec<usize>,
+    outgoing: &Vec<A::Fact>,
+    visited: &Vec<bool>,
+) -> A::Fact {
+    let forward = analysis.direction() == Direction::Forward;
+    let start = if forward { cfg.entry } else { cfg.exit };
+    let mut fact = if b == start {
+        analysis.boundary()
+    } else {
+        A::Fact::bottom()
+    };
+    for s in sources.iter().filter(|s| visited[**s]) {
+        let along = if forward {
+            edgeFact(analysis, cfg, *s, b, &outgoing[*s])
+        } else {
+            edgeFact(analysis, cfg, b, *s, &outgoing[*s])
+        };
+        fact = fact.join(&along);
+    }
+    fact
+}
+
+// where control leaves block b in the direction of the analysis, given where it enters
+fn outflow<A: Analysis>(analysis: &A, cfg: &Cfg, b: usize, fact: &A::Fact) -> A::Fact {
+    let points = blockPoints(analysis, cfg, b, fact.clone());
+    match analysis.direction() {
+        Direction::Forward => points.last().unwrap().clone(),
+        Direction::Backward => points[0].clone(),
+    }
+}
+
+pub fn solve<A: Analysis>(analysis: &A, cfg: &Cfg) -> Solution<A::Fact> {
+    let n = cfg.blocks.len();
+    let forward = analysis.direction() == Direction::Forward;
+    let succs: Vec<Vec<usize>> = (0..n).map(|b| successors(cfg, b)).collect();
+    let preds = predecessors(cfg);
+    // where facts come from and where they go to, in the direction of the analysis
+    let (sources, targets, start) = if forward {
+        (&preds, &succs, cfg.entry)
+    } else {
+        (&succs, &preds, cfg.exit)
+    };
+    let widen_at: Vec<bool> = {
+        let headers: Vec<usize> = loopForest(cfg).iter().map(|l| l.header).collect();
+        (0..n).map(|b| headers.contains(&b)).collect()
+    };
+    // the fact where control enters a block in the direction of the analysis, and where it leaves it
+    let mut incoming: Vec<A::Fact> = vec![A::Fact::bottom(); n];
+    let mut outgoing: Vec<A::Fact> = vec![A::Fact::bottom(); n];
+    let mut visited = vec![false; n];
+    let order = reversePostorder(start, targets);
+    let mut queued = vec![false; n];
+    let mut worklist: VecDeque<usize> = VecDeque::new();
+    worklist.push_back(start);
+    queued[start] = true;
+    while let Some(b) = worklist.pop_front() {
+        queued[b] = false;
+        let fact = inflow(analysis, cfg, b, &sources[b], &outgoing, &visited);
+        let joined = incoming[b].join(&fact);
+        let fact = if widen_at[b] && visited[b] {
+            incoming[b].widen(&joined)
+        } else {
+            joined
+        };
+        if visited[b] && fact.leq(&incoming[b]) {
+            continue;
+        }
+        visited[b] = true;
+        outgoing[b] = outflow(analysis, cfg, b, &fact);
+        incoming[b] = fact;
+        // visit the targets in the order they come in reverse postorder
+        let mut next: Vec<usize> = targets[b].clone();
+        next.sort_by_key(|t| order.iter().position(|o| o == t));
+        for t in next {
+            if !queued[t] {
+                queued[t] = true;
+                worklist.push_back(t);
+            }
+        }
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for b in order.iter().copied().filter(|b| visited[*b]) {
+            let fact = inflow(analysis, cfg, b, &sources[b], &outgoing, &visited);
+            let fact = if widen_at[b] {
+                incoming[b].narrow(&fact)
+            } else {
+                fact
+            };
+            if fact != incoming[b] {
+                changed = true;
+                outgoing[b] = outflow(analysis, cfg, b, &fact);
+                incoming[b] = fact;
+            }
+        }
+    }
+    let points = (0..n)
+        .map(|b| {
+            if visited[b] {
+                blockPoints(analysis, cfg, b, incoming[b].clone())
+            } else {
+                vec![A::Fact::bottom(); cfg.blocks[b].statements.len() + 1]
+            }
+        })
+        .collect();
+    Solution { points }
+}
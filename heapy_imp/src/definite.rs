@@ -0,0 +1,355 @@
+/*
+Definite assignment of stack variables and definite allocation of pointers.
+
+A forward dataflow analysis over the control-flow graph finds the variables
+that are assigned on every path reaching a program point. Its facts are sets
+of variables met by intersection, with unreached points above every set:
+
+x := e, x : τ := e, read x, havoc x, x := coin(p), x := f(...)    assign x
+x := new(e), x := new { ... }, x := newarray(e, e), x = y        allocate x
+s1 || s2                                                         everything either branch assigns,
+                                                                 as both run to the end
+
+Every read of a stack variable, including the conditions of branches, has to
+come after its assignment, and every dereference of a pointer, i.e. !x, x.f,
+x[e], the length of x, !x := e, x.f := e, x[e] := e and the source y of
+x = y, after its allocation. Otherwise the report names a path from the start
+on which the variable is not assigned, as a sequence of blocks with the
+conditions taken between them.
+
+The body of a procedure is checked on its own graph with only the parameters
+assigned, and its return value is read at the exit. The branches of a parallel
+composition are checked on their own graphs too, starting from what is
+assigned before it. A declaration var x : τ does not assign x.
+*/
+
+use std::collections::BTreeSet;
+use std::collections::VecDeque;
+
+use crate::cfg::buildCfg;
+use crate::cfg::edgeLabel;
+use crate::cfg::edges;
+use crate::cfg::Cfg;
+use crate::cfg::Terminator;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Lattice;
+use crate::dataflow::Point;
+use crate::dataflow::Solution;
+use crate::imp::definedVariables;
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+// the variables assigned on every path, None where no path reaches
+#[derive(Clone, PartialEq, Debug)]
+pub struct Assigned(pub Option<BTreeSet<String>>);
+
+impl Assigned {
+    pub fn contains(&self, x: &String) -> bool {
+        match &self.0 {
+            Some(assigned) => assigned.contains(x),
+            None => true,
+        }
+    }
+}
+
+impl Lattice for Assigned {
+    fn bottom() -> Self {
+        Assigned(None)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (&self.0, &other.0) {
+            (Some(a1), Some(a2)) => Assigned(Some(a1.intersection(a2).cloned().collect())),
+            (Some(_), None) => self.clone(),
+            (None, _) => other.clone(),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a1), Some(a2)) => a2.is_subset(a1),
+            (Some(_), None) => true,
+            (None, other) => other.is_none(),
+        }
+    }
+}
+
+struct DefiniteAssignment {
+    initial: BTreeSet<String>,
+}
+
+impl Analysis for DefiniteAssignment {
+    type Fact = Assigned;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        Assigned(Some(self.initial.clone()))
+    }
+
+    fn transfer(&self, st: &Statement, _at: Point, fact: &Self::Fact) -> Self::Fact {
+        let mut assigned = match &fact.0 {
+            Some(assigned) => assigned.clone(),
+            None => return Assigned(None),
+        };
+        match st {
+            Statement::Parallel { st1, st2 } => {
+                for branch in [st1, st2] {
+                    match afterwards(branch, &assigned).0 {
+                        Some(more) => assigned.extend(more),
+                        None => return Assigned(None),
+                    }
+                }
+            }
+            _ => assigned.extend(definedVariables(st)),
+        }
+        Assigned(Some(assigned))
+    }
+}
+
+fn assignedIn(cfg: &Cfg, initial: &BTreeSet<String>) -> Solution<Assigned> {
+    solve(
+        &DefiniteAssignment {
+            initial: initial.clone(),
+        },
+        cfg,
+    )
+}
+
+// what is assigned after st when initial is assigned before it
+fn afterwards(st: &Statement, initial: &BTreeSet<String>) -> Assigned {
+    let cfg = buildCfg(st);
+    assignedIn(&cfg, initial).blockEntry(cfg.exit).clone()
+}
+
+// the variables exp reads, and whether it dereferences them
+fn expressionReads(exp: &Expression, out: &mut Vec<(String, bool)>) {
+    match exp {
+        Expression::StackVar { x } => out.push((x.clone(), false)),
+        Expression::HeapRead { x } | Expression::FieldRead { x, .. } | Expression::ArrayLength { x } => {
+            out.push((x.clone(), true))
+        }
+        Expression::ArrayRead { x, index } => {
+            out.push((x.clone(), true));
+            expressionReads(index, out);
+        }
+        Expression::NatConstant { .. } | Expression::BoolConstant { .. } => (),
+        Expression::Negation { ex1 } => expressionReads(ex1, out),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => {
+            expressionReads(ex1, out);
+            expressionReads(ex2, out);
+        }
+    }
+}
+
+// the variables a statement of a basic block reads, and whether it dereferences them
+fn statementReads(st: &Statement) -> Vec<(String, bool)> {
+    let mut out = Vec::new();
+    match st {
+        Statement::StackAssignment { ex1, .. }
+        | Statement::AnnotatedAssignment { ex1, .. }
+        | Statement::HeapNew { ex1, .. }
+        | Statement::Print { ex1 } => expressionReads(ex1, &mut out),
+        Statement::Assert { condition } | Statement::Assume { condition } => expressionReads(condition, &mut out),
+        Statement::HeapUpdate { x, ex1 } | Statement::FieldUpdate { x, ex1, .. } => {
+            out.push((x.clone(), true));
+            expressionReads(ex1, &mut out);
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            out.push((x.clone(), true));
+            expressionReads(index, &mut out);
+            expressionReads(ex1, &mut out);
+        }
+        Statement::HeapAlias { y, .. } => out.push((y.clone(), true)),
+        Statement::RecordNew { fields, .. } => {
+            for (_, e) in fields.iter() {
+                expressionReads(e, &mut out);
+            }
+        }
+        Statement::ArrayNew { size, ex1, .. } => {
+            expressionReads(size, &mut out);
+            expressionReads(ex1, &mut out);
+        }
+        Statement::Call { args, .. } => {
+            for e in args.iter() {
+                expressionReads(e, &mut out);
+            }
+        }
+        _ => (),
+    }
+    out
+}
+
+// a path from the entry to block b, through reached blocks that leave x unassigned if there is an x
+fn unassignedPath(cfg: &Cfg, solution: &Solution<Assigned>, b: usize, x: Option<&String>) -> String {
+    let edges = edges(cfg);
+    // for each block found, the edge it was found through, searching backwards from b
+    let mut next: Vec<Option<usize>> = vec![None; cfg.blocks.len()];
+    let mut found = vec![false; cfg.blocks.len()];
+    let mut queue = VecDeque::from([b]);
+    found[b] = true;
+    while let Some(c) = queue.pop_front() {
+        if c == cfg.entry {
+            break;
+        }
+        for (i, edge) in edges.iter().enumerate() {
+            let exit = solution.blockExit(edge.from);
+            let open = match x {
+                Some(x) => !exit.contains(x),
+                None => exit.0.is_some(),
+            };
+            if edge.to == c && !found[edge.from] && open {
+                found[edge.from] = true;
+                next[edge.from] = Some(i);
+                queue.push_back(edge.from);
+            }
+        }
+    }
+    if !found[cfg.entry] {
+        return format!("b{}", b);
+    }
+    let mut path = format!("b{}", cfg.entry);
+    let mut c = cfg.entry;
+    while let Some(i) = next[c] {
+        let label = edgeLabel(cfg, &edges[i]);
+        if label.is_empty() {
+            path += " -> ";
+        } else {
+            path = path + " -[" + label.trim_end() + "]-> ";
+        }
+        c = edges[i].to;
+        path += &format!("b{}", c);
+    }
+    path
+}
+
+fn report(x: &String, dereference: bool, what: String, path: String, out: &mut Vec<String>) {
+    if dereference {
+        out.push(format!(
+            "Pointer {} may be unallocated when {} dereferences it, on the path {}",
+            x, what, path
+        ));
+    } else {
+        out.push(format!(
+            "Variable {} may be unassigned when {} reads it, on the path {}",
+            x, what, path
+        ));
+    }
+}
+
+// checks the graph of st with initial assigned before it, within telling where st is;
+// ret is read at the exit
+fn checkGraph(
+    st: &Statement,
+    initial: &BTreeSet<String>,
+    ret: Option<&Expression>,
+    within: &str,
+    out: &mut Vec<String>,
+) {
+    let cfg = buildCfg(st);
+    let solution = assignedIn(&cfg, initial);
+    let pathTo = |b: usize, x: Option<&String>| unassignedPath(&cfg, &solution, b, x) + within;
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        for (i, st) in block.statements.iter().enumerate() {
+            let fact = solution.at(b, i);
+            let assigned = match &fact.0 {
+                Some(assigned) => assigned,
+                None => continue,
+            };
+            for (x, dereference) in statementReads(st) {
+                if !fact.contains(&x) {
+                    let what = format!("`{}`", printStatement(st.clone()));
+                    report(&x, dereference, what, pathTo(b, Some(&x)), out);
+                }
+            }
+            if let Statement::Parallel { st1, st2 } = st {
+                for branch in [st1, st2] {
+                    let within = format!(" of `{}` reached by {}", printStatement(*branch.clone()), pathTo(b, None));
+                    checkGraph(branch, assigned, None, &within, out);
+                }
+            }
+        }
+        let fact = solution.blockExit(b);
+        let (condition, ret) = match (&block.terminator, ret) {
+            (Terminator::Branch { condition, .. }, _) => (condition, false),
+            (Terminator::Exit, Some(ret)) if b == cfg.exit => (ret, true),
+            _ => continue,
+        };
+        let mut reads = Vec::new();
+        expressionReads(condition, &mut reads);
+        for (x, dereference) in reads {
+            if !fact.contains(&x) {
+                let what = if ret {
+                    format!("`return {}`", printExpression(condition.clone()).trim_end())
+                } else {
+                    format!("the condition `{}`", printExpression(condition.clone()).trim_end())
+                };
+                report(&x, dereference, what, pathTo(b, Some(&x)), out);
+            }
+        }
+    }
+}
+
+fn checkProcedures(st: &Statement, out: &mut Vec<String>) {
+    match st {
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => {
+            let initial = params.iter().map(|(p, _)| p.clone()).collect();
+            let within = format!(" of procedure {}", name);
+            checkGraph(body, &initial, ret.as_ref().map(|(_, e)| e), &within, out);
+            checkProcedures(body, out);
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => {
+            checkProcedures(st1, out);
+            checkProcedures(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            checkProcedures(then_branch, out);
+            checkProcedures(else_branch, out);
+        }
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => checkProcedures(st, out),
+        _ => (),
+    }
+}
+
+// every read of a stack variable that may not be assigned yet and every dereference
+// of a pointer that may not be allocated yet, with nothing assigned at the start
+pub fn checkDefiniteAssignment(st: &Statement) -> Vec<String> {
+    let mut out = Vec::new();
+    checkGraph(st, &BTreeSet::new(), None, "", &mut out);
+    checkProcedures(st, &mut out);
+    out
+}
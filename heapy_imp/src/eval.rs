@@ -0,0 +1,980 @@
+/*
+Big-step execution of Heapy IMP programs.
+
+The store maps variables to values and the heap is a growing list of cells,
+each a nat, a record or an array, addressed by locations. Subtraction is truncated (m - n = 0 when n > m),
+and division or modulo by zero, arithmetic overflow of the i32-backed nats,
+out-of-bounds array indices and reads of unbound variables are runtime errors
+rather than undefined behaviour.
+
+A call evaluates its arguments in the caller's store, suspends that store on
+the call stack and runs the procedure body in a store holding only the
+parameters. Pointers are copied like any other value, so the callee shares
+the caller's heap cells. Nesting calls deeper than the depth limit is an error.
+
+A failing assert is an error reporting the state it failed in, while a failing
+assume silently ends the run: everything after it, including the rest of the
+callers, is skipped.
+
+print and read go through an Io, which is the terminal unless the caller of
+executeWithIo supplies another one, e.g. a ScriptedIo in tests.
+
+Which branch of s1 [] s2 runs and the value havoc picks are decided by an
+Oracle. Plain execution uses a SeededOracle, and executeAll replays the
+program once for every sequence of decisions to enumerate all of its runs,
+which only ends for programs with finitely many runs. In that mode havoc
+picks a nat up to a given bound instead of any nat.
+
+s1 || s2 interleaves the statements of its branches as described in
+parallel.rs, and atomic { s } outside of it simply runs s.
+
+s1 [p] s2 and coin(p) flip a coin that comes up heads with probability p.
+A SeededOracle samples it, and for every other Oracle, including the one of
+executeAll, it is a choice between both outcomes. Each run of executeAll also
+carries its probability, the product of the probabilities of its coin flips,
+so for a program without nondeterminism whose runs are all finite and few
+enough, outputDistribution adds up exactly how likely each ending is.
+*/
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::printType;
+use crate::imp::ExType;
+use crate::imp::Expression;
+use crate::imp::Statement;
+use crate::parallel::executeParallel;
+use crate::rational::printRational;
+use crate::rational::Rational;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Nat(i32),
+    Bool(bool),
+    Pointer(usize),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum HeapCell {
+    Nat(i32),
+    Record(Vec<(String, Value)>),
+    Array(Vec<i32>),
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct State {
+    pub store: HashMap<String, Value>,
+    pub heap: Vec<HeapCell>,
+}
+
+// a suspended caller: the procedure it called and its own store
+#[derive(Clone, PartialEq, Debug)]
+pub struct Frame {
+    pub procedure: String,
+    pub store: HashMap<String, Value>,
+}
+
+// how a statement that did not go wrong finished
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Normal,
+    // an assume did not hold, so the rest of the run is cut off
+    Blocked,
+    // a break or continue on its way to the innermost loop
+    Break,
+    Continue,
+}
+
+pub const MAX_CALL_DEPTH: usize = 200;
+pub const MAX_ARRAY_SIZE: i32 = 1 << 20;
+
+// where print writes to and read reads from
+pub trait Io {
+    // the next input, of type tp if it is known
+    fn read(&mut self, tp: Option<&ExType>) -> Result<Value, String>;
+    fn print(&mut self, v: &Value) -> Result<(), String>;
+}
+
+impl<T: Io + ?Sized> Io for &mut T {
+    fn read(&mut self, tp: Option<&ExType>) -> Result<Value, String> {
+        (**self).read(tp)
+    }
+
+    fn print(&mut self, v: &Value) -> Result<(), String> {
+        (**self).print(v)
+    }
+}
+
+// one value per line on standard input and output
+pub struct StdIo;
+
+impl Io for StdIo {
+    fn read(&mut self, tp: Option<&ExType>) -> Result<Value, String> {
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) => Err(String::from("end of input")),
+            Ok(_) => parseValue(line.trim(), tp),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn print(&mut self, v: &Value) -> Result<(), String> {
+        println!("{}", printValue(v.clone()));
+        Ok(())
+    }
+}
+
+// reads from a fixed list of inputs and records everything printed
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct ScriptedIo {
+    pub input: VecDeque<Value>,
+    pub output: Vec<Value>,
+}
+
+impl ScriptedIo {
+    pub fn new(input: Vec<Value>) -> ScriptedIo {
+        ScriptedIo {
+            input: input.into(),
+            output: Vec::new(),
+        }
+    }
+}
+
+impl Io for ScriptedIo {
+    fn read(&mut self, tp: Option<&ExType>) -> Result<Value, String> {
+        match self.input.pop_front() {
+            Some(v) if tp.map_or(true, |tp| hasType(&v, tp)) => Ok(v),
+            Some(v) => Err(format!(
+                "input {} is not of {}",
+                printValue(v),
+                printType(tp.unwrap().clone())
+            )),
+            None => Err(String::from("end of input")),
+        }
+    }
+
+    fn print(&mut self, v: &Value) -> Result<(), String> {
+        self.output.push(v.clone());
+        Ok(())
+    }
+}
+
+fn hasType(v: &Value, tp: &ExType) -> bool {
+    matches!(
+        (v, tp),
+        (Value::Nat(_), ExType::NatType) | (Value::Bool(_), ExType::BoolType)
+    )
+}
+
+// a nat or bool written the way printValue writes it
+fn parseValue(text: &str, tp: Option<&ExType>) -> Result<Value, String> {
+    let v = match text {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match text.parse::<i32>() {
+            Ok(n) if n >= 0 => Value::Nat(n),
+            _ => return Err(format!("input {} is not a nat or bool", text)),
+        },
+    };
+    match tp {
+        Some(tp) if !hasType(&v, tp) => Err(format!("input {} is not of {}", text, printType(tp.clone()))),
+        _ => Ok(v),
+    }
+}
+
+// decides how nondeterminism is resolved
+pub trait Oracle {
+    // one of the alternatives 0, ..., n - 1
+    fn choose(&mut self, n: usize) -> usize;
+
+    // heads with probability p, or either side when the oracle does not sample
+    fn flip(&mut self, p: &Rational) -> bool {
+        if p.num == 0 || p.num == p.den {
+            p.num != 0
+        } else {
+            self.choose(2) == 0
+        }
+    }
+}
+
+// pseudo-random decisions (xorshift64*), the same for the same seed
+pub struct SeededOracle {
+    state: u64,
+}
+
+impl SeededOracle {
+    pub fn new(seed: u64) -> SeededOracle {
+        // xorshift never leaves 0, so the seed is mixed with an odd constant
+        SeededOracle {
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Oracle for SeededOracle {
+    fn choose(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn flip(&mut self, p: &Rational) -> bool {
+        (self.next() as u128 % p.den as u128) < p.num as u128
+    }
+}
+
+// follows a given prefix of decisions, takes the first alternative after it
+// and records every decision made, for enumerating runs depth first
+struct ReplayOracle {
+    prefix: Vec<usize>,
+    // the alternative taken and the number of alternatives, for every decision
+    taken: Vec<(usize, usize)>,
+    // the product of the probabilities of the coin flips, None once it no longer fits
+    probability: Option<Rational>,
+    // whether a decision was not a coin flip
+    nondeterministic: bool,
+}
+
+impl ReplayOracle {
+    fn new(prefix: Vec<usize>) -> ReplayOracle {
+        ReplayOracle {
+            prefix,
+            taken: Vec::new(),
+            probability: Some(Rational::one()),
+            nondeterministic: false,
+        }
+    }
+
+    fn decide(&mut self, n: usize) -> usize {
+        let i = self.prefix.get(self.taken.len()).copied().unwrap_or(0);
+        self.taken.push((i, n));
+        i
+    }
+}
+
+impl Oracle for ReplayOracle {
+    fn choose(&mut self, n: usize) -> usize {
+        self.nondeterministic = true;
+        self.decide(n)
+    }
+
+    // a coin that cannot come up on one side is not a decision
+    fn flip(&mut self, p: &Rational) -> bool {
+        if p.num == 0 || p.num == p.den {
+            return p.num != 0;
+        }
+        let heads = self.decide(2) == 0;
+        let side = if heads { Some(*p) } else { Rational::one().sub(p) };
+        self.probability = match (self.probability, side) {
+            (Some(q), Some(side)) => q.mul(&side),
+            _ => None,
+        };
+        heads
+    }
+}
+
+impl<T: Oracle + ?Sized> Oracle for &mut T {
+    fn choose(&mut self, n: usize) -> usize {
+        (**self).choose(n)
+    }
+
+    fn flip(&mut self, p: &Rational) -> bool {
+        (**self).flip(p)
+    }
+}
+
+// what execution needs besides the state
+pub struct Env<'a> {
+    procedures: HashMap<String, &'a Statement>,
+    // the callers of the running procedure, innermost last
+    pub calls: Vec<Frame>,
+    pub max_depth: usize,
+    pub io: Box<dyn Io + 'a>,
+    pub oracle: Box<dyn Oracle + 'a>,
+    // havoc picks a nat from 0 to havoc_max
+    pub havoc_max: i32,
+    // types of the declared variables, for havoc on a variable without a value
+    declared: HashMap<String, ExType>,
+}
+
+impl<'a> Env<'a> {
+    // an environment knowing every procedure declared anywhere in program
+    pub fn new(program: &'a Statement) -> Env<'a> {
+        let mut env = Env {
+            procedures: HashMap::new(),
+            calls: Vec::new(),
+            max_depth: MAX_CALL_DEPTH,
+            io: Box::new(StdIo),
+            oracle: Box::new(SeededOracle::new(0)),
+            havoc_max: i32::MAX,
+            declared: HashMap::new(),
+        };
+        env.collect(program);
+        env
+    }
+
+    fn collect(&mut self, st: &'a Statement) {
+        match st {
+            Statement::Procedure { name, body, .. } => {
+                self.procedures.insert(name.clone(), st);
+                self.collect(body);
+            }
+            Statement::Sequence { st1, st2 } => {
+                self.collect(st1);
+                self.collect(st2);
+            }
+            Statement::IfThenElse {
+                then_branch,
+                else_branch,
+                ..
+            }
+            | Statement::Choice {
+                st1: then_branch,
+                st2: else_branch,
+            }
+            | Statement::Parallel {
+                st1: then_branch,
+                st2: else_branch,
+            }
+            | Statement::ProbChoice {
+                st1: then_branch,
+                st2: else_branch,
+                ..
+            } => {
+                self.collect(then_branch);
+                self.collect(else_branch);
+            }
+            Statement::While { st, .. }
+            | Statement::For { st, .. }
+            | Statement::DoWhile { st, .. }
+            | Statement::Atomic { st } => self.collect(st),
+            _ => (),
+        }
+    }
+}
+
+pub fn printValue(v: Value) -> String {
+    match v {
+        Value::Nat(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Pointer(l) => String::from("loc ") + &l.to_string(),
+    }
+}
+
+pub fn printState(state: &State) -> String {
+    let mut vars: Vec<&String> = state.store.keys().collect();
+    vars.sort();
+    let mut out = String::from("store: [ ");
+    for x in vars {
+        out = out + x + ": " + &printValue(state.store.get(x).unwrap().clone()) + ", ";
+    }
+    out += " ] heap: [ ";
+    for (l, cell) in state.heap.iter().enumerate() {
+        out = out + &l.to_string() + ": " + &printHeapCell(cell.clone()) + ", ";
+    }
+    out + " ]"
+}
+
+pub fn printHeapCell(cell: HeapCell) -> String {
+    match cell {
+        HeapCell::Nat(n) => n.to_string(),
+        HeapCell::Record(fields) => {
+            let printed: Vec<String> = fields
+                .into_iter()
+                .map(|(f, v)| f + ": " + &printValue(v))
+                .collect();
+            String::from("{ ") + &printed.join(", ") + " }"
+        }
+        HeapCell::Array(elements) => {
+            let printed: Vec<String> = elements.iter().map(|n| n.to_string()).collect();
+            String::from("[") + &printed.join(", ") + "]"
+        }
+    }
+}
+
+fn lookup(x: &String, state: &State) -> Result<Value, String> {
+    match state.store.get(x) {
+        Some(v) => Ok(v.clone()),
+        None => Err(format!("unbound variable: {}", x)),
+    }
+}
+
+fn location(x: &String, state: &State) -> Result<usize, String> {
+    match lookup(x, state)? {
+        Value::Pointer(l) if l < state.heap.len() => Ok(l),
+        Value::Pointer(l) => Err(format!("dangling pointer {} to loc {}", x, l)),
+        _ => Err(format!("variable: {} is not a pointer", x)),
+    }
+}
+
+fn readCell(x: &String, state: &State) -> Result<i32, String> {
+    match &state.heap[location(x, state)?] {
+        HeapCell::Nat(n) => Ok(*n),
+        _ => Err(format!("variable: {} is not a pointer", x)),
+    }
+}
+
+// the position of `field` in the record x points to
+fn fieldSlot(x: &String, field: &String, state: &State) -> Result<(usize, usize), String> {
+    let l = location(x, state)?;
+    match &state.heap[l] {
+        HeapCell::Record(fields) => match fields.iter().position(|(f, _)| f == field) {
+            Some(i) => Ok((l, i)),
+            None => Err(format!("record {} has no field {}", x, field)),
+        },
+        _ => Err(format!("variable: {} is not a record", x)),
+    }
+}
+
+// the location of the array x points to and the checked position of `index` in it
+fn arraySlot(x: &String, index: &Expression, state: &State) -> Result<(usize, usize), String> {
+    let i = evalNat(index, state)?;
+    let l = location(x, state)?;
+    match &state.heap[l] {
+        HeapCell::Array(elements) => {
+            if i < 0 || i as usize >= elements.len() {
+                Err(format!(
+                    "index {} out of bounds for array {} of length {}",
+                    i,
+                    x,
+                    elements.len()
+                ))
+            } else {
+                Ok((l, i as usize))
+            }
+        }
+        _ => Err(format!("variable: {} is not an array", x)),
+    }
+}
+
+fn evalNat(exp: &Expression, state: &State) -> Result<i32, String> {
+    match evalExp(exp, state)? {
+        Value::Nat(n) => Ok(n),
+        _ => Err(format!("Expression: {} should be of NatType", printExpression(exp.clone()))),
+    }
+}
+
+fn evalBool(exp: &Expression, state: &State) -> Result<bool, String> {
+    match evalExp(exp, state)? {
+        Value::Bool(b) => Ok(b),
+        _ => Err(format!("Expression: {} should be of BoolType", printExpression(exp.clone()))),
+    }
+}
+
+fn overflow(exp: &Expression) -> String {
+    format!("arithmetic overflow in {}", printExpression(exp.clone()))
+}
+
+pub fn evalExp(exp: &Expression, state: &State) -> Result<Value, String> {
+    match exp {
+        Expression::StackVar { x } => lookup(x, state),
+        Expression::HeapRead { x } => Ok(Value::Nat(readCell(x, state)?)),
+        Expression::NatConstant { n } => Ok(Value::Nat(*n)),
+        Expression::BoolConstant { b } => Ok(Value::Bool(*b)),
+        Expression::Add { ex1, ex2 } => {
+            let (n1, n2) = (evalNat(ex1, state)?, evalNat(ex2, state)?);
+            n1.checked_add(n2).map(Value::Nat).ok_or(overflow(exp))
+        }
+        Expression::Sub { ex1, ex2 } => {
+            let (n1, n2) = (evalNat(ex1, state)?, evalNat(ex2, state)?);
+            Ok(Value::Nat(if n2 > n1 { 0 } else { n1 - n2 }))
+        }
+        Expression::Mul { ex1, ex2 } => {
+            let (n1, n2) = (evalNat(ex1, state)?, evalNat(ex2, state)?);
+            n1.checked_mul(n2).map(Value::Nat).ok_or(overflow(exp))
+        }
+        Expression::Div { ex1, ex2 } | Expression::Mod { ex1, ex2 } => {
+            let (n1, n2) = (evalNat(ex1, state)?, evalNat(ex2, state)?);
+            if n2 == 0 {
+                Err(format!("division by zero in {}", printExpression(exp.clone())))
+            } else if let Expression::Div { .. } = exp {
+                Ok(Value::Nat(n1 / n2))
+            } else {
+                Ok(Value::Nat(n1 % n2))
+            }
+        }
+        Expression::Negation { ex1 } => Ok(Value::Bool(!evalBool(ex1, state)?)),
+        Expression::Conjunction { ex1, ex2 } => {
+            let (b1, b2) = (evalBool(ex1, state)?, evalBool(ex2, state)?);
+            Ok(Value::Bool(b1 && b2))
+        }
+        Expression::Disjunction { ex1, ex2 } => {
+            let (b1, b2) = (evalBool(ex1, state)?, evalBool(ex2, state)?);
+            Ok(Value::Bool(b1 || b2))
+        }
+        Expression::Implication { ex1, ex2 } => {
+            let (b1, b2) = (evalBool(ex1, state)?, evalBool(ex2, state)?);
+            Ok(Value::Bool(!b1 || b2))
+        }
+        Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 } => {
+            let (n1, n2) = (evalNat(ex1, state)?, evalNat(ex2, state)?);
+            Ok(Value::Bool(match exp {
+                Expression::Comparision { .. } => n1 <= n2,
+                Expression::LessThan { .. } => n1 < n2,
+                Expression::GreaterEqual { .. } => n1 >= n2,
+                _ => n1 > n2,
+            }))
+        }
+        // pointers are equal when they hold the same location
+        Expression::Equal { ex1, ex2 } => {
+            Ok(Value::Bool(evalExp(ex1, state)? == evalExp(ex2, state)?))
+        }
+        Expression::NotEqual { ex1, ex2 } => {
+            Ok(Value::Bool(evalExp(ex1, state)? != evalExp(ex2, state)?))
+        }
+        Expression::FieldRead { x, field } => {
+            let (l, i) = fieldSlot(x, field, state)?;
+            match &state.heap[l] {
+                HeapCell::Record(fields) => Ok(fields[i].1.clone()),
+                _ => Err(format!("variable: {} is not a record", x)),
+            }
+        }
+        Expression::ArrayRead { x, index } => {
+            let (l, i) = arraySlot(x, index, state)?;
+            match &state.heap[l] {
+                HeapCell::Array(elements) => Ok(Value::Nat(elements[i])),
+                _ => Err(format!("variable: {} is not an array", x)),
+            }
+        }
+        Expression::ArrayLength { x } => match &state.heap[location(x, state)?] {
+            HeapCell::Array(elements) => Ok(Value::Nat(elements.len() as i32)),
+            _ => Err(format!("variable: {} is not an array", x)),
+        },
+    }
+}
+
+pub fn execute(st: &Statement, state: &mut State) -> Result<Outcome, String> {
+    executeIn(st, state, &mut Env::new(st))
+}
+
+// like execute, but nondeterminism is resolved by a SeededOracle with the given seed
+pub fn executeSeeded(st: &Statement, state: &mut State, seed: u64) -> Result<Outcome, String> {
+    let mut env = Env::new(st);
+    env.oracle = Box::new(SeededOracle::new(seed));
+    executeIn(st, state, &mut env)
+}
+
+// one complete run: the decisions taken, how likely its coin flips are,
+// how it ended and the state it ended in
+#[derive(Clone, PartialEq, Debug)]
+pub struct Run {
+    pub choices: Vec<usize>,
+    pub probability: Rational,
+    pub result: Result<Outcome, String>,
+    pub state: State,
+}
+
+// every run of st from state, with havoc picking nats from 0 to havoc_max,
+// or an error if there are more than max_runs of them
+pub fn executeAll(
+    st: &Statement,
+    state: &State,
+    havoc_max: i32,
+    max_runs: usize,
+) -> Result<Vec<Run>, String> {
+    Ok(enumerateRuns(st, state, havoc_max, max_runs)?
+        .into_iter()
+        .map(|(run, _)| run)
+        .collect())
+}
+
+// like executeAll, also telling for every run whether it made a decision other than a coin flip
+fn enumerateRuns(
+    st: &Statement,
+    state: &State,
+    havoc_max: i32,
+    max_runs: usize,
+) -> Result<Vec<(Run, bool)>, String> {
+    if havoc_max < 0 {
+        return Err(format!("negative havoc bound {}", havoc_max));
+    }
+    let mut runs = Vec::new();
+    let mut prefix = Vec::new();
+    loop {
+        if runs.len() == max_runs {
+            return Err(format!("more than {} runs", max_runs));
+        }
+        let mut oracle = ReplayOracle::new(std::mem::take(&mut prefix));
+        let mut env = Env::new(st);
+        env.oracle = Box::new(&mut oracle);
+        env.havoc_max = havoc_max;
+        let mut end = state.clone();
+        let result = executeIn(st, &mut end, &mut env);
+        drop(env);
+        let choices: Vec<usize> = oracle.taken.iter().map(|(i, _)| *i).collect();
+        let probability = match oracle.probability {
+            Some(p) => p,
+            None => return Err(format!("the probability of the run {:?} is too small to represent", choices)),
+        };
+        runs.push((
+            Run {
+                choices,
+                probability,
+                result,
+                state: end,
+            },
+            oracle.nondeterministic,
+        ));
+        // the next run takes the next alternative at the last decision that has one left
+        let mut taken = oracle.taken;
+        loop {
+            match taken.pop() {
+                Some((i, n)) if i + 1 < n => {
+                    prefix = taken.iter().map(|(i, _)| *i).collect();
+                    prefix.push(i + 1);
+                    break;
+                }
+                Some(_) => (),
+                None => return Ok(runs),
+            }
+        }
+    }
+}
+
+// a way for a program to end and how likely it is
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ending {
+    pub probability: Rational,
+    pub result: Result<Outcome, String>,
+    pub state: State,
+}
+
+// the exact probability of every way st can end when run from state,
+// or an error if it has a nondeterministic choice or more than max_runs runs
+pub fn outputDistribution(st: &Statement, state: &State, max_runs: usize) -> Result<Vec<Ending>, String> {
+    let mut endings: Vec<Ending> = Vec::new();
+    for (run, nondeterministic) in enumerateRuns(st, state, 0, max_runs)? {
+        if nondeterministic {
+            return Err(format!(
+                "the run {:?} makes a nondeterministic choice, which has no probability",
+                run.choices
+            ));
+        }
+        match endings
+            .iter_mut()
+            .find(|ending| ending.result == run.result && ending.state == run.state)
+        {
+            Some(ending) => {
+                ending.probability = match ending.probability.add(&run.probability) {
+                    Some(p) => p,
+                    None => return Err(String::from("probabilities too large to add up")),
+                }
+            }
+            None => endings.push(Ending {
+                probability: run.probability,
+                result: run.result,
+                state: run.state,
+            }),
+        }
+    }
+    Ok(endings)
+}
+
+// the expected value of the nat variable x at the end of the runs that end normally
+pub fn expectedValue(endings: &Vec<Ending>, x: &str) -> Result<Rational, String> {
+    let (mut total, mut sum) = (Rational::zero(), Rational::zero());
+    for ending in endings.iter().filter(|ending| ending.result == Ok(Outcome::Normal)) {
+        let n = match ending.state.store.get(x) {
+            Some(Value::Nat(n)) => *n,
+            Some(v) => return Err(format!("{} is {}, not a nat", x, printValue(v.clone()))),
+            None => return Err(format!("{} is unbound at the end of a run", x)),
+        };
+        let weighted = ending.probability.mul(&Rational::integer(n as i128));
+        match (total.add(&ending.probability), weighted.and_then(|w| sum.add(&w))) {
+            (Some(t), Some(s)) => {
+                total = t;
+                sum = s;
+            }
+            _ => return Err(String::from("expected value too large to represent")),
+        }
+    }
+    if total.num == 0 {
+        return Err(String::from("no run ends normally"));
+    }
+    // runs cut off by an assume do not count, so the others are weighed as if they were all
+    sum.div(&total).ok_or(String::from("expected value too large to represent"))
+}
+
+// like execute, but print and read go through io
+pub fn executeWithIo(st: &Statement, state: &mut State, io: &mut dyn Io) -> Result<Outcome, String> {
+    let mut env = Env::new(st);
+    env.io = Box::new(io);
+    executeIn(st, state, &mut env)
+}
+
+pub fn executeIn(st: &Statement, state: &mut State, env: &mut Env) -> Result<Outcome, String> {
+    match st {
+        Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => {
+            let v = evalExp(ex1, state)?;
+            state.store.insert(x.clone(), v);
+            Ok(Outcome::Normal)
+        }
+        Statement::HeapUpdate { x, ex1 } => {
+            let n = evalNat(ex1, state)?;
+            readCell(x, state)?;
+            let l = location(x, state)?;
+            state.heap[l] = HeapCell::Nat(n);
+            Ok(Outcome::Normal)
+        }
+        Statement::HeapAlias { x, y } => {
+            let l = location(y, state)?;
+            state.store.insert(x.clone(), Value::Pointer(l));
+            Ok(Outcome::Normal)
+        }
+        Statement::HeapNew { x, ex1 } => {
+            let n = evalNat(ex1, state)?;
+            state.heap.push(HeapCell::Nat(n));
+            state
+                .store
+                .insert(x.clone(), Value::Pointer(state.heap.len() - 1));
+            Ok(Outcome::Normal)
+        }
+        Statement::RecordNew { x, fields } => {
+            let mut values = Vec::new();
+            for (f, e) in fields {
+                values.push((f.clone(), evalExp(e, state)?));
+            }
+            state.heap.push(HeapCell::Record(values));
+            state
+                .store
+                .insert(x.clone(), Value::Pointer(state.heap.len() - 1));
+            Ok(Outcome::Normal)
+        }
+        Statement::FieldUpdate { x, field, ex1 } => {
+            let v = evalExp(ex1, state)?;
+            let (l, i) = fieldSlot(x, field, state)?;
+            if let HeapCell::Record(fields) = &mut state.heap[l] {
+                fields[i].1 = v;
+            }
+            Ok(Outcome::Normal)
+        }
+        Statement::ArrayNew { x, size, ex1 } => {
+            let n = evalNat(size, state)?;
+            let v = evalNat(ex1, state)?;
+            if n < 0 {
+                return Err(format!("negative array size {} in {}", n, printStatement(st.clone())));
+            }
+            if n > MAX_ARRAY_SIZE {
+                return Err(format!("array size {} too large in {}", n, printStatement(st.clone())));
+            }
+            state.heap.push(HeapCell::Array(vec![v; n as usize]));
+            state
+                .store
+                .insert(x.clone(), Value::Pointer(state.heap.len() - 1));
+            Ok(Outcome::Normal)
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            let v = evalNat(ex1, state)?;
+            let (l, i) = arraySlot(x, index, state)?;
+            if let HeapCell::Array(elements) = &mut state.heap[l] {
+                elements[i] = v;
+            }
+            Ok(Outcome::Normal)
+        }
+        Statement::Sequence { st1, st2 } => {
+            match executeIn(st1, state, env)? {
+                Outcome::Normal => executeIn(st2, state, env),
+                outcome => Ok(outcome),
+            }
+        }
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            if evalBool(condition, state)? {
+                executeIn(then_branch, state, env)
+            } else {
+                executeIn(else_branch, state, env)
+            }
+        }
+        Statement::Declaration { x, tp } => {
+            env.declared.insert(x.clone(), tp.clone());
+            Ok(Outcome::Normal)
+        }
+        Statement::Skip | Statement::Procedure { .. } => Ok(Outcome::Normal),
+        Statement::While { condition, st } => {
+            while evalBool(condition, state)? {
+                match executeIn(st, state, env)? {
+                    Outcome::Break => break,
+                    Outcome::Blocked => return Ok(Outcome::Blocked),
+                    Outcome::Normal | Outcome::Continue => (),
+                }
+            }
+            Ok(Outcome::Normal)
+        }
+        Statement::Call { x, name, args } => {
+            let (params, body, ret) = match env.procedures.get(name).copied() {
+                Some(Statement::Procedure {
+                    params, body, ret, ..
+                }) => (params, body, ret),
+                _ => return Err(format!("undefined procedure {}", name)),
+            };
+            if params.len() != args.len() {
+                return Err(format!(
+                    "procedure {} takes {} arguments but {} are passed",
+                    name,
+                    params.len(),
+                    args.len()
+                ));
+            }
+            if env.calls.len() >= env.max_depth {
+                return Err(format!(
+                    "call to {} exceeds the maximum call depth of {}",
+                    name, env.max_depth
+                ));
+            }
+            let mut store = HashMap::new();
+            for ((p, _), e) in params.iter().zip(args) {
+                store.insert(p.clone(), evalExp(e, state)?);
+            }
+            let caller = std::mem::replace(&mut state.store, store);
+            env.calls.push(Frame {
+                procedure: name.clone(),
+                store: caller,
+            });
+            let outcome = executeIn(body, state, env);
+            let result = match (&outcome, ret) {
+                (Ok(Outcome::Normal), Some((_, ex1))) => evalExp(ex1, state).map(Some),
+                _ => Ok(None),
+            };
+            state.store = env.calls.pop().unwrap().store;
+            // the checker keeps break and continue from leaving a procedure body
+            if outcome? == Outcome::Blocked {
+                return Ok(Outcome::Blocked);
+            }
+            match (x, result?) {
+                (Some(x), Some(v)) => {
+                    state.store.insert(x.clone(), v);
+                    Ok(Outcome::Normal)
+                }
+                (Some(_), None) => Err(format!("procedure {} does not return a value", name)),
+                (None, _) => Ok(Outcome::Normal),
+            }
+        }
+        Statement::Assert { condition } => {
+            if evalBool(condition, state)? {
+                Ok(Outcome::Normal)
+            } else {
+                let place = match env.calls.last() {
+                    Some(frame) => String::from("procedure ") + &frame.procedure,
+                    None => String::from("the main program"),
+                };
+                Err(format!(
+                    "assertion `{}` failed in {} with {}",
+                    printStatement(st.clone()),
+                    place,
+                    printState(state)
+                ))
+            }
+        }
+        Statement::Assume { condition } => {
+            if evalBool(condition, state)? {
+                Ok(Outcome::Normal)
+            } else {
+                Ok(Outcome::Blocked)
+            }
+        }
+        Statement::For { x, from, to, st } => {
+            let (n1, n2) = (evalNat(from, state)?, evalNat(to, state)?);
+            for n in n1..=n2 {
+                state.store.insert(x.clone(), Value::Nat(n));
+                match executeIn(st, state, env)? {
+                    Outcome::Break => break,
+                    Outcome::Blocked => return Ok(Outcome::Blocked),
+                    Outcome::Normal | Outcome::Continue => (),
+                }
+            }
+            Ok(Outcome::Normal)
+        }
+        Statement::DoWhile { st, condition } => {
+            loop {
+                match executeIn(st, state, env)? {
+                    Outcome::Break => break,
+                    Outcome::Blocked => return Ok(Outcome::Blocked),
+                    Outcome::Normal | Outcome::Continue => (),
+                }
+                if !evalBool(condition, state)? {
+                    break;
+                }
+            }
+            Ok(Outcome::Normal)
+        }
+        Statement::Choice { st1, st2 } => {
+            if env.oracle.choose(2) == 0 {
+                executeIn(st1, state, env)
+            } else {
+                executeIn(st2, state, env)
+            }
+        }
+        Statement::Havoc { x } => {
+            let tp = match state.store.get(x) {
+                Some(Value::Nat(_)) => Some(ExType::NatType),
+                Some(Value::Bool(_)) => Some(ExType::BoolType),
+                _ => env.declared.get(x).cloned(),
+            };
+            let v = match tp {
+                Some(ExType::NatType) if env.havoc_max < 0 => {
+                    return Err(format!("negative havoc bound {} in {}", env.havoc_max, printStatement(st.clone())))
+                }
+                Some(ExType::NatType) => Value::Nat(env.oracle.choose(env.havoc_max as usize + 1) as i32),
+                Some(ExType::BoolType) => Value::Bool(env.oracle.choose(2) == 1),
+                _ => return Err(format!("cannot havoc {} without knowing its type", x)),
+            };
+            state.store.insert(x.clone(), v);
+            Ok(Outcome::Normal)
+        }
+        Statement::ProbChoice { st1, st2, p } => {
+            if !p.isProbability() {
+                return Err(format!("probability {} is not between 0 and 1", printRational(*p)));
+            }
+            if env.oracle.flip(p) {
+                executeIn(st1, state, env)
+            } else {
+                executeIn(st2, state, env)
+            }
+        }
+        Statement::Coin { x, p } => {
+            if !p.isProbability() {
+                return Err(format!("probability {} is not between 0 and 1", printRational(*p)));
+            }
+            let b = env.oracle.flip(p);
+            state.store.insert(x.clone(), Value::Bool(b));
+            Ok(Outcome::Normal)
+        }
+        Statement::Parallel { .. } => executeParallel(st, state, env),
+        Statement::Atomic { st } => executeIn(st, state, env),
+        Statement::Break => Ok(Outcome::Break),
+        Statement::Continue => Ok(Outcome::Continue),
+        Statement::Print { ex1 } => {
+            let v = evalExp(ex1, state)?;
+            env.io.print(&v)?;
+            Ok(Outcome::Normal)
+        }
+        // without an annotation, a read keeps the type x already has
+        Statement::Read { x, tp } => {
+            let tp = tp.clone().or(match state.store.get(x) {
+                Some(Value::Nat(_)) => Some(ExType::NatType),
+                Some(Value::Bool(_)) => Some(ExType::BoolType),
+                _ => None,
+            });
+            let v = env
+                .io
+                .read(tp.as_ref())
+                .map_err(|e| format!("{} in `{}`", e, printStatement(st.clone())))?;
+            state.store.insert(x.clone(), v);
+            Ok(Outcome::Normal)
+        }
+    }
+}
@@ -8,12 +8,27 @@ e ::= x read from store
     | !x read from heap
     | N | b constants
     | e + e addition
+    | e - e truncated subtraction (monus)
+    | e * e multiplication
+    | e / e division
+    | e % e modulo
     | ¬ e boolean negation
     | e ∧ e boolean conjunction
+    | e ∨ e boolean disjunction
+    | e ⇒ e boolean implication
     | e ≤ e comparison
+    | e < e | e ≥ e | e > e comparisons
+    | e = e | e ≠ e equality on nats, bools and pointers
+    | x.f read field of heap record
+    | x[e] read element of heap array
+    | len(x) length of heap array
 */
 
 use std::{collections::HashMap, fmt::format, marker};
+use std::collections::HashSet;
+
+use crate::rational::printRational;
+use crate::rational::Rational;
 
 #[derive(Clone)]
 pub enum Expression {
@@ -33,6 +48,22 @@ pub enum Expression {
         ex1: Box<Expression>,
         ex2: Box<Expression>,
     },
+    Sub {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    Mul {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    Div {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    Mod {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
     Negation {
         ex1: Box<Expression>,
     },
@@ -44,6 +75,46 @@ pub enum Expression {
         ex1: Box<Expression>,
         ex2: Box<Expression>,
     },
+    Disjunction {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    Implication {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    LessThan {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    GreaterEqual {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    GreaterThan {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    // on pointers, whether both variables alias the same location
+    Equal {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    NotEqual {
+        ex1: Box<Expression>,
+        ex2: Box<Expression>,
+    },
+    FieldRead {
+        x: String,
+        field: String,
+    },
+    ArrayRead {
+        x: String,
+        index: Box<Expression>,
+    },
+    ArrayLength {
+        x: String,
+    },
 }
 
 /*
@@ -55,6 +126,31 @@ s ::= x := e assignment to store
     | if e then s else s conditional
     | skip no - op
     | while e do s loop
+    | var x : τ declaration
+    | x : τ := e annotated assignment to store
+    | x := new { f: e, ... } put new record on heap
+    | x.f := e update field of heap record
+    | x := newarray(e, e) put new array of given length and initial value on heap
+    | x[e] := e update element of heap array
+    | proc f(x: τ, ...) { s } procedure declaration
+    | proc f(x: τ, ...): τ { s; return e } procedure returning a nat or bool
+    | f(e, ...) procedure call
+    | x := f(e, ...) procedure call storing the returned value
+    | assert e check that e holds
+    | assume e only continue the runs where e holds
+    | print e write a nat or bool to the output
+    | read x | read x : τ read a nat or bool from the input
+    | for x := e to e do s counting loop over both bounds and everything between
+    | do s while e loop running s at least once
+    | break | continue leave or restart the innermost loop
+    | s [] s nondeterministic choice
+    | havoc x assign an arbitrary value of the type of x
+    | s || s parallel composition, interleaving the statements of both
+    | atomic { s } run s without interleaving
+    | s [p] s probabilistic choice, running the first statement with probability p
+    | x := coin(p) assign true with probability p and false otherwise
+
+τ ::= nat | bool | ptr nat | ptr { f: τ, ... } | ptr nat[]
 */
 
 #[derive(Clone)]
@@ -89,6 +185,98 @@ pub enum Statement {
         condition: Expression,
         st: Box<Statement>,
     },
+    Declaration {
+        x: String,
+        tp: ExType,
+    },
+    AnnotatedAssignment {
+        x: String,
+        tp: ExType,
+        ex1: Expression,
+    },
+    RecordNew {
+        x: String,
+        fields: Vec<(String, Expression)>,
+    },
+    FieldUpdate {
+        x: String,
+        field: String,
+        ex1: Expression,
+    },
+    ArrayNew {
+        x: String,
+        size: Expression,
+        ex1: Expression,
+    },
+    ArrayUpdate {
+        x: String,
+        index: Expression,
+        ex1: Expression,
+    },
+    // the body only sees the parameters, and ret is evaluated after it
+    Procedure {
+        name: String,
+        params: Vec<(String, ExType)>,
+        body: Box<Statement>,
+        ret: Option<(ExType, Expression)>,
+    },
+    Call {
+        x: Option<String>,
+        name: String,
+        args: Vec<Expression>,
+    },
+    // a proof obligation for analyses and an error at run time when it does not hold
+    Assert {
+        condition: Expression,
+    },
+    // a hypothesis for analyses, runs where it does not hold are discarded
+    Assume {
+        condition: Expression,
+    },
+    Print {
+        ex1: Expression,
+    },
+    // without a type, x has to be declared or already bound
+    Read {
+        x: String,
+        tp: Option<ExType>,
+    },
+    // the bounds are evaluated once, and x is set to the next value before each iteration
+    For {
+        x: String,
+        from: Expression,
+        to: Expression,
+        st: Box<Statement>,
+    },
+    DoWhile {
+        st: Box<Statement>,
+        condition: Expression,
+    },
+    Break,
+    Continue,
+    Choice {
+        st1: Box<Statement>,
+        st2: Box<Statement>,
+    },
+    Havoc {
+        x: String,
+    },
+    Parallel {
+        st1: Box<Statement>,
+        st2: Box<Statement>,
+    },
+    Atomic {
+        st: Box<Statement>,
+    },
+    ProbChoice {
+        st1: Box<Statement>,
+        st2: Box<Statement>,
+        p: Rational,
+    },
+    Coin {
+        x: String,
+        p: Rational,
+    },
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -96,6 +284,23 @@ pub enum ExType {
     NatType,
     BoolType,
     PointerType,
+    // pointer to a heap record, fields sorted by name so that equality is structural
+    RecordType { fields: Vec<(String, ExType)> },
+    // pointer to a heap array of nats
+    ArrayType,
+}
+
+pub fn recordType(mut fields: Vec<(String, ExType)>) -> ExType {
+    fields.sort_by(|a, b| a.0.cmp(&b.0));
+    ExType::RecordType { fields }
+}
+
+// pointers, records and arrays live on the heap and cannot be stored in stack variables
+pub fn isHeapReference(tp: &ExType) -> bool {
+    matches!(
+        tp,
+        ExType::PointerType | ExType::RecordType { .. } | ExType::ArrayType
+    )
 }
 
 pub fn printExpression(exp: Expression) -> String {
@@ -109,6 +314,26 @@ pub fn printExpression(exp: Expression) -> String {
             let t2 = printExpression(*ex2);
             String::from("(") + &t1.to_owned() + ") + (" + &t2.to_owned() + ")"
         }
+        Expression::Sub { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") - (" + &t2.to_owned() + ")"
+        }
+        Expression::Mul { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") * (" + &t2.to_owned() + ")"
+        }
+        Expression::Div { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") / (" + &t2.to_owned() + ")"
+        }
+        Expression::Mod { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") % (" + &t2.to_owned() + ")"
+        }
         Expression::Negation { ex1 } => {
             let t1 = printExpression(*ex1);
             String::from("not (") + &t1.to_owned() + ")"
@@ -123,6 +348,47 @@ pub fn printExpression(exp: Expression) -> String {
             let t2 = printExpression(*ex2);
             String::from("(") + &t1.to_owned() + ") <= (" + &t2.to_owned() + ")"
         }
+        Expression::Disjunction { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") or (" + &t2.to_owned() + ")"
+        }
+        Expression::Implication { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") implies (" + &t2.to_owned() + ")"
+        }
+        Expression::LessThan { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") < (" + &t2.to_owned() + ")"
+        }
+        Expression::GreaterEqual { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") >= (" + &t2.to_owned() + ")"
+        }
+        Expression::GreaterThan { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") > (" + &t2.to_owned() + ")"
+        }
+        Expression::Equal { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") == (" + &t2.to_owned() + ")"
+        }
+        Expression::NotEqual { ex1, ex2 } => {
+            let t1 = printExpression(*ex1);
+            let t2 = printExpression(*ex2);
+            String::from("(") + &t1.to_owned() + ") != (" + &t2.to_owned() + ")"
+        }
+        Expression::FieldRead { x, field } => String::from("") + &x + "." + &field + " ",
+        Expression::ArrayRead { x, index } => {
+            let t1 = printExpression(*index);
+            String::from("") + &x + "[" + &t1.to_owned() + "] "
+        }
+        Expression::ArrayLength { x } => String::from("len(") + &x + ") ",
     }
 }
 
@@ -136,7 +402,7 @@ pub fn printStatement(st: Statement) -> String {
             let t1 = printExpression(ex1);
             String::from("!") + &x + " = " + &t1.to_owned()
         }
-        Statement::HeapAlias { x, y } => String::from("") + &x + &y,
+        Statement::HeapAlias { x, y } => String::from("") + &x + " = " + &y,
         Statement::HeapNew { x, ex1 } => {
             let t1 = printExpression(ex1);
             String::from("") + &x + " = new(" + &t1.to_owned() + ")"
@@ -168,6 +434,125 @@ pub fn printStatement(st: Statement) -> String {
             let t1 = printStatement(*st);
             String::from("while (") + &cnd.to_owned() + ")" + " do { " + &t1.to_owned() + "}"
         }
+        Statement::Declaration { x, tp } => {
+            String::from("var ") + &x + " : " + &printTypeAnnotation(tp)
+        }
+        Statement::AnnotatedAssignment { x, tp, ex1 } => {
+            let t1 = printExpression(ex1);
+            String::from("") + &x + " : " + &printTypeAnnotation(tp) + " = " + &t1.to_owned()
+        }
+        Statement::RecordNew { x, fields } => {
+            let printed: Vec<String> = fields
+                .into_iter()
+                .map(|(f, e)| f + ": " + &printExpression(e))
+                .collect();
+            String::from("") + &x + " = new { " + &printed.join(", ") + " }"
+        }
+        Statement::FieldUpdate { x, field, ex1 } => {
+            let t1 = printExpression(ex1);
+            String::from("") + &x + "." + &field + " = " + &t1.to_owned()
+        }
+        Statement::ArrayNew { x, size, ex1 } => {
+            let t1 = printExpression(size);
+            let t2 = printExpression(ex1);
+            String::from("") + &x + " = newarray(" + &t1.to_owned() + ", " + &t2.to_owned() + ")"
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            let t1 = printExpression(index);
+            let t2 = printExpression(ex1);
+            String::from("") + &x + "[" + &t1.to_owned() + "] = " + &t2.to_owned()
+        }
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => {
+            let printed: Vec<String> = params
+                .into_iter()
+                .map(|(p, tp)| p + ": " + &printTypeAnnotation(tp))
+                .collect();
+            let t1 = printStatement(*body);
+            let head = String::from("proc ") + &name + "(" + &printed.join(", ") + ")";
+            match ret {
+                Some((tp, ex1)) => {
+                    head + ": "
+                        + &printTypeAnnotation(tp)
+                        + " { "
+                        + &t1.to_owned()
+                        + "; return "
+                        + &printExpression(ex1)
+                        + " }"
+                }
+                None => head + " { " + &t1.to_owned() + " }",
+            }
+        }
+        Statement::Call { x, name, args } => {
+            let printed: Vec<String> = args.into_iter().map(printExpression).collect();
+            let call = name + "(" + &printed.join(", ") + ")";
+            match x {
+                Some(x) => x + " = " + &call,
+                None => call,
+            }
+        }
+        Statement::Assert { condition } => {
+            let cnd = printExpression(condition);
+            String::from("assert (") + &cnd.to_owned() + ")"
+        }
+        Statement::Assume { condition } => {
+            let cnd = printExpression(condition);
+            String::from("assume (") + &cnd.to_owned() + ")"
+        }
+        Statement::Print { ex1 } => {
+            let t1 = printExpression(ex1);
+            String::from("print (") + &t1.to_owned() + ")"
+        }
+        Statement::Read { x, tp } => match tp {
+            Some(tp) => String::from("read ") + &x + " : " + &printTypeAnnotation(tp),
+            None => String::from("read ") + &x,
+        },
+        Statement::For { x, from, to, st } => {
+            let t1 = printExpression(from);
+            let t2 = printExpression(to);
+            let t3 = printStatement(*st);
+            String::from("for ")
+                + &x
+                + " = "
+                + &t1.to_owned()
+                + " to "
+                + &t2.to_owned()
+                + " do { "
+                + &t3.to_owned()
+                + "}"
+        }
+        Statement::DoWhile { st, condition } => {
+            let t1 = printStatement(*st);
+            let cnd = printExpression(condition);
+            String::from("do { ") + &t1.to_owned() + " } while (" + &cnd.to_owned() + ")"
+        }
+        Statement::Break => String::from("break"),
+        Statement::Continue => String::from("continue"),
+        Statement::Choice { st1, st2 } => {
+            let t1 = printStatement(*st1);
+            let t2 = printStatement(*st2);
+            String::from("{ ") + &t1.to_owned() + " } [] { " + &t2.to_owned() + " }"
+        }
+        Statement::Havoc { x } => String::from("havoc ") + &x,
+        Statement::Parallel { st1, st2 } => {
+            let t1 = printStatement(*st1);
+            let t2 = printStatement(*st2);
+            String::from("{ ") + &t1.to_owned() + " } || { " + &t2.to_owned() + " }"
+        }
+        Statement::Atomic { st } => {
+            let t1 = printStatement(*st);
+            String::from("atomic { ") + &t1.to_owned() + " }"
+        }
+        Statement::ProbChoice { st1, st2, p } => {
+            let t1 = printStatement(*st1);
+            let t2 = printStatement(*st2);
+            String::from("{ ") + &t1.to_owned() + " } [" + &printRational(p) + "] { " + &t2.to_owned() + " }"
+        }
+        Statement::Coin { x, p } => x + " = coin(" + &printRational(p) + ")",
     }
 }
 
@@ -176,6 +561,115 @@ pub fn printType(tp: ExType) -> String {
         ExType::NatType => String::from("NatType"),
         ExType::BoolType => String::from("BoolType"),
         ExType::PointerType => String::from("PointerType"),
+        ExType::RecordType { fields } => {
+            let printed: Vec<String> = fields
+                .into_iter()
+                .map(|(f, tp)| f + ": " + &printType(tp))
+                .collect();
+            String::from("RecordType { ") + &printed.join(", ") + " }"
+        }
+        ExType::ArrayType => String::from("ArrayType"),
+    }
+}
+
+// prints a type the way it is written in a declaration
+pub fn printTypeAnnotation(tp: ExType) -> String {
+    match tp {
+        ExType::NatType => String::from("nat"),
+        ExType::BoolType => String::from("bool"),
+        ExType::PointerType => String::from("ptr nat"),
+        ExType::RecordType { fields } => {
+            let printed: Vec<String> = fields
+                .into_iter()
+                .map(|(f, tp)| f + ": " + &printTypeAnnotation(tp))
+                .collect();
+            String::from("ptr { ") + &printed.join(", ") + " }"
+        }
+        ExType::ArrayType => String::from("ptr nat[]"),
+    }
+}
+
+// parameter types and return type of a procedure
+#[derive(Clone, PartialEq, Debug)]
+pub struct Signature {
+    pub params: Vec<ExType>,
+    pub ret: Option<ExType>,
+}
+
+// declared types and where they were declared, threaded through the checker
+// and scoped like the bindings of the stack
+#[derive(Clone, Default)]
+pub struct CheckContext {
+    pub declared: HashMap<String, (ExType, String)>,
+    // every variable must be declared before use
+    pub strict: bool,
+    // the variables bound before the program starts, which need no declaration
+    pub given: HashSet<String>,
+    // every procedure of the program, so that calls may come before the declaration
+    pub procedures: HashMap<String, Signature>,
+    // whether break and continue are allowed
+    pub in_loop: bool,
+}
+
+impl CheckContext {
+    pub fn strict() -> CheckContext {
+        CheckContext {
+            strict: true,
+            ..Default::default()
+        }
+    }
+}
+
+// both operands of a binary operator must have type `expected`
+fn checkOperands(
+    ex1: Expression,
+    ex2: Expression,
+    expected: ExType,
+    stack: &HashMap<String, ExType>,
+) -> Result<(), String> {
+    let p1 = printExpression(ex1.clone());
+    let p2 = printExpression(ex2.clone());
+    let t1 = typeCheckExp(ex1, stack)?;
+    let t2 = typeCheckExp(ex2, stack)?;
+    match (t1 == expected, t2 == expected) {
+        (true, true) => Result::Ok(()),
+        (false, true) => Result::Err(format!(
+            "Expression: {} should be of {}",
+            p1,
+            printType(expected)
+        )),
+        (true, false) => Result::Err(format!(
+            "Expression: {} should be of {}",
+            p2,
+            printType(expected)
+        )),
+        (false, false) => Result::Err(format!(
+            "Expression: {} & Expression: {} should be of {}",
+            p1,
+            p2,
+            printType(expected)
+        )),
+    }
+}
+
+// the type of field `field` of the record x points to
+fn fieldType(x: &String, field: &String, stack: &HashMap<String, ExType>) -> Result<ExType, String> {
+    match stack.get(x) {
+        Some(ExType::RecordType { fields }) => match fields.iter().find(|(f, _)| f == field) {
+            Some((_, tp)) => Result::Ok(tp.clone()),
+            None => Result::Err(format!("record {} has no field {}", x, field)),
+        },
+        Some(_) => Result::Err(format!("variable: {} is not a record", x)),
+        None => Result::Err("Null Pointer Exception".to_string()),
+    }
+}
+
+// x has to be bound to an array
+fn arrayOperand(x: &String, stack: &HashMap<String, ExType>) -> Result<(), String> {
+    match stack.get(x) {
+        Some(ExType::ArrayType) => Result::Ok(()),
+        Some(_) => Result::Err(format!("variable: {} is not an array", x)),
+        None => Result::Err("Null Pointer Exception".to_string()),
     }
 }
 
@@ -200,24 +694,12 @@ pub fn typeCheckExp(exp: Expression, stack: &HashMap<String, ExType>) -> Result<
         }
         Expression::NatConstant { n } => Result::Ok(ExType::NatType),
         Expression::BoolConstant { b } => Result::Ok(ExType::BoolType),
-        Expression::Add { ex1, ex2 } => {
-            let p1 = printExpression(*ex1.clone());
-            let p2 = printExpression(*ex2.clone());
-            match (typeCheckExp(*ex1, stack), typeCheckExp(*ex2, stack)) {
-                (Ok(ExType::NatType), Ok(ExType::NatType)) => Result::Ok(ExType::NatType),
-                (Err(e), _) => Result::Err(e),
-                (_, Err(e)) => Result::Err(e),
-                (Ok(ExType::NatType), Ok(_)) => {
-                    Result::Err(format!("Expression: {} should be of NatType", p1))
-                }
-                (Ok(_), Ok(ExType::NatType)) => {
-                    Result::Err(format!("Expression: {} should be of NatType", p2))
-                }
-                (Ok(_), Ok(_)) => Result::Err(format!(
-                    "Expression: {} & Expression: {} should be of NatType",
-                    p1, p2
-                )),
-            }
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 } => {
+            checkOperands(*ex1, *ex2, ExType::NatType, stack).map(|_| ExType::NatType)
         }
         Expression::Negation { ex1 } => {
             let p1 = printExpression(*ex1.clone());
@@ -229,41 +711,39 @@ pub fn typeCheckExp(exp: Expression, stack: &HashMap<String, ExType>) -> Result<
                 Err(e) => Result::Err(e),
             }
         }
-        Expression::Conjunction { ex1, ex2 } => {
-            let p1 = printExpression(*ex1.clone());
-            let p2 = printExpression(*ex2.clone());
-            match (typeCheckExp(*ex1, stack), typeCheckExp(*ex2, stack)) {
-                (Ok(ExType::BoolType), Ok(ExType::BoolType)) => Result::Ok(ExType::BoolType),
-                (Err(e), _) => Result::Err(e),
-                (_, Err(e)) => Result::Err(e),
-                (Ok(ExType::NatType), Ok(_)) => {
-                    Result::Err(format!("Expression: {} should be of BoolType", p1))
-                }
-                (Ok(_), Ok(ExType::NatType)) => {
-                    Result::Err(format!("Expression: {} should be of BoolType", p2))
-                }
-                (Ok(_), Ok(_)) => Result::Err(format!(
-                    "Expression: {} & Expression: {} should be of BoolType",
-                    p1, p2
-                )),
+        Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 } => {
+            checkOperands(*ex1, *ex2, ExType::BoolType, stack).map(|_| ExType::BoolType)
+        }
+        Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 } => {
+            checkOperands(*ex1, *ex2, ExType::NatType, stack).map(|_| ExType::BoolType)
+        }
+        Expression::FieldRead { x, field } => fieldType(&x, &field, stack),
+        Expression::ArrayRead { x, index } => {
+            arrayOperand(&x, stack)?;
+            let p1 = printExpression(*index.clone());
+            match typeCheckExp(*index, stack)? {
+                ExType::NatType => Result::Ok(ExType::NatType),
+                _ => Result::Err(format!("Expression: {} should be of NatType", p1)),
             }
         }
-        Expression::Comparision { ex1, ex2 } => {
+        Expression::ArrayLength { x } => arrayOperand(&x, stack).map(|_| ExType::NatType),
+        // both sides must have the same type, which may also be PointerType
+        Expression::Equal { ex1, ex2 } | Expression::NotEqual { ex1, ex2 } => {
             let p1 = printExpression(*ex1.clone());
             let p2 = printExpression(*ex2.clone());
-            match (typeCheckExp(*ex1, stack), typeCheckExp(*ex2, stack)) {
-                (Ok(ExType::NatType), Ok(ExType::NatType)) => Result::Ok(ExType::BoolType),
-                (Err(e), _) => Result::Err(e),
-                (_, Err(e)) => Result::Err(e),
-                (Ok(ExType::NatType), Ok(_)) => {
-                    Result::Err(format!("Expression: {} should be of NatType", p1))
-                }
-                (Ok(_), Ok(ExType::NatType)) => {
-                    Result::Err(format!("Expression: {} should be of NatType", p2))
-                }
-                (Ok(_), Ok(_)) => Result::Err(format!(
-                    "Expression: {} & Expression: {} should be of NatType",
-                    p1, p2
+            let t1 = typeCheckExp(*ex1, stack)?;
+            match typeCheckExp(*ex2, stack)? {
+                t2 if t2 == t1 => Result::Ok(ExType::BoolType),
+                _ => Result::Err(format!(
+                    "Expression: {} should be of {} to be compared with {}",
+                    p2,
+                    printType(t1),
+                    p1
                 )),
             }
         }
@@ -271,13 +751,478 @@ pub fn typeCheckExp(exp: Expression, stack: &HashMap<String, ExType>) -> Result<
 }
 
 pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<String> {
+    typeCheckProgram(st, stack, &mut CheckContext::default())
+}
+
+// like typeCheck, but every variable has to be declared before it is used
+pub fn typeCheckStrict(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<String> {
+    typeCheckProgram(st, stack, &mut CheckContext::strict())
+}
+
+// collects the signatures of all procedures in st before checking it
+pub fn typeCheckProgram(
+    st: Statement,
+    stack: &mut HashMap<String, ExType>,
+    ctx: &mut CheckContext,
+) -> Option<String> {
+    ctx.given.extend(stack.keys().cloned());
+    match collectProcedures(&st, &mut ctx.procedures) {
+        Some(e) => Some(e),
+        None => typeCheckWith(st, stack, ctx),
+    }
+}
+
+fn collectProcedures(st: &Statement, procedures: &mut HashMap<String, Signature>) -> Option<String> {
+    match st {
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => {
+            if procedures.contains_key(name) {
+                return Some(format!("Procedure {} is declared twice", name));
+            }
+            procedures.insert(
+                name.clone(),
+                Signature {
+                    params: params.iter().map(|(_, tp)| tp.clone()).collect(),
+                    ret: ret.as_ref().map(|(tp, _)| tp.clone()),
+                },
+            );
+            nestedProcedure(body)
+        }
+        Statement::Sequence { st1, st2 } => {
+            collectProcedures(st1, procedures).or_else(|| collectProcedures(st2, procedures))
+        }
+        _ => nestedProcedure(st),
+    }
+}
+
+// procedures are declared at the top level only, since one declared in a branch or
+// loop body that never runs could otherwise still be called
+fn nestedProcedure(st: &Statement) -> Option<String> {
+    match st {
+        Statement::Procedure { name, .. } => {
+            Some(format!("Procedure {} must be declared at the top level", name))
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::Parallel { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => nestedProcedure(st1).or_else(|| nestedProcedure(st2)),
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => nestedProcedure(then_branch).or_else(|| nestedProcedure(else_branch)),
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => nestedProcedure(st),
+        _ => None,
+    }
+}
+
+// a statement binding x to tp has to agree with the declaration of x, if any
+fn checkBinding(
+    x: &String,
+    tp: &ExType,
+    st: String,
+    stack: &HashMap<String, ExType>,
+    ctx: &CheckContext,
+) -> Option<String> {
+    match ctx.declared.get(x) {
+        Some((declared_tp, site)) => {
+            if declared_tp != tp {
+                Some(format!(
+                    "Variable {} declared as {} at `{}` but `{}` makes it {}",
+                    x,
+                    printType(declared_tp.clone()),
+                    site,
+                    st,
+                    printType(tp.clone())
+                ))
+            } else {
+                None
+            }
+        }
+        None => {
+            if ctx.strict && !stack.contains_key(x) {
+                Some(format!("Variable {} must be declared before use", x))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn declare(
+    x: String,
+    tp: ExType,
+    site: String,
+    stack: &HashMap<String, ExType>,
+    ctx: &mut CheckContext,
+) -> Option<String> {
+    match ctx.declared.get(&x) {
+        Some((declared_tp, declared_site)) => {
+            if declared_tp != &tp {
+                Some(format!(
+                    "Variable {} is already declared as {} at `{}`",
+                    x,
+                    printType(declared_tp.clone()),
+                    declared_site
+                ))
+            } else {
+                None
+            }
+        }
+        None => match stack.get(&x) {
+            Some(existing) if existing != &tp => Some(format!(
+                "Declaration `{}` does not match the type {} of existing variable {}",
+                site,
+                printType(existing.clone()),
+                x
+            )),
+            _ => {
+                ctx.declared.insert(x, (tp, site));
+                None
+            }
+        },
+    }
+}
+
+// binds x to a pointer or record type, which must agree with what x already is
+fn bindHeapReference(
+    x: String,
+    tp: ExType,
+    text: String,
+    stack: &mut HashMap<String, ExType>,
+    ctx: &CheckContext,
+) -> Option<String> {
+    if let Some(e) = checkBinding(&x, &tp, text, stack, ctx) {
+        Some(e)
+    } else if stack.contains_key(&x) {
+        if stack.get(&x).unwrap().clone() != tp {
+            Some(format!(
+                "Variable {} already exists and is not {}",
+                x,
+                printType(tp)
+            ))
+        } else {
+            None
+        }
+    } else {
+        stack.insert(x, tp);
+        None
+    }
+}
+
+// binds x to a nat or bool type, which must agree with what x already is
+fn bindStackVariable(
+    x: String,
+    tp: ExType,
+    text: String,
+    stack: &mut HashMap<String, ExType>,
+    ctx: &CheckContext,
+) -> Option<String> {
+    if let Some(e) = checkBinding(&x, &tp, text.clone(), stack, ctx) {
+        Some(e)
+    } else {
+        match stack.get(&x) {
+            Some(existing) if existing != &tp => Some(format!(
+                "Variable {} already exists & does not match the type of {}",
+                x, text
+            )),
+            Some(_) => None,
+            None => {
+                stack.insert(x, tp);
+                None
+            }
+        }
+    }
+}
+
+// in strict mode every variable read has to be declared, or bound before the program starts
+fn checkReads(reads: Vec<String>, ctx: &CheckContext) -> Option<String> {
+    if !ctx.strict {
+        return None;
+    }
+    reads
+        .into_iter()
+        .find(|x| !ctx.declared.contains_key(x) && !ctx.given.contains(x))
+        .map(|x| format!("Variable {} must be declared before use", x))
+}
+
+// the variables st reads itself, leaving out those of the statements inside it
+fn ownReads(st: &Statement) -> Vec<String> {
+    let mut out = Vec::new();
+    match st {
+        Statement::IfThenElse { condition, .. } | Statement::While { condition, .. } => {
+            expressionVariables(condition, &mut out)
+        }
+        Statement::For { from, to, .. } => {
+            expressionVariables(from, &mut out);
+            expressionVariables(to, &mut out);
+        }
+        // the condition of a do-while is read after the body
+        Statement::Sequence { .. }
+        | Statement::Choice { .. }
+        | Statement::Parallel { .. }
+        | Statement::ProbChoice { .. }
+        | Statement::Atomic { .. }
+        | Statement::DoWhile { .. }
+        | Statement::Procedure { .. } => (),
+        _ => out = usedVariables(st),
+    }
+    out
+}
+
+// keeps only the declarations that hold with the same type in `other`
+fn intersectDeclarations(declared: &mut HashMap<String, (ExType, String)>, other: &HashMap<String, (ExType, String)>) {
+    declared.retain(|x, (tp, _)| other.get(x).map(|(other_tp, _)| other_tp == tp).unwrap_or(false));
+}
+
+// checks st1 on a copy of stack and st2 on stack itself, each seeing only the declarations
+// made before them, keeps the declarations both make and returns the bindings after st1
+fn typeCheckBranches(
+    st1: Statement,
+    st2: Statement,
+    stack: &mut HashMap<String, ExType>,
+    ctx: &mut CheckContext,
+) -> Result<HashMap<String, ExType>, String> {
+    let mut n_map = stack.clone();
+    let declared = ctx.declared.clone();
+    if let Some(e) = typeCheckWith(st1, &mut n_map, ctx) {
+        return Err(e);
+    }
+    let first = std::mem::replace(&mut ctx.declared, declared);
+    if let Some(e) = typeCheckWith(st2, stack, ctx) {
+        return Err(e);
+    }
+    intersectDeclarations(&mut ctx.declared, &first);
+    Ok(n_map)
+}
+
+fn typeCheckLoopBody(
+    st: Statement,
+    stack: &mut HashMap<String, ExType>,
+    ctx: &mut CheckContext,
+) -> Option<String> {
+    let outer = ctx.in_loop;
+    ctx.in_loop = true;
+    let result = typeCheckWith(st, stack, ctx);
+    ctx.in_loop = outer;
+    result
+}
+
+// whether st can leave or restart the loop it is in, i.e. has a break or continue
+// that does not belong to a nested loop
+pub fn escapesLoop(st: &Statement) -> bool {
+    match st {
+        Statement::Break | Statement::Continue => true,
+        Statement::Sequence { st1, st2 } => escapesLoop(st1) || escapesLoop(st2),
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        }
+        | Statement::Choice {
+            st1: then_branch,
+            st2: else_branch,
+        }
+        | Statement::ProbChoice {
+            st1: then_branch,
+            st2: else_branch,
+            ..
+        } => escapesLoop(then_branch) || escapesLoop(else_branch),
+        Statement::Atomic { st } => escapesLoop(st),
+        _ => false,
+    }
+}
+
+// the stack variables exp reads, including the pointers it reads the heap through
+pub fn expressionVariables(exp: &Expression, out: &mut Vec<String>) {
+    match exp {
+        Expression::StackVar { x }
+        | Expression::HeapRead { x }
+        | Expression::FieldRead { x, .. }
+        | Expression::ArrayLength { x } => {
+            if !out.contains(x) {
+                out.push(x.clone());
+            }
+        }
+        Expression::ArrayRead { x, index } => {
+            if !out.contains(x) {
+                out.push(x.clone());
+            }
+            expressionVariables(index, out);
+        }
+        Expression::NatConstant { .. } | Expression::BoolConstant { .. } => (),
+        Expression::Negation { ex1 } => expressionVariables(ex1, out),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => {
+            expressionVariables(ex1, out);
+            expressionVariables(ex2, out);
+        }
+    }
+}
+
+// the stack variables st may assign, for compound statements on any path through them
+pub fn definedVariables(st: &Statement) -> Vec<String> {
+    let mut out = Vec::new();
+    collectDefined(st, &mut out);
+    out
+}
+
+fn collectDefined(st: &Statement, out: &mut Vec<String>) {
+    let define = |x: &String, out: &mut Vec<String>| {
+        if !out.contains(x) {
+            out.push(x.clone());
+        }
+    };
+    match st {
+        Statement::StackAssignment { x, .. }
+        | Statement::AnnotatedAssignment { x, .. }
+        | Statement::HeapNew { x, .. }
+        | Statement::HeapAlias { x, .. }
+        | Statement::RecordNew { x, .. }
+        | Statement::ArrayNew { x, .. }
+        | Statement::Call { x: Some(x), .. }
+        | Statement::Read { x, .. }
+        | Statement::Havoc { x }
+        | Statement::Coin { x, .. } => define(x, out),
+        Statement::For { x, st, .. } => {
+            define(x, out);
+            collectDefined(st, out);
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => {
+            collectDefined(st1, out);
+            collectDefined(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collectDefined(then_branch, out);
+            collectDefined(else_branch, out);
+        }
+        Statement::While { st, .. } | Statement::DoWhile { st, .. } | Statement::Atomic { st } => {
+            collectDefined(st, out)
+        }
+        _ => (),
+    }
+}
+
+// the stack variables st may read, including the pointers it reads or updates the heap through
+pub fn usedVariables(st: &Statement) -> Vec<String> {
+    let mut out = Vec::new();
+    collectUsed(st, &mut out);
+    out
+}
+
+fn collectUsed(st: &Statement, out: &mut Vec<String>) {
+    let pointer = |x: &String, out: &mut Vec<String>| {
+        if !out.contains(x) {
+            out.push(x.clone());
+        }
+    };
+    match st {
+        Statement::StackAssignment { ex1, .. }
+        | Statement::AnnotatedAssignment { ex1, .. }
+        | Statement::HeapNew { ex1, .. }
+        | Statement::Print { ex1 } => expressionVariables(ex1, out),
+        Statement::HeapUpdate { x, ex1 } | Statement::FieldUpdate { x, ex1, .. } => {
+            pointer(x, out);
+            expressionVariables(ex1, out);
+        }
+        Statement::HeapAlias { y, .. } => pointer(y, out),
+        Statement::RecordNew { fields, .. } => {
+            for (_, e) in fields {
+                expressionVariables(e, out);
+            }
+        }
+        Statement::ArrayNew { size, ex1, .. } => {
+            expressionVariables(size, out);
+            expressionVariables(ex1, out);
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            pointer(x, out);
+            expressionVariables(index, out);
+            expressionVariables(ex1, out);
+        }
+        Statement::Call { args, .. } => {
+            for e in args {
+                expressionVariables(e, out);
+            }
+        }
+        Statement::Assert { condition } | Statement::Assume { condition } => {
+            expressionVariables(condition, out)
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => {
+            collectUsed(st1, out);
+            collectUsed(st2, out);
+        }
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            expressionVariables(condition, out);
+            collectUsed(then_branch, out);
+            collectUsed(else_branch, out);
+        }
+        Statement::While { condition, st } | Statement::DoWhile { st, condition } => {
+            expressionVariables(condition, out);
+            collectUsed(st, out);
+        }
+        Statement::For { from, to, st, .. } => {
+            expressionVariables(from, out);
+            expressionVariables(to, out);
+            collectUsed(st, out);
+        }
+        Statement::Atomic { st } => collectUsed(st, out),
+        _ => (),
+    }
+}
+
+pub fn typeCheckWith(
+    st: Statement,
+    stack: &mut HashMap<String, ExType>,
+    ctx: &mut CheckContext,
+) -> Option<String> {
+    if let Some(e) = checkReads(ownReads(&st), ctx) {
+        return Some(e);
+    }
     match st {
         Statement::StackAssignment { x, ex1 } => {
             let p1 = printExpression(ex1.clone());
             match typeCheckExp(ex1, stack) {
                 Ok(tp) => {
-                    if tp == ExType::PointerType {
+                    if isHeapReference(&tp) {
                         Some("Cannot Assign pointer type to a stack variable".to_string())
+                    } else if let Some(e) =
+                        checkBinding(&x, &tp, x.clone() + " = " + &p1, stack, ctx)
+                    {
+                        Some(e)
                     } else {
                         if stack.contains_key(&x) {
                             if stack.get(&x).unwrap() != &tp {
@@ -316,21 +1261,10 @@ pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<S
             if !stack.contains_key(&y) {
                 Some(format!("Undefined reference to variable {}", y))
             } else {
-                match stack.get(&y).unwrap() {
-                    ExType::PointerType => {
-                        if stack.contains_key(&x) {
-                            if stack.get(&x).unwrap().clone() != ExType::PointerType {
-                                Some(format!(
-                                    "Variable {} already exists and is not PointerType",
-                                    x
-                                ))
-                            } else {
-                                None
-                            }
-                        } else {
-                            stack.insert(x, ExType::PointerType);
-                            None
-                        }
+                match stack.get(&y).unwrap().clone() {
+                    tp if isHeapReference(&tp) => {
+                        let text = x.clone() + " = " + &y;
+                        bindHeapReference(x, tp, text, stack, ctx)
                     }
                     _ => Some(format!("Cannot alias {} since it is not PointerType", y)),
                 }
@@ -340,7 +1274,10 @@ pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<S
             let p1 = printExpression(ex1.clone());
             match typeCheckExp(ex1, stack) {
                 Ok(ExType::NatType) => {
-                    if stack.contains_key(&x) {
+                    let text = x.clone() + " = new(" + &p1 + ")";
+                    if let Some(e) = checkBinding(&x, &ExType::PointerType, text, stack, ctx) {
+                        Some(e)
+                    } else if stack.contains_key(&x) {
                         if stack.get(&x).unwrap().clone() != ExType::PointerType {
                             Some(format!(
                                 "Variable {} already exists and is not PointerType",
@@ -358,9 +1295,9 @@ pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<S
                 Err(e) => Some(e),
             }
         }
-        Statement::Sequence { st1, st2 } => match typeCheck(*st1, stack) {
+        Statement::Sequence { st1, st2 } => match typeCheckWith(*st1, stack, ctx) {
             Some(e) => Some(e),
-            None => typeCheck(*st2, stack),
+            None => typeCheckWith(*st2, stack, ctx),
         },
         Statement::IfThenElse {
             condition,
@@ -370,14 +1307,10 @@ pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<S
             let p1 = printExpression(condition.clone());
             match typeCheckExp(condition, stack) {
                 Ok(ExType::BoolType) => {
-                    //clone the hash map
-                    let mut n_map = stack.clone();
-                    let check_then_b = typeCheck(*then_branch, &mut n_map);
-                    let check_else_b = typeCheck(*else_branch, stack);
-                    match (check_then_b, check_else_b) {
-                        (None, None) => {
+                    match typeCheckBranches(*then_branch, *else_branch, stack, ctx) {
+                        Ok(n_map) => {
                             let mut check = true;
-                            if !n_map.len() == stack.len() {
+                            if n_map.len() != stack.len() {
                                 check = false;
                             }
                             for (key, value) in n_map {
@@ -398,8 +1331,7 @@ pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<S
                                 None
                             }
                         }
-                        (Some(e), _) => Some(e),
-                        (_, Some(e)) => Some(e),
+                        Err(e) => Some(e),
                     }
                 }
                 Ok(_) => Some(format!("Expression: {} should be of BoolType", p1)),
@@ -410,10 +1342,394 @@ pub fn typeCheck(st: Statement, stack: &mut HashMap<String, ExType>) -> Option<S
         Statement::While { condition, st } => {
             let p1 = printExpression(condition.clone());
             match typeCheckExp(condition, stack) {
-                Ok(ExType::BoolType) => typeCheck(*st, stack),
+                Ok(ExType::BoolType) => {
+                    // the body may run zero times, so it is checked on a copy
+                    // of the context and nothing it introduces or declares survives
+                    let declared = ctx.declared.clone();
+                    let result = typeCheckLoopBody(*st, &mut stack.clone(), ctx);
+                    ctx.declared = declared;
+                    result
+                }
+                Ok(_) => Some(format!("Expression: {} should be of BoolType", p1)),
+                Err(e) => Some(e),
+            }
+        }
+        Statement::Declaration { x, tp } => {
+            let site = String::from("var ") + &x + " : " + &printTypeAnnotation(tp.clone());
+            declare(x, tp, site, stack, ctx)
+        }
+        // declares x and then assigns to it, so a mismatch is reported against the annotation
+        Statement::AnnotatedAssignment { x, tp, ex1 } => {
+            let site = String::from("")
+                + &x
+                + " : "
+                + &printTypeAnnotation(tp.clone())
+                + " = "
+                + &printExpression(ex1.clone());
+            match declare(x.clone(), tp, site, stack, ctx) {
+                Some(e) => Some(e),
+                None => typeCheckWith(Statement::StackAssignment { x, ex1 }, stack, ctx),
+            }
+        }
+        Statement::RecordNew { x, fields } => {
+            let text = printStatement(Statement::RecordNew {
+                x: x.clone(),
+                fields: fields.clone(),
+            });
+            let mut field_types: Vec<(String, ExType)> = Vec::new();
+            for (f, e) in fields {
+                if field_types.iter().any(|(g, _)| g == &f) {
+                    return Some(format!("Field {} is given twice in `{}`", f, text));
+                }
+                let p1 = printExpression(e.clone());
+                match typeCheckExp(e, stack) {
+                    Ok(tp) if !isHeapReference(&tp) => field_types.push((f, tp)),
+                    Ok(_) => {
+                        return Some(format!(
+                            "Expression: {} should be of NatType or BoolType to be stored in field {}",
+                            p1, f
+                        ))
+                    }
+                    Err(e) => return Some(e),
+                }
+            }
+            bindHeapReference(x, recordType(field_types), text, stack, ctx)
+        }
+        Statement::FieldUpdate { x, field, ex1 } => {
+            let p1 = printExpression(ex1.clone());
+            match fieldType(&x, &field, stack) {
+                Ok(tp) => match typeCheckExp(ex1, stack) {
+                    Ok(etp) if etp == tp => None,
+                    Ok(_) => Some(format!("Expression: {} should be {}", p1, printType(tp))),
+                    Err(e) => Some(e),
+                },
+                Err(e) => Some(e),
+            }
+        }
+        Statement::ArrayNew { x, size, ex1 } => {
+            let text = printStatement(Statement::ArrayNew {
+                x: x.clone(),
+                size: size.clone(),
+                ex1: ex1.clone(),
+            });
+            match checkOperands(size, ex1, ExType::NatType, stack) {
+                Ok(()) => bindHeapReference(x, ExType::ArrayType, text, stack, ctx),
+                Err(e) => Some(e),
+            }
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            if !stack.contains_key(&x) {
+                Some(format!("Undefined reference to variable {}", x))
+            } else {
+                match arrayOperand(&x, stack).and(checkOperands(index, ex1, ExType::NatType, stack)) {
+                    Ok(()) => None,
+                    Err(e) => Some(e),
+                }
+            }
+        }
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => {
+            // the body is checked on its own, so none of its variables reach the caller
+            let mut local = HashMap::new();
+            for (p, tp) in params.iter() {
+                if local.insert(p.clone(), tp.clone()).is_some() {
+                    return Some(format!("Parameter {} of procedure {} is given twice", p, name));
+                }
+            }
+            let mut inner = CheckContext {
+                declared: HashMap::new(),
+                strict: ctx.strict,
+                given: local.keys().cloned().collect(),
+                procedures: ctx.procedures.clone(),
+                in_loop: false,
+            };
+            inner.procedures.entry(name.clone()).or_insert(Signature {
+                params: params.iter().map(|(_, tp)| tp.clone()).collect(),
+                ret: ret.as_ref().map(|(tp, _)| tp.clone()),
+            });
+            if let Some(e) = typeCheckWith(*body, &mut local, &mut inner) {
+                return Some(format!("In procedure {}: {}", name, e));
+            }
+            match ret {
+                Some((tp, _)) if isHeapReference(&tp) => Some(format!(
+                    "Procedure {} can only return NatType or BoolType",
+                    name
+                )),
+                Some((tp, ex1)) => {
+                    let p1 = printExpression(ex1.clone());
+                    let mut reads = Vec::new();
+                    expressionVariables(&ex1, &mut reads);
+                    if let Some(e) = checkReads(reads, &inner) {
+                        return Some(format!("In procedure {}: {}", name, e));
+                    }
+                    match typeCheckExp(ex1, &local) {
+                        Ok(etp) if etp == tp => None,
+                        Ok(_) => Some(format!(
+                            "In procedure {}: returned expression {} should be of {}",
+                            name,
+                            p1,
+                            printType(tp)
+                        )),
+                        Err(e) => Some(format!("In procedure {}: {}", name, e)),
+                    }
+                }
+                None => None,
+            }
+        }
+        Statement::Call { x, name, args } => {
+            let text = printStatement(Statement::Call {
+                x: x.clone(),
+                name: name.clone(),
+                args: args.clone(),
+            });
+            let signature = match ctx.procedures.get(&name) {
+                Some(signature) => signature.clone(),
+                None => return Some(format!("Undefined procedure {}", name)),
+            };
+            if signature.params.len() != args.len() {
+                return Some(format!(
+                    "Procedure {} takes {} arguments but `{}` passes {}",
+                    name,
+                    signature.params.len(),
+                    text,
+                    args.len()
+                ));
+            }
+            for (e, tp) in args.into_iter().zip(signature.params) {
+                let p1 = printExpression(e.clone());
+                match typeCheckExp(e, stack) {
+                    Ok(etp) if etp == tp => (),
+                    Ok(_) => {
+                        return Some(format!(
+                            "Argument {} of `{}` should be of {}",
+                            p1,
+                            text,
+                            printType(tp)
+                        ))
+                    }
+                    Err(e) => return Some(e),
+                }
+            }
+            match (x, signature.ret) {
+                (None, _) => None,
+                (Some(x), None) => Some(format!(
+                    "Procedure {} does not return a value to store in {}",
+                    name, x
+                )),
+                (Some(x), Some(tp)) => bindStackVariable(x, tp, text, stack, ctx),
+            }
+        }
+        Statement::Assert { condition } | Statement::Assume { condition } => {
+            let p1 = printExpression(condition.clone());
+            match typeCheckExp(condition, stack) {
+                Ok(ExType::BoolType) => None,
                 Ok(_) => Some(format!("Expression: {} should be of BoolType", p1)),
                 Err(e) => Some(e),
             }
         }
+        Statement::Print { ex1 } => {
+            let p1 = printExpression(ex1.clone());
+            match typeCheckExp(ex1, stack) {
+                Ok(tp) if isHeapReference(&tp) => Some(format!(
+                    "Expression: {} should be of NatType or BoolType to be printed",
+                    p1
+                )),
+                Ok(_) => None,
+                Err(e) => Some(e),
+            }
+        }
+        // the type read comes from the annotation, else from the declaration or earlier binding of x
+        Statement::Read { x, tp } => {
+            let text = printStatement(Statement::Read {
+                x: x.clone(),
+                tp: tp.clone(),
+            });
+            let known = tp
+                .or(ctx.declared.get(&x).map(|(tp, _)| tp.clone()))
+                .or(stack.get(&x).cloned());
+            match known {
+                Some(tp) if isHeapReference(&tp) => Some(format!(
+                    "`{}` can only read NatType or BoolType",
+                    text
+                )),
+                Some(tp) => bindStackVariable(x, tp, text, stack, ctx),
+                None => Some(format!(
+                    "Type of {} in `{}` is unknown: declare {} or give the type to read",
+                    x, text, x
+                )),
+            }
+        }
+        // like a while loop whose variable is a nat bound before the body
+        Statement::For { x, from, to, st } => {
+            let text = String::from("for ")
+                + &x
+                + " = "
+                + &printExpression(from.clone())
+                + " to "
+                + &printExpression(to.clone());
+            if let Err(e) = checkOperands(from, to, ExType::NatType, stack) {
+                return Some(e);
+            }
+            let mut body_map = stack.clone();
+            let declared = ctx.declared.clone();
+            let result = match bindStackVariable(x, ExType::NatType, text, &mut body_map, ctx) {
+                Some(e) => Some(e),
+                None => typeCheckLoopBody(*st, &mut body_map, ctx),
+            };
+            ctx.declared = declared;
+            result
+        }
+        // the body always runs, so what it binds survives unless it can stop early
+        Statement::DoWhile { st, condition } => {
+            let p1 = printExpression(condition.clone());
+            let escapes = escapesLoop(&st);
+            let mut body_map = stack.clone();
+            let declared = ctx.declared.clone();
+            if let Some(e) = typeCheckLoopBody(*st, &mut body_map, ctx) {
+                return Some(e);
+            }
+            if escapes {
+                intersectContexts(&mut body_map, stack);
+                intersectDeclarations(&mut ctx.declared, &declared);
+            }
+            let mut reads = Vec::new();
+            expressionVariables(&condition, &mut reads);
+            if let Some(e) = checkReads(reads, ctx) {
+                return Some(e);
+            }
+            match typeCheckExp(condition, &body_map) {
+                Ok(ExType::BoolType) => {
+                    *stack = body_map;
+                    None
+                }
+                Ok(_) => Some(format!("Expression: {} should be of BoolType", p1)),
+                Err(e) => Some(e),
+            }
+        }
+        // like an if, both branches have to leave the same bindings behind
+        Statement::Choice { st1, st2 } => match typeCheckBranches(*st1, *st2, stack, ctx) {
+            Ok(n_map) => {
+                if n_map != *stack {
+                    Some(format!(
+                        "Stack or Heap after the Nondeterministic Choice are not identical"
+                    ))
+                } else {
+                    None
+                }
+            }
+            Err(e) => Some(e),
+        },
+        Statement::Havoc { x } => {
+            let known = ctx
+                .declared
+                .get(&x)
+                .map(|(tp, _)| tp.clone())
+                .or(stack.get(&x).cloned());
+            match known {
+                Some(tp) if isHeapReference(&tp) => {
+                    Some(format!("Cannot havoc {} since it is {}", x, printType(tp)))
+                }
+                Some(tp) => {
+                    stack.insert(x, tp);
+                    None
+                }
+                None => Some(format!(
+                    "Type of {} in `havoc {}` is unknown: declare or assign {} first",
+                    x, x, x
+                )),
+            }
+        }
+        // each branch only sees what was bound before, since the other one may not have run yet,
+        // and afterwards everything either branch bound is bound
+        Statement::Parallel { st1, st2 } => {
+            let outer = ctx.in_loop;
+            ctx.in_loop = false;
+            let mut n_map = stack.clone();
+            let mut m_map = stack.clone();
+            let declared = ctx.declared.clone();
+            let result = typeCheckWith(*st1, &mut n_map, ctx);
+            let first = std::mem::replace(&mut ctx.declared, declared);
+            let result = result.or_else(|| typeCheckWith(*st2, &mut m_map, ctx));
+            ctx.in_loop = outer;
+            if result.is_some() {
+                return result;
+            }
+            for (x, (tp, site)) in first.into_iter() {
+                match ctx.declared.get(&x) {
+                    Some((other, _)) if *other != tp => {
+                        return Some(format!(
+                            "Variable {} is declared as {} in one branch of the Parallel Composition but {} in the other",
+                            x,
+                            printType(tp),
+                            printType(other.clone())
+                        ))
+                    }
+                    _ => {
+                        ctx.declared.insert(x, (tp, site));
+                    }
+                }
+            }
+            for (x, tp) in m_map.into_iter() {
+                match n_map.get(&x) {
+                    Some(other) if *other != tp => {
+                        return Some(format!(
+                            "Variable {} is {} after one branch of the Parallel Composition but {} after the other",
+                            x,
+                            printType(other.clone()),
+                            printType(tp)
+                        ))
+                    }
+                    _ => {
+                        n_map.insert(x, tp);
+                    }
+                }
+            }
+            *stack = n_map;
+            None
+        }
+        Statement::Atomic { st } => typeCheckWith(*st, stack, ctx),
+        Statement::ProbChoice { st1, st2, p } => {
+            if !p.isProbability() {
+                return Some(format!("Probability {} is not between 0 and 1", printRational(p)));
+            }
+            match typeCheckBranches(*st1, *st2, stack, ctx) {
+                Ok(n_map) => {
+                    if n_map != *stack {
+                        Some(format!(
+                            "Stack or Heap after the Probabilistic Choice are not identical"
+                        ))
+                    } else {
+                        None
+                    }
+                }
+                Err(e) => Some(e),
+            }
+        }
+        Statement::Coin { x, p } => {
+            if !p.isProbability() {
+                return Some(format!("Probability {} is not between 0 and 1", printRational(p)));
+            }
+            let text = printStatement(Statement::Coin { x: x.clone(), p });
+            bindStackVariable(x, ExType::BoolType, text, stack, ctx)
+        }
+        Statement::Break | Statement::Continue => {
+            if ctx.in_loop {
+                None
+            } else {
+                Some(format!(
+                    "`{}` is only allowed inside a loop",
+                    printStatement(st)
+                ))
+            }
+        }
     }
 }
+
+// keeps only the bindings of `stack` that hold with the same type in `other`
+fn intersectContexts(stack: &mut HashMap<String, ExType>, other: &HashMap<String, ExType>) {
+    stack.retain(|key, value| other.get(key) == Some(value));
+}
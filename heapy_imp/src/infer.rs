@@ -0,0 +1,725 @@
+/*
+Whole-program type inference.
+
+Instead of walking the program in order like typeCheck, every statement
+contributes equality constraints between type terms and the constraints are
+solved together by unification:
+
+τ ::= α_x type of variable x
+    | α_x.f type of field f of the record x points to
+    | α_f::x type of variable x local to procedure f
+    | α_f::i type of the i-th parameter of procedure f
+    | α_f::return type returned by procedure f
+    | nat | bool | ptr nat | ptr { f: τ, ... } | ptr nat[]
+
+e1 op e2     τ(e1) = nat, τ(e2) = nat for op ∈ {+, -, *, /, %, ≤, <, ≥, >}
+e1 op e2     τ(e1) = bool, τ(e2) = bool for op ∈ {∧, ∨, ⇒}
+e1 = e2      τ(e1) = τ(e2), also for ≠
+x := e       α_x = τ(e), α_x is not a pointer, record or array
+!x := e      α_x = ptr nat, τ(e) = nat
+x = y        α_x = α_y, α_y is ptr nat, a record or ptr nat[]
+x := new(e)  α_x = ptr nat, τ(e) = nat
+x := new { f: e, ... }  α_x = ptr { f: τ(e), ... }, τ(e) is not a pointer, record or array
+x.f          α_x has field f of type α_x.f
+x.f := e     α_x has field f of type α_x.f, τ(e) = α_x.f
+x[e]         α_x = ptr nat[], τ(e) = nat
+len(x)       α_x = ptr nat[]
+x := newarray(e1, e2)  α_x = ptr nat[], τ(e1) = nat, τ(e2) = nat
+x[e1] := e2  α_x = ptr nat[], τ(e1) = nat, τ(e2) = nat
+if e ...     τ(e) = bool
+while e ...  τ(e) = bool
+assert e     τ(e) = bool, also for assume
+for x := e1 to e2 ...  α_x = nat, τ(e1) = nat, τ(e2) = nat
+do ... while e  τ(e) = bool
+havoc x      α_x is not a pointer, record or array
+x := coin(p)  α_x = bool
+print e      τ(e) is not a pointer, record or array
+read x : t   α_x = t, α_x is not a pointer, record or array, also without t
+var x : t    α_x = t
+proc f(x_i: t_i): t { s; return e }  α_f::i = t_i = α_f::x_i, α_f::return = t = τ(e)
+f(e_i)       τ(e_i) = α_f::i
+x := f(e_i)  τ(e_i) = α_f::i, α_x = α_f::return, α_x is not a pointer, record or array
+*/
+
+use std::collections::HashMap;
+
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::printType;
+use crate::imp::ExType;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TypeTerm {
+    // the type of the program variable with this name, or of `x.f` for a field
+    Var(String),
+    // never a RecordType, records are always spelled out with Record
+    Known(ExType),
+    Record(Vec<(String, TypeTerm)>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Constraint {
+    Equal {
+        t1: TypeTerm,
+        t2: TypeTerm,
+        origin: String,
+    },
+    // values of store variables cannot be pointers, records or arrays
+    NotPointer {
+        t1: TypeTerm,
+        origin: String,
+    },
+    // only pointers, records and arrays can be aliased
+    Reference {
+        t1: TypeTerm,
+        origin: String,
+    },
+    // t1 is a record with a field `field` of type t2
+    HasField {
+        t1: TypeTerm,
+        field: String,
+        t2: TypeTerm,
+        origin: String,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum InferenceError {
+    // a minimal set of constraints that cannot hold together
+    Conflict(Vec<Constraint>),
+    // variables that no constraint gives a type to
+    Ambiguous(Vec<String>),
+}
+
+pub fn printTypeTerm(tp: TypeTerm) -> String {
+    match tp {
+        TypeTerm::Var(x) => String::from("typeof(") + &x + ")",
+        TypeTerm::Known(tp) => printType(tp),
+        TypeTerm::Record(fields) => {
+            let printed: Vec<String> = fields
+                .into_iter()
+                .map(|(f, t)| f + ": " + &printTypeTerm(t))
+                .collect();
+            String::from("RecordType { ") + &printed.join(", ") + " }"
+        }
+    }
+}
+
+pub fn printConstraint(c: Constraint) -> String {
+    match c {
+        Constraint::Equal { t1, t2, origin } => format!(
+            "{} = {} (from `{}`)",
+            printTypeTerm(t1),
+            printTypeTerm(t2),
+            origin
+        ),
+        Constraint::NotPointer { t1, origin } => format!(
+            "{} is not PointerType (from `{}`)",
+            printTypeTerm(t1),
+            origin
+        ),
+        Constraint::Reference { t1, origin } => format!(
+            "{} is PointerType, RecordType or ArrayType (from `{}`)",
+            printTypeTerm(t1),
+            origin
+        ),
+        Constraint::HasField {
+            t1,
+            field,
+            t2,
+            origin,
+        } => format!(
+            "{} has field {}: {} (from `{}`)",
+            printTypeTerm(t1),
+            field,
+            printTypeTerm(t2),
+            origin
+        ),
+    }
+}
+
+pub fn printInferenceError(e: InferenceError) -> String {
+    match e {
+        InferenceError::Conflict(constraints) => {
+            let mut out = String::from("Conflicting constraints:");
+            for c in constraints {
+                out = out + "\n  " + &printConstraint(c);
+            }
+            out
+        }
+        InferenceError::Ambiguous(vars) => {
+            format!("Could not infer a type for: {}", vars.join(", "))
+        }
+    }
+}
+
+fn equal(t1: TypeTerm, t2: TypeTerm, origin: &String, out: &mut Vec<Constraint>) {
+    out.push(Constraint::Equal {
+        t1,
+        t2,
+        origin: origin.clone(),
+    });
+}
+
+fn known(tp: ExType) -> TypeTerm {
+    match tp {
+        ExType::RecordType { fields } => TypeTerm::Record(
+            fields.into_iter().map(|(f, tp)| (f, known(tp))).collect(),
+        ),
+        tp => TypeTerm::Known(tp),
+    }
+}
+
+// the term of the i-th parameter of procedure f, or of its return value
+fn signatureVar(name: &String, slot: String) -> TypeTerm {
+    TypeTerm::Var(String::from("") + name + "::" + &slot)
+}
+
+// moves the variables of tp into the scope of procedure `name`
+fn localTerm(tp: TypeTerm, name: &String) -> TypeTerm {
+    match tp {
+        // signatures and inner procedures are already named after their procedure
+        TypeTerm::Var(x) if x.contains("::") => TypeTerm::Var(x),
+        TypeTerm::Var(x) => TypeTerm::Var(String::from("") + name + "::" + &x),
+        TypeTerm::Known(tp) => TypeTerm::Known(tp),
+        TypeTerm::Record(fields) => TypeTerm::Record(
+            fields
+                .into_iter()
+                .map(|(f, t)| (f, localTerm(t, name)))
+                .collect(),
+        ),
+    }
+}
+
+fn localConstraint(c: Constraint, name: &String) -> Constraint {
+    match c {
+        Constraint::Equal { t1, t2, origin } => Constraint::Equal {
+            t1: localTerm(t1, name),
+            t2: localTerm(t2, name),
+            origin,
+        },
+        Constraint::NotPointer { t1, origin } => Constraint::NotPointer {
+            t1: localTerm(t1, name),
+            origin,
+        },
+        Constraint::Reference { t1, origin } => Constraint::Reference {
+            t1: localTerm(t1, name),
+            origin,
+        },
+        Constraint::HasField {
+            t1,
+            field,
+            t2,
+            origin,
+        } => Constraint::HasField {
+            t1: localTerm(t1, name),
+            field,
+            t2: localTerm(t2, name),
+            origin,
+        },
+    }
+}
+
+fn fieldVar(x: &String, field: &String) -> TypeTerm {
+    TypeTerm::Var(String::from("") + x + "." + field)
+}
+
+// returns the type term of exp, recording the constraints it needs on the way
+fn generateExp(exp: &Expression, origin: &String, out: &mut Vec<Constraint>) -> TypeTerm {
+    match exp {
+        Expression::StackVar { x } => TypeTerm::Var(x.clone()),
+        Expression::HeapRead { x } => {
+            equal(TypeTerm::Var(x.clone()), known(ExType::PointerType), origin, out);
+            known(ExType::NatType)
+        }
+        Expression::NatConstant { .. } => known(ExType::NatType),
+        Expression::BoolConstant { .. } => known(ExType::BoolType),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 } => {
+            let t1 = generateExp(ex1, origin, out);
+            let t2 = generateExp(ex2, origin, out);
+            equal(t1, known(ExType::NatType), origin, out);
+            equal(t2, known(ExType::NatType), origin, out);
+            known(ExType::NatType)
+        }
+        Expression::Negation { ex1 } => {
+            let t1 = generateExp(ex1, origin, out);
+            equal(t1, known(ExType::BoolType), origin, out);
+            known(ExType::BoolType)
+        }
+        Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 } => {
+            let t1 = generateExp(ex1, origin, out);
+            let t2 = generateExp(ex2, origin, out);
+            equal(t1, known(ExType::BoolType), origin, out);
+            equal(t2, known(ExType::BoolType), origin, out);
+            known(ExType::BoolType)
+        }
+        Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 } => {
+            let t1 = generateExp(ex1, origin, out);
+            let t2 = generateExp(ex2, origin, out);
+            equal(t1, known(ExType::NatType), origin, out);
+            equal(t2, known(ExType::NatType), origin, out);
+            known(ExType::BoolType)
+        }
+        Expression::Equal { ex1, ex2 } | Expression::NotEqual { ex1, ex2 } => {
+            let t1 = generateExp(ex1, origin, out);
+            let t2 = generateExp(ex2, origin, out);
+            equal(t1, t2, origin, out);
+            known(ExType::BoolType)
+        }
+        Expression::FieldRead { x, field } => {
+            out.push(Constraint::HasField {
+                t1: TypeTerm::Var(x.clone()),
+                field: field.clone(),
+                t2: fieldVar(x, field),
+                origin: origin.clone(),
+            });
+            fieldVar(x, field)
+        }
+        Expression::ArrayRead { x, index } => {
+            let t1 = generateExp(index, origin, out);
+            equal(TypeTerm::Var(x.clone()), known(ExType::ArrayType), origin, out);
+            equal(t1, known(ExType::NatType), origin, out);
+            known(ExType::NatType)
+        }
+        Expression::ArrayLength { x } => {
+            equal(TypeTerm::Var(x.clone()), known(ExType::ArrayType), origin, out);
+            known(ExType::NatType)
+        }
+    }
+}
+
+fn generateStatement(st: &Statement, out: &mut Vec<Constraint>) {
+    match st {
+        Statement::StackAssignment { x, ex1 } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(ex1, &origin, out);
+            equal(TypeTerm::Var(x.clone()), t1, &origin, out);
+            out.push(Constraint::NotPointer {
+                t1: TypeTerm::Var(x.clone()),
+                origin,
+            });
+        }
+        Statement::HeapUpdate { x, ex1 } | Statement::HeapNew { x, ex1 } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(ex1, &origin, out);
+            equal(TypeTerm::Var(x.clone()), known(ExType::PointerType), &origin, out);
+            equal(t1, known(ExType::NatType), &origin, out);
+        }
+        Statement::HeapAlias { x, y } => {
+            let origin = printStatement(st.clone());
+            equal(TypeTerm::Var(x.clone()), TypeTerm::Var(y.clone()), &origin, out);
+            out.push(Constraint::Reference {
+                t1: TypeTerm::Var(y.clone()),
+                origin,
+            });
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::Parallel { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => {
+            generateStatement(st1, out);
+            generateStatement(st2, out);
+        }
+        Statement::Coin { x, .. } => equal(
+            TypeTerm::Var(x.clone()),
+            known(ExType::BoolType),
+            &printStatement(st.clone()),
+            out,
+        ),
+        Statement::Atomic { st } => generateStatement(st, out),
+        Statement::Havoc { x } => out.push(Constraint::NotPointer {
+            t1: TypeTerm::Var(x.clone()),
+            origin: printStatement(st.clone()),
+        }),
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            let origin = String::from("if (") + &printExpression(condition.clone()) + ")";
+            let t1 = generateExp(condition, &origin, out);
+            equal(t1, known(ExType::BoolType), &origin, out);
+            generateStatement(then_branch, out);
+            generateStatement(else_branch, out);
+        }
+        Statement::Skip | Statement::Break | Statement::Continue => (),
+        Statement::While { condition, st } => {
+            let origin = String::from("while (") + &printExpression(condition.clone()) + ")";
+            let t1 = generateExp(condition, &origin, out);
+            equal(t1, known(ExType::BoolType), &origin, out);
+            generateStatement(st, out);
+        }
+        Statement::Declaration { x, tp } => {
+            let origin = printStatement(st.clone());
+            equal(TypeTerm::Var(x.clone()), known(tp.clone()), &origin, out);
+        }
+        Statement::AnnotatedAssignment { x, tp, ex1 } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(ex1, &origin, out);
+            equal(TypeTerm::Var(x.clone()), known(tp.clone()), &origin, out);
+            equal(TypeTerm::Var(x.clone()), t1, &origin, out);
+            out.push(Constraint::NotPointer {
+                t1: TypeTerm::Var(x.clone()),
+                origin,
+            });
+        }
+        Statement::RecordNew { x, fields } => {
+            let origin = printStatement(st.clone());
+            let mut terms = Vec::new();
+            for (f, e) in fields {
+                let t1 = generateExp(e, &origin, out);
+                out.push(Constraint::NotPointer {
+                    t1: t1.clone(),
+                    origin: origin.clone(),
+                });
+                terms.push((f.clone(), t1));
+            }
+            terms.sort_by(|a, b| a.0.cmp(&b.0));
+            equal(TypeTerm::Var(x.clone()), TypeTerm::Record(terms), &origin, out);
+        }
+        Statement::FieldUpdate { x, field, ex1 } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(ex1, &origin, out);
+            out.push(Constraint::HasField {
+                t1: TypeTerm::Var(x.clone()),
+                field: field.clone(),
+                t2: fieldVar(x, field),
+                origin: origin.clone(),
+            });
+            equal(fieldVar(x, field), t1, &origin, out);
+        }
+        Statement::ArrayNew {
+            x,
+            size: ex1,
+            ex1: ex2,
+        }
+        | Statement::ArrayUpdate {
+            x,
+            index: ex1,
+            ex1: ex2,
+        } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(ex1, &origin, out);
+            let t2 = generateExp(ex2, &origin, out);
+            equal(TypeTerm::Var(x.clone()), known(ExType::ArrayType), &origin, out);
+            equal(t1, known(ExType::NatType), &origin, out);
+            equal(t2, known(ExType::NatType), &origin, out);
+        }
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => {
+            let origin = String::from("proc ") + name;
+            let mut local = Vec::new();
+            for (i, (p, tp)) in params.iter().enumerate() {
+                equal(signatureVar(name, i.to_string()), known(tp.clone()), &origin, out);
+                equal(TypeTerm::Var(p.clone()), signatureVar(name, i.to_string()), &origin, &mut local);
+            }
+            generateStatement(body, &mut local);
+            if let Some((tp, ex1)) = ret {
+                let t1 = generateExp(ex1, &origin, &mut local);
+                equal(signatureVar(name, String::from("return")), known(tp.clone()), &origin, out);
+                equal(t1, signatureVar(name, String::from("return")), &origin, &mut local);
+            }
+            out.extend(local.into_iter().map(|c| localConstraint(c, name)));
+        }
+        Statement::Assert { condition } | Statement::Assume { condition } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(condition, &origin, out);
+            equal(t1, known(ExType::BoolType), &origin, out);
+        }
+        Statement::For { x, from, to, st } => {
+            let origin = String::from("for ")
+                + x
+                + " = "
+                + &printExpression(from.clone())
+                + " to "
+                + &printExpression(to.clone());
+            let t1 = generateExp(from, &origin, out);
+            let t2 = generateExp(to, &origin, out);
+            equal(TypeTerm::Var(x.clone()), known(ExType::NatType), &origin, out);
+            equal(t1, known(ExType::NatType), &origin, out);
+            equal(t2, known(ExType::NatType), &origin, out);
+            generateStatement(st, out);
+        }
+        Statement::DoWhile { st, condition } => {
+            let origin = String::from("while (") + &printExpression(condition.clone()) + ")";
+            generateStatement(st, out);
+            let t1 = generateExp(condition, &origin, out);
+            equal(t1, known(ExType::BoolType), &origin, out);
+        }
+        Statement::Print { ex1 } => {
+            let origin = printStatement(st.clone());
+            let t1 = generateExp(ex1, &origin, out);
+            out.push(Constraint::NotPointer { t1, origin });
+        }
+        Statement::Read { x, tp } => {
+            let origin = printStatement(st.clone());
+            if let Some(tp) = tp {
+                equal(TypeTerm::Var(x.clone()), known(tp.clone()), &origin, out);
+            }
+            out.push(Constraint::NotPointer {
+                t1: TypeTerm::Var(x.clone()),
+                origin,
+            });
+        }
+        Statement::Call { x, name, args } => {
+            let origin = printStatement(st.clone());
+            for (i, e) in args.iter().enumerate() {
+                let t1 = generateExp(e, &origin, out);
+                equal(t1, signatureVar(name, i.to_string()), &origin, out);
+            }
+            if let Some(x) = x {
+                equal(TypeTerm::Var(x.clone()), signatureVar(name, String::from("return")), &origin, out);
+                out.push(Constraint::NotPointer {
+                    t1: TypeTerm::Var(x.clone()),
+                    origin,
+                });
+            }
+        }
+    }
+}
+
+// the constraints of the whole program; variables of `stack` are fixed to their types
+pub fn generateConstraints(st: Statement, stack: &HashMap<String, ExType>) -> Vec<Constraint> {
+    let mut out = Vec::new();
+    for (x, tp) in stack {
+        equal(
+            TypeTerm::Var(x.clone()),
+            known(tp.clone()),
+            &String::from("typing context"),
+            &mut out,
+        );
+    }
+    generateStatement(&st, &mut out);
+    out
+}
+
+// union-find over type variables, each class carrying the type it was unified with
+struct Unifier {
+    parent: HashMap<String, String>,
+    // a Known or Record term for every class whose type is fixed
+    bound: HashMap<String, TypeTerm>,
+}
+
+impl Unifier {
+    fn new() -> Unifier {
+        Unifier {
+            parent: HashMap::new(),
+            bound: HashMap::new(),
+        }
+    }
+
+    fn find(&mut self, x: &String) -> String {
+        let p = self.parent.entry(x.clone()).or_insert(x.clone()).clone();
+        if &p == x {
+            p
+        } else {
+            let root = self.find(&p);
+            self.parent.insert(x.clone(), root.clone());
+            root
+        }
+    }
+
+    // one step: a variable becomes its class representative or the type it is bound to
+    fn shallow(&mut self, tp: &TypeTerm) -> TypeTerm {
+        match tp {
+            TypeTerm::Var(x) => {
+                let root = self.find(x);
+                match self.bound.get(&root) {
+                    Some(t) => t.clone(),
+                    None => TypeTerm::Var(root),
+                }
+            }
+            t => t.clone(),
+        }
+    }
+
+    // the fully known type of tp, if there is one
+    fn resolve(&mut self, tp: &TypeTerm) -> Option<ExType> {
+        match self.shallow(tp) {
+            TypeTerm::Var(_) => None,
+            TypeTerm::Known(t) => Some(t),
+            TypeTerm::Record(fields) => {
+                let mut resolved = Vec::new();
+                for (f, t) in fields {
+                    resolved.push((f, self.resolve(&t)?));
+                }
+                Some(ExType::RecordType { fields: resolved })
+            }
+        }
+    }
+
+    fn unify(&mut self, t1: &TypeTerm, t2: &TypeTerm) -> bool {
+        match (self.shallow(t1), self.shallow(t2)) {
+            (TypeTerm::Var(a), TypeTerm::Var(b)) => {
+                if a != b {
+                    self.parent.insert(b, a);
+                }
+                true
+            }
+            (TypeTerm::Var(a), t) | (t, TypeTerm::Var(a)) => {
+                self.bound.insert(a, t);
+                true
+            }
+            (TypeTerm::Known(ta), TypeTerm::Known(tb)) => ta == tb,
+            // records are structural: same field names, pairwise equal field types
+            (TypeTerm::Record(fa), TypeTerm::Record(fb)) => {
+                if fa.len() != fb.len() || fa.iter().zip(fb.iter()).any(|(a, b)| a.0 != b.0) {
+                    return false;
+                }
+                fa.iter()
+                    .zip(fb.iter())
+                    .all(|((_, a), (_, b))| self.unify(a, b))
+            }
+            _ => false,
+        }
+    }
+}
+
+fn solve(constraints: &Vec<Constraint>) -> Option<Unifier> {
+    let mut unifier = Unifier::new();
+    let mut has_field = Vec::new();
+    for c in constraints {
+        match c {
+            Constraint::Equal { t1, t2, .. } => {
+                if !unifier.unify(t1, t2) {
+                    return None;
+                }
+            }
+            Constraint::HasField { t1, field, t2, .. } => has_field.push((t1, field, t2)),
+            _ => (),
+        }
+    }
+    // a field access can only be resolved once the record type of its variable is known
+    let mut progress = true;
+    while progress {
+        progress = false;
+        let mut pending = Vec::new();
+        for (t1, field, t2) in has_field {
+            match unifier.shallow(t1) {
+                TypeTerm::Var(_) => pending.push((t1, field, t2)),
+                TypeTerm::Record(fields) => match fields.iter().find(|(f, _)| f == field) {
+                    Some((_, ft)) => {
+                        if !unifier.unify(ft, t2) {
+                            return None;
+                        }
+                        progress = true;
+                    }
+                    None => return None,
+                },
+                TypeTerm::Known(_) => return None,
+            }
+        }
+        has_field = pending;
+    }
+    for c in constraints {
+        match c {
+            Constraint::NotPointer { t1, .. } => match unifier.shallow(t1) {
+                TypeTerm::Known(ExType::PointerType)
+                | TypeTerm::Known(ExType::ArrayType)
+                | TypeTerm::Record(_) => return None,
+                _ => (),
+            },
+            Constraint::Reference { t1, .. } => match unifier.shallow(t1) {
+                TypeTerm::Known(ExType::PointerType)
+                | TypeTerm::Known(ExType::ArrayType)
+                | TypeTerm::Record(_)
+                | TypeTerm::Var(_) => (),
+                TypeTerm::Known(_) => return None,
+            },
+            _ => (),
+        }
+    }
+    Some(unifier)
+}
+
+// deletion filter: drop every constraint the conflict does not depend on
+fn minimalConflict(constraints: &Vec<Constraint>) -> Vec<Constraint> {
+    let mut core = constraints.clone();
+    let mut i = 0;
+    while i < core.len() {
+        let mut without = core.clone();
+        without.remove(i);
+        if solve(&without).is_none() {
+            core = without;
+        } else {
+            i += 1;
+        }
+    }
+    core
+}
+
+fn termVars(tp: &TypeTerm, vars: &mut Vec<String>) {
+    match tp {
+        // field types only show up as part of their record, and procedure locals stay local
+        TypeTerm::Var(x) => {
+            if !x.contains('.') && !x.contains("::") && !vars.contains(x) {
+                vars.push(x.clone());
+            }
+        }
+        TypeTerm::Known(_) => (),
+        TypeTerm::Record(fields) => {
+            for (_, t) in fields {
+                termVars(t, vars);
+            }
+        }
+    }
+}
+
+fn collectVars(constraints: &Vec<Constraint>) -> Vec<String> {
+    let mut vars: Vec<String> = Vec::new();
+    for c in constraints {
+        match c {
+            Constraint::Equal { t1, t2, .. } | Constraint::HasField { t1, t2, .. } => {
+                termVars(t1, &mut vars);
+                termVars(t2, &mut vars);
+            }
+            Constraint::NotPointer { t1, .. } | Constraint::Reference { t1, .. } => {
+                termVars(t1, &mut vars)
+            }
+        }
+    }
+    vars
+}
+
+pub fn inferTypes(
+    st: Statement,
+    stack: &HashMap<String, ExType>,
+) -> Result<HashMap<String, ExType>, InferenceError> {
+    let constraints = generateConstraints(st, stack);
+    match solve(&constraints) {
+        None => Err(InferenceError::Conflict(minimalConflict(&constraints))),
+        Some(mut unifier) => {
+            let mut context = HashMap::new();
+            let mut ambiguous = Vec::new();
+            for x in collectVars(&constraints) {
+                match unifier.resolve(&TypeTerm::Var(x.clone())) {
+                    Some(tp) => {
+                        context.insert(x, tp);
+                    }
+                    None => ambiguous.push(x),
+                }
+            }
+            if ambiguous.is_empty() {
+                Ok(context)
+            } else {
+                ambiguous.sort();
+                Err(InferenceError::Ambiguous(ambiguous))
+            }
+        }
+    }
+}
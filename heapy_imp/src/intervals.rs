@@ -0,0 +1,581 @@
+/*
+Interval analysis of nat variables and heap cells.
+
+An abstract interpreter over the control-flow graph keeps, for every stack
+variable and every cell !x holding a nat, an interval its value lies in. It is
+a forward dataflow analysis whose facts map names to intervals, with the ones
+not mentioned unknown. Intervals are within the range of an i32, as a run
+leaves it only by failing on an overflow. Branch conditions, assume and assert
+narrow the intervals of the variables they compare, and a fact becomes
+unreachable when they cannot hold.
+
+a + b, a * b     over the bounds, may overflow if they leave the range of an i32
+a - b            saturates at 0 like the interpreter
+a / b, a % b     for non-negative operands, leaving out a divisor of 0
+!x := e          sets !x, and joins e into every other cell, as they may be aliases
+x = y            copies !y to !x
+f(...)           forgets every cell, as the procedure may update them
+s1 || s2         forgets everything either branch may assign
+
+Loop headers are widened to 0 or the least i32 from below and the greatest i32
+from above, and narrowed afterwards by the bounds the loop really has. The
+report lists arithmetic that may overflow and conditions of branches that are
+always true or always false. The branches of a parallel composition are checked
+starting from what neither of them assigns, and procedure bodies knowing
+nothing about their parameters.
+*/
+
+use std::collections::BTreeMap;
+
+use crate::cfg::buildCfg;
+use crate::cfg::procedureCfgs;
+use crate::cfg::Cfg;
+use crate::cfg::Terminator;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Lattice;
+use crate::dataflow::Point;
+use crate::dataflow::Solution;
+use crate::imp::definedVariables;
+use crate::imp::expressionVariables;
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+const MIN: i64 = i32::MIN as i64;
+const MAX: i64 = i32::MAX as i64;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Interval {
+    pub lo: i64,
+    pub hi: i64,
+}
+
+impl Interval {
+    pub fn new(lo: i64, hi: i64) -> Interval {
+        Interval { lo, hi }
+    }
+
+    fn top() -> Interval {
+        Interval::new(MIN, MAX)
+    }
+
+    fn hull(&self, other: &Interval) -> Interval {
+        Interval::new(self.lo.min(other.lo), self.hi.max(other.hi))
+    }
+
+    // the values of the interval the interpreter can get to without overflowing
+    fn clamp(&self) -> Interval {
+        Interval::new(self.lo.clamp(MIN, MAX), self.hi.clamp(MIN, MAX))
+    }
+
+    fn overflows(&self) -> bool {
+        self.lo < MIN || self.hi > MAX
+    }
+}
+
+pub fn printInterval(i: Interval) -> String {
+    format!("[{}, {}]", i.lo, i.hi)
+}
+
+// the intervals of the variables and cells, None where no run gets to
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ranges(pub Option<BTreeMap<String, Interval>>);
+
+impl Ranges {
+    pub fn get(&self, x: &str) -> Option<Interval> {
+        self.0.as_ref().and_then(|ranges| ranges.get(x).copied())
+    }
+}
+
+// the name the interval of the cell x points to is kept under
+fn cell(x: &String) -> String {
+    String::from("!") + x
+}
+
+fn isCell(name: &String) -> bool {
+    name.starts_with('!')
+}
+
+impl Lattice for Ranges {
+    fn bottom() -> Self {
+        Ranges(None)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (&self.0, &other.0) {
+            (Some(r1), Some(r2)) => Ranges(Some(
+                r1.iter()
+                    .filter_map(|(x, i)| r2.get(x).map(|j| (x.clone(), i.hull(j))))
+                    .collect(),
+            )),
+            (Some(_), None) => self.clone(),
+            (None, _) => other.clone(),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(r1), Some(r2)) => r2
+                .iter()
+                .all(|(x, j)| r1.get(x).map(|i| j.lo <= i.lo && i.hi <= j.hi).unwrap_or(false)),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    fn widen(&self, next: &Self) -> Self {
+        match (&self.0, &next.0) {
+            (Some(r1), Some(r2)) => Ranges(Some(
+                r1.iter()
+                    .filter_map(|(x, i)| {
+                        let j = r2.get(x)?;
+                        let lo = match (j.lo < i.lo, j.lo >= 0) {
+                            (false, _) => i.lo,
+                            (true, true) => 0,
+                            (true, false) => MIN,
+                        };
+                        let hi = if j.hi > i.hi { MAX } else { i.hi };
+                        Some((x.clone(), Interval::new(lo, hi)))
+                    })
+                    .collect(),
+            )),
+            _ => self.join(next),
+        }
+    }
+
+    fn narrow(&self, next: &Self) -> Self {
+        match (&self.0, &next.0) {
+            (Some(r1), Some(r2)) => {
+                let mut ranges = r2.clone();
+                for (x, i) in r1.iter() {
+                    let j = r2.get(x).copied().unwrap_or(*i);
+                    let lo = if i.lo == MIN { j.lo } else { i.lo };
+                    let hi = if i.hi == MAX { j.hi } else { i.hi };
+                    ranges.insert(x.clone(), Interval::new(lo, hi));
+                }
+                Ranges(Some(ranges))
+            }
+            _ => next.clone(),
+        }
+    }
+}
+
+// the interval of exp before clamping it into the range of an i32, if it is a nat we know anything about
+fn rawRange(exp: &Expression, ranges: &BTreeMap<String, Interval>) -> Option<Interval> {
+    let both = |ex1: &Expression, ex2: &Expression| Some((range(ex1, ranges)?, range(ex2, ranges)?));
+    match exp {
+        Expression::NatConstant { n } => Some(Interval::new(*n as i64, *n as i64)),
+        Expression::StackVar { x } => ranges.get(x).copied(),
+        Expression::HeapRead { x } => ranges.get(&cell(x)).copied(),
+        Expression::ArrayLength { .. } => Some(Interval::new(0, MAX)),
+        Expression::Add { ex1, ex2 } => {
+            let (i, j) = both(ex1, ex2)?;
+            Some(Interval::new(i.lo + j.lo, i.hi + j.hi))
+        }
+        Expression::Sub { ex1, ex2 } => {
+            let (i, j) = both(ex1, ex2)?;
+            Some(Interval::new((i.lo - j.hi).max(0), (i.hi - j.lo).max(0)))
+        }
+        Expression::Mul { ex1, ex2 } => {
+            let (i, j) = both(ex1, ex2)?;
+            let products = [i.lo * j.lo, i.lo * j.hi, i.hi * j.lo, i.hi * j.hi];
+            Some(Interval::new(
+                *products.iter().min().unwrap(),
+                *products.iter().max().unwrap(),
+            ))
+        }
+        Expression::Div { ex1, ex2 } => {
+            let (i, j) = both(ex1, ex2)?;
+            if i.lo < 0 || j.lo < 0 || j.hi == 0 {
+                return None;
+            }
+            Some(Interval::new(i.lo / j.hi, i.hi / j.lo.max(1)))
+        }
+        Expression::Mod { ex1, ex2 } => {
+            let (i, j) = both(ex1, ex2)?;
+            if i.lo < 0 || j.lo < 0 || j.hi == 0 {
+                return None;
+            }
+            Some(Interval::new(0, i.hi.min(j.hi - 1)))
+        }
+        _ => None,
+    }
+}
+
+fn range(exp: &Expression, ranges: &BTreeMap<String, Interval>) -> Option<Interval> {
+    rawRange(exp, ranges).map(|i| i.clamp())
+}
+
+fn setRange(name: String, exp: &Expression, ranges: &mut BTreeMap<String, Interval>) {
+    match range(exp, ranges) {
+        Some(i) => {
+            ranges.insert(name, i);
+        }
+        None => {
+            ranges.remove(&name);
+        }
+    }
+}
+
+// the name a comparison can narrow, if exp is a variable or a cell
+fn narrowable(exp: &Expression) -> Option<String> {
+    match exp {
+        Expression::StackVar { x } => Some(x.clone()),
+        Expression::HeapRead { x } => Some(cell(x)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Relation {
+    AtMost,
+    Below,
+    AtLeast,
+    Above,
+    Equal,
+    NotEqual,
+}
+
+impl Relation {
+    fn negate(self) -> Relation {
+        match self {
+            Relation::AtMost => Relation::Above,
+            Relation::Below => Relation::AtLeast,
+            Relation::AtLeast => Relation::Below,
+            Relation::Above => Relation::AtMost,
+            Relation::Equal => Relation::NotEqual,
+            Relation::NotEqual => Relation::Equal,
+        }
+    }
+
+    // the relation with both sides swapped
+    fn mirror(self) -> Relation {
+        match self {
+            Relation::AtMost => Relation::AtLeast,
+            Relation::Below => Relation::Above,
+            Relation::AtLeast => Relation::AtMost,
+            Relation::Above => Relation::Below,
+            other => other,
+        }
+    }
+}
+
+// narrows the interval of name to the values standing in relation to some value of other
+fn narrowTo(name: String, relation: Relation, other: Interval, ranges: &mut BTreeMap<String, Interval>) -> bool {
+    let i = ranges.get(&name).copied().unwrap_or(Interval::top());
+    let i = match relation {
+        Relation::AtMost => Interval::new(i.lo, i.hi.min(other.hi)),
+        Relation::Below => Interval::new(i.lo, i.hi.min(other.hi - 1)),
+        Relation::AtLeast => Interval::new(i.lo.max(other.lo), i.hi),
+        Relation::Above => Interval::new(i.lo.max(other.lo + 1), i.hi),
+        Relation::Equal => Interval::new(i.lo.max(other.lo), i.hi.min(other.hi)),
+        Relation::NotEqual if other.lo == other.hi && i.lo == other.lo => Interval::new(i.lo + 1, i.hi),
+        Relation::NotEqual if other.lo == other.hi && i.hi == other.lo => Interval::new(i.lo, i.hi - 1),
+        Relation::NotEqual => i,
+    };
+    ranges.insert(name, i);
+    i.lo <= i.hi
+}
+
+// the ranges of the runs on which condition evaluates to holds
+fn assume(condition: &Expression, holds: bool, ranges: &Ranges) -> Ranges {
+    let map = match &ranges.0 {
+        Some(map) => map,
+        None => return Ranges(None),
+    };
+    let (ex1, ex2, relation) = match condition {
+        Expression::BoolConstant { b } if *b != holds => return Ranges(None),
+        Expression::Negation { ex1 } => return assume(ex1, !holds, ranges),
+        Expression::Conjunction { ex1, ex2 } if holds => return assume(ex2, true, &assume(ex1, true, ranges)),
+        Expression::Conjunction { ex1, ex2 } => return assume(ex1, false, ranges).join(&assume(ex2, false, ranges)),
+        Expression::Disjunction { ex1, ex2 } if holds => return assume(ex1, true, ranges).join(&assume(ex2, true, ranges)),
+        Expression::Disjunction { ex1, ex2 } => return assume(ex2, false, &assume(ex1, false, ranges)),
+        Expression::Implication { ex1, ex2 } if holds => {
+            return assume(ex1, false, ranges).join(&assume(ex2, true, ranges))
+        }
+        Expression::Implication { ex1, ex2 } => return assume(ex2, false, &assume(ex1, true, ranges)),
+        Expression::Comparision { ex1, ex2 } => (ex1, ex2, Relation::AtMost),
+        Expression::LessThan { ex1, ex2 } => (ex1, ex2, Relation::Below),
+        Expression::GreaterEqual { ex1, ex2 } => (ex1, ex2, Relation::AtLeast),
+        Expression::GreaterThan { ex1, ex2 } => (ex1, ex2, Relation::Above),
+        Expression::Equal { ex1, ex2 } => (ex1, ex2, Relation::Equal),
+        Expression::NotEqual { ex1, ex2 } => (ex1, ex2, Relation::NotEqual),
+        _ => return ranges.clone(),
+    };
+    let relation = if holds { relation } else { relation.negate() };
+    let mut map = map.clone();
+    // both sides are narrowed by the interval the other one had before
+    let (i1, i2) = (range(ex1, &map), range(ex2, &map));
+    if let (Some(x), Some(i2)) = (narrowable(ex1), i2) {
+        if !narrowTo(x, relation, i2, &mut map) {
+            return Ranges(None);
+        }
+    }
+    if let (Some(y), Some(i1)) = (narrowable(ex2), i1) {
+        if !narrowTo(y, relation.mirror(), i1, &mut map) {
+            return Ranges(None);
+        }
+    }
+    // constants on both sides can still contradict each other
+    if let (None, None, Some(i1), Some(i2)) = (narrowable(ex1), narrowable(ex2), i1, i2) {
+        let possible = match relation {
+            Relation::AtMost => i1.lo <= i2.hi,
+            Relation::Below => i1.lo < i2.hi,
+            Relation::AtLeast => i1.hi >= i2.lo,
+            Relation::Above => i1.hi > i2.lo,
+            Relation::Equal => i1.lo <= i2.hi && i2.lo <= i1.hi,
+            Relation::NotEqual => !(i1.lo == i1.hi && i2.lo == i2.hi && i1.lo == i2.lo),
+        };
+        if !possible {
+            return Ranges(None);
+        }
+    }
+    Ranges(Some(map))
+}
+
+fn forgetCells(ranges: &mut BTreeMap<String, Interval>) {
+    ranges.retain(|name, _| !isCell(name));
+}
+
+struct Intervals {
+    initial: Ranges,
+}
+
+impl Analysis for Intervals {
+    type Fact = Ranges;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        self.initial.clone()
+    }
+
+    fn transfer(&self, st: &Statement, _at: Point, fact: &Self::Fact) -> Self::Fact {
+        let mut ranges = match &fact.0 {
+            Some(ranges) => ranges.clone(),
+            None => return Ranges(None),
+        };
+        match st {
+            Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => {
+                setRange(x.clone(), ex1, &mut ranges)
+            }
+            Statement::HeapNew { x, ex1 } => setRange(cell(x), ex1, &mut ranges),
+            Statement::HeapUpdate { x, ex1 } => {
+                let value = range(ex1, &ranges);
+                let others: Vec<String> = ranges.keys().filter(|c| isCell(c) && **c != cell(x)).cloned().collect();
+                for other in others {
+                    match value {
+                        Some(value) => {
+                            let joined = ranges[&other].hull(&value);
+                            ranges.insert(other, joined);
+                        }
+                        None => {
+                            ranges.remove(&other);
+                        }
+                    }
+                }
+                setRange(cell(x), ex1, &mut ranges);
+            }
+            Statement::HeapAlias { x, y } => match ranges.get(&cell(y)).copied() {
+                Some(i) => {
+                    ranges.insert(cell(x), i);
+                }
+                None => {
+                    ranges.remove(&cell(x));
+                }
+            },
+            Statement::RecordNew { x, .. } | Statement::ArrayNew { x, .. } => {
+                ranges.remove(&cell(x));
+            }
+            Statement::Call { x, .. } => {
+                if let Some(x) = x {
+                    ranges.remove(x);
+                }
+                forgetCells(&mut ranges);
+            }
+            Statement::Read { x, .. } | Statement::Havoc { x } | Statement::Coin { x, .. } => {
+                ranges.remove(x);
+            }
+            Statement::Assert { condition } | Statement::Assume { condition } => {
+                return assume(condition, true, &Ranges(Some(ranges)));
+            }
+            Statement::Parallel { .. } => {
+                for x in definedVariables(st) {
+                    ranges.remove(&x);
+                }
+                forgetCells(&mut ranges);
+            }
+            _ => (),
+        }
+        Ranges(Some(ranges))
+    }
+
+    fn edge(&self, condition: &Expression, holds: bool, fact: &Self::Fact) -> Self::Fact {
+        assume(condition, holds, fact)
+    }
+}
+
+fn solveFrom(cfg: &Cfg, initial: Ranges) -> Solution<Ranges> {
+    solve(&Intervals { initial }, cfg)
+}
+
+// the intervals at every point of cfg, knowing nothing at the start
+pub fn intervals(cfg: &Cfg) -> Solution<Ranges> {
+    solveFrom(cfg, Ranges(Some(BTreeMap::new())))
+}
+
+pub fn printRanges(ranges: &Ranges) -> String {
+    match &ranges.0 {
+        Some(ranges) => ranges
+            .iter()
+            .map(|(x, i)| format!("{} ∈ {}", x, printInterval(*i)))
+            .collect::<Vec<String>>()
+            .join(", "),
+        None => String::from("unreachable"),
+    }
+}
+
+// every statement of st with the intervals before it, block by block
+pub fn printIntervals(st: &Statement) -> String {
+    let cfg = buildCfg(st);
+    let solution = intervals(&cfg);
+    let mut out = String::new();
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        out += &format!("b{}:\n", b);
+        for (i, st) in block.statements.iter().enumerate() {
+            out += &format!("    {{ {} }}\n    {}\n", printRanges(solution.at(b, i)), printStatement(st.clone()));
+        }
+        out += &format!("    {{ {} }}\n", printRanges(solution.blockExit(b)));
+    }
+    out
+}
+
+// the additions and multiplications in exp that may overflow
+fn checkArithmetic(exp: &Expression, ranges: &BTreeMap<String, Interval>, st: &String, out: &mut Vec<String>) {
+    match exp {
+        Expression::Negation { ex1 } => checkArithmetic(ex1, ranges, st, out),
+        Expression::ArrayRead { index, .. } => checkArithmetic(index, ranges, st, out),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => {
+            checkArithmetic(ex1, ranges, st, out);
+            checkArithmetic(ex2, ranges, st, out);
+            if let (Expression::Add { .. } | Expression::Mul { .. }, Some(i)) = (exp, rawRange(exp, ranges)) {
+                if i.overflows() {
+                    out.push(format!(
+                        "`{}` may overflow in `{}`, as its operands are in {} and {}",
+                        printExpression(exp.clone()).trim_end(),
+                        st,
+                        printInterval(range(ex1, ranges).unwrap()),
+                        printInterval(range(ex2, ranges).unwrap())
+                    ));
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+fn statementExpressions(st: &Statement) -> Vec<&Expression> {
+    match st {
+        Statement::StackAssignment { ex1, .. }
+        | Statement::AnnotatedAssignment { ex1, .. }
+        | Statement::HeapUpdate { ex1, .. }
+        | Statement::HeapNew { ex1, .. }
+        | Statement::FieldUpdate { ex1, .. }
+        | Statement::Print { ex1 } => vec![ex1],
+        Statement::Assert { condition } | Statement::Assume { condition } => vec![condition],
+        Statement::ArrayNew { size, ex1, .. } => vec![size, ex1],
+        Statement::ArrayUpdate { index, ex1, .. } => vec![index, ex1],
+        Statement::RecordNew { fields, .. } => fields.iter().map(|(_, e)| e).collect(),
+        Statement::Call { args, .. } => args.iter().collect(),
+        _ => vec![],
+    }
+}
+
+fn checkGraph(cfg: &Cfg, initial: Ranges, within: &str, out: &mut Vec<String>) {
+    let solution = solveFrom(cfg, initial);
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        for (i, st) in block.statements.iter().enumerate() {
+            let ranges = match &solution.at(b, i).0 {
+                Some(ranges) => ranges,
+                None => continue,
+            };
+            let text = printStatement(st.clone());
+            // the interpreter counts a for loop on without overflowing, unlike the increment it becomes
+            if let Statement::StackAssignment { x, .. } = st {
+                if x.contains('#') {
+                    continue;
+                }
+            }
+            for exp in statementExpressions(st) {
+                let before = out.len();
+                checkArithmetic(exp, ranges, &text, out);
+                for report in out[before..].iter_mut() {
+                    *report += within;
+                }
+            }
+            if let Statement::Parallel { st1, st2 } = st {
+                let mut shared = ranges.clone();
+                for x in definedVariables(st) {
+                    shared.remove(&x);
+                }
+                forgetCells(&mut shared);
+                for branch in [st1, st2] {
+                    let within = format!(" in `{}`{}", printStatement(*branch.clone()), within);
+                    checkGraph(&buildCfg(branch), Ranges(Some(shared.clone())), &within, out);
+                }
+            }
+        }
+        let fact = solution.blockExit(b);
+        if let (Terminator::Branch { condition, .. }, Some(ranges)) = (&block.terminator, &fact.0) {
+            let text = printExpression(condition.clone());
+            checkArithmetic(condition, ranges, &text.trim_end().to_string(), out);
+            let always = match (assume(condition, true, fact).0, assume(condition, false, fact).0) {
+                (Some(_), None) => "true",
+                (None, Some(_)) => "false",
+                _ => continue,
+            };
+            let mut read = Vec::new();
+            expressionVariables(condition, &mut read);
+            let shown = ranges
+                .iter()
+                .filter(|(x, _)| read.iter().any(|y| **x == *y || **x == cell(y)))
+                .map(|(x, i)| (x.clone(), *i))
+                .collect();
+            out.push(format!(
+                "Condition `{}` is always {}, with {}{}",
+                text.trim_end(),
+                always,
+                printRanges(&Ranges(Some(shown))),
+                within
+            ));
+        }
+    }
+}
+
+// the arithmetic in st that may overflow, and the conditions of branches that are always true or false
+pub fn checkRanges(st: &Statement) -> Vec<String> {
+    let mut out = Vec::new();
+    checkGraph(&buildCfg(st), Ranges(Some(BTreeMap::new())), "", &mut out);
+    for (name, cfg) in procedureCfgs(st) {
+        checkGraph(&cfg, Ranges(Some(BTreeMap::new())), &format!(" in procedure {}", name), &mut out);
+    }
+    out
+}
@@ -0,0 +1,897 @@
+/*
+A linter with rules that can be turned on and off one by one.
+
+Every lint carries the code of its rule, a severity and possibly a fix that
+rewrites the program without changing what it computes:
+
+L001 unused-variable     a variable assigned but never read in its procedure or the main program,
+                         fixed by removing x := e where e cannot fail
+L002 self-assignment     x := x, fixed by removing it where x is surely assigned
+L003 self-alias          x = x between pointers, fixed by removing it where x is surely assigned
+L004 constant-condition  a condition of an if or a loop that folds to a constant, fixed by
+                         keeping the branch taken or removing a loop that never runs
+L005 empty-loop-body     a loop whose body does nothing
+L006 identical-branches  an if with the same statement in both branches, fixed by keeping one
+                         of them where the condition cannot fail
+L007 unused-allocation   an allocation none of whose cells is ever dereferenced, by the points-to
+                         analysis, or passed to a procedure or stored in a record
+L008 overwritten-cell    !p := e followed in the same basic block by an update of a cell p surely
+                         aliases, with no read of a cell p may alias in between
+
+An expression cannot fail when it reads only variables assigned on every path
+to it, which leaves out those of the initial context, reads no heap cell,
+divides only by constants other than 0 and adds or multiplies only where it
+folds to a constant.
+
+The fixes are applied from the innermost statement outwards, so that each one
+still finds its statement where the lint was found, and the skips they leave in
+sequences are dropped.
+
+Statements are found by their path from the root of the program, one index of
+a directly enclosed statement per level as in children.
+*/
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::cfg::buildCfg;
+use crate::cfg::Cfg;
+use crate::cfg::Terminator;
+use crate::constants::cannotFail;
+use crate::constants::foldExpression;
+use crate::dataflow::Point;
+use crate::imp::definedVariables;
+use crate::imp::escapesLoop;
+use crate::imp::expressionVariables;
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::usedVariables;
+use crate::imp::Expression;
+use crate::imp::Statement;
+use crate::pointsto::pointsTo;
+use crate::pointsto::Location;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Rule {
+    UnusedVariable,
+    SelfAssignment,
+    SelfAlias,
+    ConstantCondition,
+    EmptyLoopBody,
+    IdenticalBranches,
+    UnusedAllocation,
+    OverwrittenCell,
+}
+
+pub const RULES: [Rule; 8] = [
+    Rule::UnusedVariable,
+    Rule::SelfAssignment,
+    Rule::SelfAlias,
+    Rule::ConstantCondition,
+    Rule::EmptyLoopBody,
+    Rule::IdenticalBranches,
+    Rule::UnusedAllocation,
+    Rule::OverwrittenCell,
+];
+
+pub fn ruleCode(rule: Rule) -> &'static str {
+    match rule {
+        Rule::UnusedVariable => "L001",
+        Rule::SelfAssignment => "L002",
+        Rule::SelfAlias => "L003",
+        Rule::ConstantCondition => "L004",
+        Rule::EmptyLoopBody => "L005",
+        Rule::IdenticalBranches => "L006",
+        Rule::UnusedAllocation => "L007",
+        Rule::OverwrittenCell => "L008",
+    }
+}
+
+pub fn ruleName(rule: Rule) -> &'static str {
+    match rule {
+        Rule::UnusedVariable => "unused-variable",
+        Rule::SelfAssignment => "self-assignment",
+        Rule::SelfAlias => "self-alias",
+        Rule::ConstantCondition => "constant-condition",
+        Rule::EmptyLoopBody => "empty-loop-body",
+        Rule::IdenticalBranches => "identical-branches",
+        Rule::UnusedAllocation => "unused-allocation",
+        Rule::OverwrittenCell => "overwritten-cell",
+    }
+}
+
+// the rule with a code or name
+pub fn findRule(text: &str) -> Option<Rule> {
+    RULES.iter().copied().find(|rule| ruleCode(*rule) == text || ruleName(*rule) == text)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    Warning,
+}
+
+pub fn printSeverity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Warning => "warning",
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Edit {
+    // replace the statement by skip
+    Remove,
+    // replace the statement by the one directly inside it at this index
+    KeepChild(usize),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Fix {
+    pub at: Vec<usize>,
+    pub edit: Edit,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Lint {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<Fix>,
+}
+
+pub fn printLint(lint: &Lint) -> String {
+    let fix = match &lint.fix {
+        Some(Fix { edit: Edit::Remove, .. }) => " (fix: remove it)",
+        Some(Fix {
+            edit: Edit::KeepChild(_),
+            ..
+        }) => " (fix: keep the branch)",
+        None => "",
+    };
+    format!(
+        "{}[{}] {}{}",
+        printSeverity(lint.severity),
+        ruleCode(lint.rule),
+        lint.message,
+        fix
+    )
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LintConfig {
+    pub enabled: BTreeSet<Rule>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            enabled: RULES.iter().copied().collect(),
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn none() -> LintConfig {
+        LintConfig {
+            enabled: BTreeSet::new(),
+        }
+    }
+
+    pub fn enable(&mut self, rule: Rule) {
+        self.enabled.insert(rule);
+    }
+
+    pub fn disable(&mut self, rule: Rule) {
+        self.enabled.remove(&rule);
+    }
+}
+
+// what the lint subcommand was asked to do
+#[derive(Clone, PartialEq, Debug)]
+pub struct LintOptions {
+    pub config: LintConfig,
+    pub fix: bool,
+    pub files: Vec<String>,
+}
+
+// --enable rule, --disable rule, --only rule, which may repeat, --fix, and the files of the programs
+pub fn lintOptions(args: &[String]) -> Result<LintOptions, String> {
+    let mut options = LintOptions {
+        config: LintConfig::default(),
+        fix: false,
+        files: Vec::new(),
+    };
+    let mut only = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fix" => options.fix = true,
+            "--enable" | "--disable" | "--only" => {
+                let text = args.next().ok_or(format!("{} needs a rule", arg))?;
+                let rule = findRule(text).ok_or(format!("unknown lint rule {}", text))?;
+                match arg.as_str() {
+                    "--enable" => options.config.enable(rule),
+                    "--disable" => options.config.disable(rule),
+                    _ => {
+                        if !only {
+                            options.config = LintConfig::none();
+                            only = true;
+                        }
+                        options.config.enable(rule);
+                    }
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown lint option {}", arg)),
+            _ => options.files.push(arg.clone()),
+        }
+    }
+    Ok(options)
+}
+
+// the statements directly inside st, in order
+pub fn children(st: &Statement) -> Vec<&Statement> {
+    match st {
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => vec![st1, st2],
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => vec![then_branch, else_branch],
+        Statement::While { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::For { st, .. }
+        | Statement::Atomic { st }
+        | Statement::Procedure { body: st, .. } => vec![st],
+        _ => vec![],
+    }
+}
+
+// st with each statement directly inside it replaced by what f makes of it and its index
+fn mapChildren(st: &Statement, f: &mut dyn FnMut(usize, &Statement) -> Statement) -> Statement {
+    match st {
+        Statement::Sequence { st1, st2 } => Statement::Sequence {
+            st1: Box::new(f(0, st1)),
+            st2: Box::new(f(1, st2)),
+        },
+        Statement::Choice { st1, st2 } => Statement::Choice {
+            st1: Box::new(f(0, st1)),
+            st2: Box::new(f(1, st2)),
+        },
+        Statement::ProbChoice { st1, st2, p } => Statement::ProbChoice {
+            st1: Box::new(f(0, st1)),
+            st2: Box::new(f(1, st2)),
+            p: *p,
+        },
+        Statement::Parallel { st1, st2 } => Statement::Parallel {
+            st1: Box::new(f(0, st1)),
+            st2: Box::new(f(1, st2)),
+        },
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => Statement::IfThenElse {
+            condition: condition.clone(),
+            then_branch: Box::new(f(0, then_branch)),
+            else_branch: Box::new(f(1, else_branch)),
+        },
+        Statement::While { condition, st } => Statement::While {
+            condition: condition.clone(),
+            st: Box::new(f(0, st)),
+        },
+        Statement::DoWhile { st, condition } => Statement::DoWhile {
+            st: Box::new(f(0, st)),
+            condition: condition.clone(),
+        },
+        Statement::For { x, from, to, st } => Statement::For {
+            x: x.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            st: Box::new(f(0, st)),
+        },
+        Statement::Atomic { st } => Statement::Atomic {
+            st: Box::new(f(0, st)),
+        },
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => Statement::Procedure {
+            name: name.clone(),
+            params: params.clone(),
+            body: Box::new(f(0, body)),
+            ret: ret.clone(),
+        },
+        _ => st.clone(),
+    }
+}
+
+// every statement within st with its path, before the ones inside it, entering procedures if procedures
+fn visit<'a>(st: &'a Statement, path: &mut Vec<usize>, procedures: bool, out: &mut Vec<(Vec<usize>, &'a Statement)>) {
+    out.push((path.clone(), st));
+    if let (Statement::Procedure { .. }, false) = (st, procedures) {
+        return;
+    }
+    for (i, child) in children(st).into_iter().enumerate() {
+        path.push(i);
+        visit(child, path, procedures, out);
+        path.pop();
+    }
+}
+
+// the main program and the body of every procedure, with the path, parameters, return value and where it is
+struct Scope<'a> {
+    path: Vec<usize>,
+    body: &'a Statement,
+    params: Vec<String>,
+    ret: Option<&'a Expression>,
+    within: String,
+}
+
+fn scopes(st: &Statement) -> Vec<Scope<'_>> {
+    let mut out = vec![Scope {
+        path: vec![],
+        body: st,
+        params: vec![],
+        ret: None,
+        within: String::new(),
+    }];
+    let mut all = Vec::new();
+    visit(st, &mut vec![], true, &mut all);
+    for (mut path, st) in all {
+        if let Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } = st
+        {
+            path.push(0);
+            out.push(Scope {
+                path,
+                body,
+                params: params.iter().map(|(p, _)| p.clone()).collect(),
+                ret: ret.as_ref().map(|(_, e)| e),
+                within: format!(" in procedure {}", name),
+            });
+        }
+    }
+    out
+}
+
+// the variable a statement of a basic block assigns, if any
+fn assigns(st: &Statement) -> Option<&String> {
+    match st {
+        Statement::StackAssignment { x, .. }
+        | Statement::AnnotatedAssignment { x, .. }
+        | Statement::HeapNew { x, .. }
+        | Statement::HeapAlias { x, .. }
+        | Statement::RecordNew { x, .. }
+        | Statement::ArrayNew { x, .. }
+        | Statement::Call { x: Some(x), .. }
+        | Statement::Read { x, .. }
+        | Statement::Havoc { x }
+        | Statement::Coin { x, .. }
+        | Statement::For { x, .. } => Some(x),
+        _ => None,
+    }
+}
+
+// the variables assigned on every path to each statement within st, by its path, given those assigned
+// before st, and returns those assigned on every path through st
+fn assignedBefore(
+    st: &Statement,
+    path: &mut Vec<usize>,
+    before: &BTreeSet<String>,
+    out: &mut BTreeMap<Vec<usize>, BTreeSet<String>>,
+) -> BTreeSet<String> {
+    out.insert(path.clone(), before.clone());
+    let mut child = |i: usize, child: &Statement, before: &BTreeSet<String>| {
+        path.push(i);
+        let after = assignedBefore(child, path, before, out);
+        path.pop();
+        after
+    };
+    match st {
+        Statement::Sequence { st1, st2 } => {
+            let between = child(0, st1, before);
+            child(1, st2, &between)
+        }
+        Statement::IfThenElse {
+            then_branch: st1,
+            else_branch: st2,
+            ..
+        }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => {
+            let first = child(0, st1, before);
+            child(1, st2, before).intersection(&first).cloned().collect()
+        }
+        // both branches run to the end
+        Statement::Parallel { st1, st2 } => {
+            let first = child(0, st1, before);
+            child(1, st2, before).union(&first).cloned().collect()
+        }
+        Statement::While { st, .. } => {
+            child(0, st, before);
+            before.clone()
+        }
+        Statement::For { x, st, .. } => {
+            let mut inside = before.clone();
+            inside.insert(x.clone());
+            child(0, st, &inside);
+            before.clone()
+        }
+        Statement::DoWhile { st, .. } => {
+            let after = child(0, st, before);
+            if escapesLoop(st) {
+                before.clone()
+            } else {
+                after
+            }
+        }
+        Statement::Atomic { st } => child(0, st, before),
+        Statement::Procedure { params, body, .. } => {
+            child(0, body, &params.iter().map(|(p, _)| p.clone()).collect());
+            before.clone()
+        }
+        _ => {
+            let mut after = before.clone();
+            if let Some(x) = assigns(st) {
+                after.insert(x.clone());
+            }
+            after
+        }
+    }
+}
+
+fn assignedBeforeAll(st: &Statement) -> BTreeMap<Vec<usize>, BTreeSet<String>> {
+    let mut out = BTreeMap::new();
+    assignedBefore(st, &mut vec![], &BTreeSet::new(), &mut out);
+    out
+}
+
+fn unusedVariables(st: &Statement, out: &mut Vec<Lint>) {
+    let assigned = assignedBeforeAll(st);
+    for scope in scopes(st) {
+        let mut used = usedVariables(scope.body);
+        if let Some(ret) = scope.ret {
+            expressionVariables(ret, &mut used);
+        }
+        for p in scope.params.iter() {
+            if !used.contains(p) {
+                out.push(Lint {
+                    rule: Rule::UnusedVariable,
+                    severity: Severity::Warning,
+                    message: format!("Parameter {} is never read{}", p, scope.within),
+                    fix: None,
+                });
+            }
+        }
+        let mut statements = Vec::new();
+        visit(scope.body, &mut scope.path.clone(), false, &mut statements);
+        for (path, st) in statements {
+            let x = match assigns(st) {
+                Some(x) if !used.contains(x) => x,
+                _ => continue,
+            };
+            let fix = match st {
+                Statement::StackAssignment { ex1, .. } | Statement::AnnotatedAssignment { ex1, .. }
+                    if cannotFail(ex1, Some(&assigned[&path])) =>
+                {
+                    Some(Fix {
+                        at: path,
+                        edit: Edit::Remove,
+                    })
+                }
+                _ => None,
+            };
+            let what = match st {
+                Statement::For { .. } => String::from("a for loop"),
+                _ => format!("`{}`", printStatement(st.clone()).trim_end()),
+            };
+            out.push(Lint {
+                rule: Rule::UnusedVariable,
+                severity: Severity::Warning,
+                message: format!("Variable {} is assigned by {} but never read{}", x, what, scope.within),
+                fix,
+            });
+        }
+    }
+}
+
+// the self-assignments of stack variables, or the self-aliases of pointers
+fn selfAssignments(st: &Statement, rule: Rule, out: &mut Vec<Lint>) {
+    let assigned = assignedBeforeAll(st);
+    let mut statements = Vec::new();
+    visit(st, &mut vec![], true, &mut statements);
+    for (path, st) in statements {
+        let (what, x) = match (st, rule) {
+            (
+                Statement::StackAssignment {
+                    x,
+                    ex1: Expression::StackVar { x: y },
+                },
+                Rule::SelfAssignment,
+            ) if x == y => ("Self-assignment", x),
+            (Statement::HeapAlias { x, y }, Rule::SelfAlias) if x == y => ("Self-alias", x),
+            _ => continue,
+        };
+        // reading x fails where it may not be assigned yet
+        let fix = if assigned[&path].contains(x) {
+            Some(Fix {
+                at: path,
+                edit: Edit::Remove,
+            })
+        } else {
+            None
+        };
+        out.push(Lint {
+            rule,
+            severity: Severity::Warning,
+            message: format!("{} `{}` has no effect", what, printStatement(st.clone()).trim_end()),
+            fix,
+        });
+    }
+}
+
+fn constantConditions(st: &Statement, out: &mut Vec<Lint>) {
+    let mut statements = Vec::new();
+    visit(st, &mut vec![], true, &mut statements);
+    for (path, st) in statements {
+        let (condition, taken) = match st {
+            Statement::IfThenElse { condition, .. } => (condition, true),
+            Statement::While { condition, .. } | Statement::DoWhile { condition, .. } => (condition, false),
+            _ => continue,
+        };
+        let b = match foldExpression(condition) {
+            Expression::BoolConstant { b } => b,
+            _ => continue,
+        };
+        let edit = match (st, b) {
+            (Statement::IfThenElse { .. }, true) => Some(Edit::KeepChild(0)),
+            (Statement::IfThenElse { .. }, false) => Some(Edit::KeepChild(1)),
+            (Statement::While { .. }, false) => Some(Edit::Remove),
+            _ => None,
+        };
+        let message = format!(
+            "Condition `{}` is always {}{}",
+            printExpression(condition.clone()).trim_end(),
+            b,
+            if taken { "" } else { " at the loop" }
+        );
+        out.push(Lint {
+            rule: Rule::ConstantCondition,
+            severity: Severity::Warning,
+            message,
+            fix: edit.map(|edit| Fix { at: path, edit }),
+        });
+    }
+}
+
+fn isEmpty(st: &Statement) -> bool {
+    match st {
+        Statement::Skip => true,
+        Statement::Sequence { st1, st2 } => isEmpty(st1) && isEmpty(st2),
+        _ => false,
+    }
+}
+
+fn emptyLoops(st: &Statement, out: &mut Vec<Lint>) {
+    let mut statements = Vec::new();
+    visit(st, &mut vec![], true, &mut statements);
+    for (_, st) in statements {
+        let what = match st {
+            Statement::While { condition, st } if isEmpty(st) => {
+                format!("`while {}`", printExpression(condition.clone()).trim_end())
+            }
+            Statement::DoWhile { st, condition } if isEmpty(st) => {
+                format!("`do ... while {}`", printExpression(condition.clone()).trim_end())
+            }
+            Statement::For { x, st, .. } if isEmpty(st) => format!("for over {}", x),
+            _ => continue,
+        };
+        out.push(Lint {
+            rule: Rule::EmptyLoopBody,
+            severity: Severity::Warning,
+            message: format!("Loop {} has an empty body", what),
+            fix: None,
+        });
+    }
+}
+
+fn identicalBranches(st: &Statement, out: &mut Vec<Lint>) {
+    let assigned = assignedBeforeAll(st);
+    let mut statements = Vec::new();
+    visit(st, &mut vec![], true, &mut statements);
+    for (path, st) in statements {
+        if let Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } = st
+        {
+            let branch = printStatement(*then_branch.clone());
+            if branch != printStatement(*else_branch.clone()) {
+                continue;
+            }
+            out.push(Lint {
+                rule: Rule::IdenticalBranches,
+                severity: Severity::Warning,
+                message: format!(
+                    "Both branches of the if on `{}` are `{}`",
+                    printExpression(condition.clone()).trim_end(),
+                    branch.trim_end()
+                ),
+                fix: if cannotFail(condition, Some(&assigned[&path])) {
+                    Some(Fix {
+                        at: path,
+                        edit: Edit::KeepChild(0),
+                    })
+                } else {
+                    None
+                },
+            });
+        }
+    }
+}
+
+// the pointers exp dereferences
+fn dereferences(exp: &Expression, out: &mut Vec<String>) {
+    match exp {
+        Expression::HeapRead { x } | Expression::FieldRead { x, .. } | Expression::ArrayLength { x } => out.push(x.clone()),
+        Expression::ArrayRead { x, index } => {
+            out.push(x.clone());
+            dereferences(index, out);
+        }
+        Expression::StackVar { .. } | Expression::NatConstant { .. } | Expression::BoolConstant { .. } => (),
+        Expression::Negation { ex1 } => dereferences(ex1, out),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => {
+            dereferences(ex1, out);
+            dereferences(ex2, out);
+        }
+    }
+}
+
+// the pointers a statement of a basic block reads cells through, None if it may read any cell
+fn cellReads(st: &Statement) -> Option<Vec<String>> {
+    let mut out = Vec::new();
+    match st {
+        Statement::Call { .. } | Statement::Parallel { .. } => return None,
+        Statement::StackAssignment { ex1, .. }
+        | Statement::AnnotatedAssignment { ex1, .. }
+        | Statement::HeapNew { ex1, .. }
+        | Statement::HeapUpdate { ex1, .. }
+        | Statement::FieldUpdate { ex1, .. }
+        | Statement::Print { ex1 } => dereferences(ex1, &mut out),
+        Statement::Assert { condition } | Statement::Assume { condition } => dereferences(condition, &mut out),
+        Statement::RecordNew { fields, .. } => {
+            for (_, e) in fields.iter() {
+                dereferences(e, &mut out);
+            }
+        }
+        Statement::ArrayNew { size, ex1, .. } => {
+            dereferences(size, &mut out);
+            dereferences(ex1, &mut out);
+        }
+        Statement::ArrayUpdate { index, ex1, .. } => {
+            dereferences(index, &mut out);
+            dereferences(ex1, &mut out);
+        }
+        _ => (),
+    }
+    Some(out)
+}
+
+// the pointers a statement of a basic block reads, updates or hands on, None if it may reach any cell
+fn cellUses(st: &Statement) -> Option<Vec<String>> {
+    let mut out = cellReads(st)?;
+    match st {
+        Statement::HeapUpdate { x, ex1 } | Statement::FieldUpdate { x, ex1, .. } => {
+            out.push(x.clone());
+            // a pointer stored in a record may be dereferenced through it
+            expressionVariables(ex1, &mut out);
+        }
+        Statement::ArrayUpdate { x, .. } => out.push(x.clone()),
+        Statement::RecordNew { fields, .. } => {
+            for (_, e) in fields.iter() {
+                expressionVariables(e, &mut out);
+            }
+        }
+        _ => (),
+    }
+    Some(out)
+}
+
+fn unusedAllocations(st: &Statement, out: &mut Vec<Lint>) {
+    for scope in scopes(st) {
+        let cfg = buildCfg(scope.body);
+        let solution = pointsTo(&cfg);
+        let mut used = BTreeSet::new();
+        let mut any = false;
+        let record = |at: Point, xs: Option<Vec<String>>, any: &mut bool, used: &mut BTreeSet<Location>| {
+            let aliases = match &solution.at(at.block, at.index).0 {
+                Some(aliases) => aliases,
+                None => return,
+            };
+            match xs {
+                Some(xs) => {
+                    for x in xs {
+                        used.extend(aliases.pointsTo(&x));
+                    }
+                }
+                None => *any = true,
+            }
+        };
+        for (b, block) in cfg.blocks.iter().enumerate() {
+            for (i, st) in block.statements.iter().enumerate() {
+                record(Point { block: b, index: i }, cellUses(st), &mut any, &mut used);
+            }
+            let exit = Point {
+                block: b,
+                index: block.statements.len(),
+            };
+            if let Terminator::Branch { condition, .. } = &block.terminator {
+                let mut xs = Vec::new();
+                dereferences(condition, &mut xs);
+                record(exit, Some(xs), &mut any, &mut used);
+            }
+            if let (Some(ret), true) = (scope.ret, b == cfg.exit) {
+                // a returned pointer is handed on to the caller
+                let mut xs = Vec::new();
+                expressionVariables(ret, &mut xs);
+                record(exit, Some(xs), &mut any, &mut used);
+            }
+        }
+        if any || used.contains(&Location::Unknown) {
+            continue;
+        }
+        for (b, block) in cfg.blocks.iter().enumerate() {
+            for (i, st) in block.statements.iter().enumerate() {
+                let site = Location::Site(Point { block: b, index: i });
+                if let Statement::HeapNew { .. } | Statement::RecordNew { .. } | Statement::ArrayNew { .. } = st {
+                    if !used.contains(&site) && solution.at(b, i).0.is_some() {
+                        out.push(Lint {
+                            rule: Rule::UnusedAllocation,
+                            severity: Severity::Warning,
+                            message: format!(
+                                "`{}` allocates a cell that is never dereferenced{}",
+                                printStatement(st.clone()).trim_end(),
+                                scope.within
+                            ),
+                            fix: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn overwrittenCells(cfg: &Cfg, within: &String, out: &mut Vec<Lint>) {
+    let solution = pointsTo(cfg);
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        // the updates of this block whose cells have not been read since, with their pointers
+        let mut pending: Vec<(&String, &Statement)> = Vec::new();
+        for (i, st) in block.statements.iter().enumerate() {
+            let aliases = match &solution.at(b, i).0 {
+                Some(aliases) => aliases,
+                None => break,
+            };
+            match cellReads(st) {
+                Some(reads) => pending.retain(|(p, _)| !reads.iter().any(|x| x == *p || aliases.mayAlias(p, x))),
+                None => pending.clear(),
+            }
+            if let Statement::HeapUpdate { x, .. } = st {
+                pending.retain(|(p, earlier)| {
+                    if *p != x && !aliases.mustAlias(p, x) {
+                        return true;
+                    }
+                    out.push(Lint {
+                        rule: Rule::OverwrittenCell,
+                        severity: Severity::Warning,
+                        message: format!(
+                            "The cell `{}` writes is overwritten by `{}` before it is read{}",
+                            printStatement((*earlier).clone()).trim_end(),
+                            printStatement(st.clone()).trim_end(),
+                            within
+                        ),
+                        fix: None,
+                    });
+                    false
+                });
+            }
+            let defined = definedVariables(st);
+            pending.retain(|(p, _)| !defined.contains(p));
+            if let Statement::HeapUpdate { x, .. } = st {
+                pending.push((x, st));
+            }
+        }
+    }
+}
+
+// the lints of the enabled rules on st, rule by rule
+pub fn lint(st: &Statement, config: &LintConfig) -> Vec<Lint> {
+    let mut out = Vec::new();
+    for rule in config.enabled.iter() {
+        match rule {
+            Rule::UnusedVariable => unusedVariables(st, &mut out),
+            Rule::SelfAssignment | Rule::SelfAlias => selfAssignments(st, *rule, &mut out),
+            Rule::ConstantCondition => constantConditions(st, &mut out),
+            Rule::EmptyLoopBody => emptyLoops(st, &mut out),
+            Rule::IdenticalBranches => identicalBranches(st, &mut out),
+            Rule::UnusedAllocation => unusedAllocations(st, &mut out),
+            Rule::OverwrittenCell => {
+                for scope in scopes(st) {
+                    overwrittenCells(&buildCfg(scope.body), &scope.within, &mut out);
+                }
+            }
+        }
+    }
+    out
+}
+
+// st with the edit made at path, None if there is no such statement
+fn editAt(st: &Statement, path: &[usize], edit: Edit) -> Option<Statement> {
+    if path.is_empty() {
+        return match edit {
+            Edit::Remove => Some(Statement::Skip),
+            Edit::KeepChild(i) => children(st).get(i).map(|child| (*child).clone()),
+        };
+    }
+    if path[0] >= children(st).len() {
+        return None;
+    }
+    let mut found = None;
+    let st = mapChildren(st, &mut |i, child| {
+        if i != path[0] {
+            return child.clone();
+        }
+        found = editAt(child, &path[1..], edit);
+        found.clone().unwrap_or(child.clone())
+    });
+    found.map(|_| st)
+}
+
+fn dropSkips(st: &Statement) -> Statement {
+    match mapChildren(st, &mut |_, child| dropSkips(child)) {
+        Statement::Sequence { st1, st2 } => match (*st1, *st2) {
+            (Statement::Skip, st) | (st, Statement::Skip) => st,
+            (st1, st2) => Statement::Sequence {
+                st1: Box::new(st1),
+                st2: Box::new(st2),
+            },
+        },
+        st => st,
+    }
+}
+
+// st with the fixes of lints made, innermost first, and without skips in sequences
+pub fn applyFixes(st: &Statement, lints: &Vec<Lint>) -> Statement {
+    let mut fixes: Vec<&Fix> = lints.iter().filter_map(|lint| lint.fix.as_ref()).collect();
+    fixes.sort_by(|f1, f2| f2.at.cmp(&f1.at));
+    fixes.dedup_by(|f1, f2| f1.at == f2.at);
+    let mut st = st.clone();
+    for fix in fixes {
+        if let Some(fixed) = editAt(&st, &fix.at, fix.edit) {
+            st = fixed;
+        }
+    }
+    dropSkips(&st)
+}
@@ -0,0 +1,310 @@
+/*
+Live variables and dead-store elimination.
+
+A stack variable is live at a program point if some path from it reads the
+variable before assigning it. Liveness is a backward dataflow analysis over
+sets of variables, starting from the variables observed at the end:
+
+in = (out − what st assigns) ∪ what st reads     for a statement st of a block
+in = out ∪ what s1 || s2 reads                   as neither branch surely assigns before the other reads
+
+and the condition of a branch is read on both of its edges. A pointer is read
+by every access to its cell, so a heap update keeps it live and is never
+removed itself: the cell may be observed later through an alias.
+
+Dead-store elimination runs the same transfer over the tree of the program,
+finding the fixpoint of each loop on the way, and replaces x := e by skip when
+x is dead after it. An assignment only read by dead ones is dead as well, as
+its reads are dropped with it. It is kept if evaluating e might fail, i.e. if
+e reads the heap, divides by anything other than a nonzero constant, or adds or
+multiplies anything that does not fold to a constant, as that may overflow. Afterwards every skip in a sequence is dropped.
+
+The branches of a parallel composition are left as they are, since the other
+branch may read what one of them assigns at any time. The body of a procedure
+is rewritten with only its return value observed.
+*/
+
+use std::collections::BTreeSet;
+
+use crate::cfg::Cfg;
+use crate::constants::cannotFail;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Point;
+use crate::dataflow::Solution;
+use crate::imp::definedVariables;
+use crate::imp::expressionVariables;
+use crate::imp::printStatement;
+use crate::imp::usedVariables;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+// the variables live before a statement of a basic block, or a parallel composition
+fn liveBefore(st: &Statement, after: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut live = after.clone();
+    if let Statement::Parallel { .. } = st {
+    } else {
+        for x in definedVariables(st) {
+            live.remove(&x);
+        }
+    }
+    live.extend(usedVariables(st));
+    live
+}
+
+fn withVariables(exp: &Expression, live: &BTreeSet<String>) -> BTreeSet<String> {
+    let mut read = Vec::new();
+    expressionVariables(exp, &mut read);
+    let mut live = live.clone();
+    live.extend(read);
+    live
+}
+
+struct Liveness {
+    observed: BTreeSet<String>,
+}
+
+impl Analysis for Liveness {
+    type Fact = BTreeSet<String>;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        self.observed.clone()
+    }
+
+    fn transfer(&self, st: &Statement, _at: Point, fact: &Self::Fact) -> Self::Fact {
+        liveBefore(st, fact)
+    }
+
+    fn edge(&self, condition: &Expression, _holds: bool, fact: &Self::Fact) -> Self::Fact {
+        withVariables(condition, fact)
+    }
+}
+
+// the variables live at every point of cfg, with observed live at the exit
+pub fn liveVariables(cfg: &Cfg, observed: &BTreeSet<String>) -> Solution<BTreeSet<String>> {
+    solve(
+        &Liveness {
+            observed: observed.clone(),
+        },
+        cfg,
+    )
+}
+
+// what is live where a break and where a continue of the innermost loop goes
+#[derive(Clone)]
+struct Jumps {
+    break_live: BTreeSet<String>,
+    continue_live: BTreeSet<String>,
+}
+
+struct Eliminator {
+    // what was removed, None while looking for the fixpoint of a loop
+    removed: Option<Vec<String>>,
+    within: String,
+}
+
+impl Eliminator {
+    fn remove(&mut self, st: &Statement) {
+        if let Some(removed) = &mut self.removed {
+            match st {
+                Statement::Skip => removed.push(format!("Removed skip{}", self.within)),
+                _ => removed.push(format!("Removed dead store `{}`{}", printStatement(st.clone()), self.within)),
+            }
+        }
+    }
+
+    // the live variables at the header of a loop, the least fixpoint of step above start
+    fn loopLive<F>(&mut self, start: BTreeSet<String>, mut step: F) -> BTreeSet<String>
+    where
+        F: FnMut(&mut Eliminator, &BTreeSet<String>) -> BTreeSet<String>,
+    {
+        let removed = self.removed.take();
+        let mut live = start;
+        loop {
+            let next: BTreeSet<String> = live.union(&step(self, &live)).cloned().collect();
+            if next == live {
+                break;
+            }
+            live = next;
+        }
+        self.removed = removed;
+        live
+    }
+
+    // st without its dead stores, and what is live before it, given what is live after it
+    fn eliminate(&mut self, st: &Statement, after: &BTreeSet<String>, jumps: &Jumps) -> (Statement, BTreeSet<String>) {
+        match st {
+            Statement::StackAssignment { x, ex1 } if !after.contains(x) && cannotFail(ex1, None) => {
+                self.remove(st);
+                (Statement::Skip, after.clone())
+            }
+            Statement::Sequence { st1, st2 } => {
+                for st in [st1, st2] {
+                    if let Statement::Skip = **st {
+                        self.remove(st);
+                    }
+                }
+                let (st2, middle) = self.eliminate(st2, after, jumps);
+                let (st1, before) = self.eliminate(st1, &middle, jumps);
+                let st = match (st1, st2) {
+                    (Statement::Skip, st2) => st2,
+                    (st1, Statement::Skip) => st1,
+                    (st1, st2) => Statement::Sequence {
+                        st1: Box::new(st1),
+                        st2: Box::new(st2),
+                    },
+                };
+                (st, before)
+            }
+            Statement::IfThenElse {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let (then_branch, live1) = self.eliminate(then_branch, after, jumps);
+                let (else_branch, live2) = self.eliminate(else_branch, after, jumps);
+                let st = Statement::IfThenElse {
+                    condition: condition.clone(),
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(else_branch),
+                };
+                (st, withVariables(condition, &live1.union(&live2).cloned().collect()))
+            }
+            Statement::Choice { st1, st2 } | Statement::ProbChoice { st1, st2, .. } => {
+                let (new1, live1) = self.eliminate(st1, after, jumps);
+                let (new2, live2) = self.eliminate(st2, after, jumps);
+                let st = match st {
+                    Statement::ProbChoice { p, .. } => Statement::ProbChoice {
+                        st1: Box::new(new1),
+                        st2: Box::new(new2),
+                        p: *p,
+                    },
+                    _ => Statement::Choice {
+                        st1: Box::new(new1),
+                        st2: Box::new(new2),
+                    },
+                };
+                (st, live1.union(&live2).cloned().collect())
+            }
+            Statement::While { condition, st: body } => {
+                let start = withVariables(condition, after);
+                let header = self.loopLive(start, |this, header| {
+                    let inner = Jumps {
+                        break_live: after.clone(),
+                        continue_live: header.clone(),
+                    };
+                    this.eliminate(body, header, &inner).1
+                });
+                let inner = Jumps {
+                    break_live: after.clone(),
+                    continue_live: header.clone(),
+                };
+                let (body, _) = self.eliminate(body, &header, &inner);
+                let st = Statement::While {
+                    condition: condition.clone(),
+                    st: Box::new(body),
+                };
+                (st, header)
+            }
+            Statement::DoWhile { st: body, condition } => {
+                // the live variables where the condition is checked
+                let check = self.loopLive(withVariables(condition, after), |this, check| {
+                    let inner = Jumps {
+                        break_live: after.clone(),
+                        continue_live: check.clone(),
+                    };
+                    this.eliminate(body, check, &inner).1
+                });
+                let inner = Jumps {
+                    break_live: after.clone(),
+                    continue_live: check.clone(),
+                };
+                let (body, before) = self.eliminate(body, &check, &inner);
+                let st = Statement::DoWhile {
+                    st: Box::new(body),
+                    condition: condition.clone(),
+                };
+                (st, before)
+            }
+            Statement::For { x, from, to, st: body } => {
+                // x is assigned before every iteration, and keeps its value when there is none
+                let header = self.loopLive(after.clone(), |this, header| {
+                    let inner = Jumps {
+                        break_live: after.clone(),
+                        continue_live: header.clone(),
+                    };
+                    let mut live = this.eliminate(body, header, &inner).1;
+                    live.remove(x);
+                    live
+                });
+                let inner = Jumps {
+                    break_live: after.clone(),
+                    continue_live: header.clone(),
+                };
+                let (body, _) = self.eliminate(body, &header, &inner);
+                let st = Statement::For {
+                    x: x.clone(),
+                    from: from.clone(),
+                    to: to.clone(),
+                    st: Box::new(body),
+                };
+                (st, withVariables(to, &withVariables(from, &header)))
+            }
+            Statement::Break => (Statement::Break, jumps.break_live.clone()),
+            Statement::Continue => (Statement::Continue, jumps.continue_live.clone()),
+            Statement::Atomic { st } => {
+                let (st, before) = self.eliminate(st, after, jumps);
+                (Statement::Atomic { st: Box::new(st) }, before)
+            }
+            Statement::Procedure {
+                name,
+                params,
+                body,
+                ret,
+            } => {
+                let mut observed = Vec::new();
+                if let Some((_, e)) = ret {
+                    expressionVariables(e, &mut observed);
+                }
+                let mut inner = Eliminator {
+                    removed: self.removed.take(),
+                    within: format!(" in procedure {}", name),
+                };
+                let none = Jumps {
+                    break_live: BTreeSet::new(),
+                    continue_live: BTreeSet::new(),
+                };
+                let (body, _) = inner.eliminate(body, &observed.into_iter().collect(), &none);
+                self.removed = inner.removed;
+                let st = Statement::Procedure {
+                    name: name.clone(),
+                    params: params.clone(),
+                    body: Box::new(body),
+                    ret: ret.clone(),
+                };
+                (st, after.clone())
+            }
+            _ => (st.clone(), liveBefore(st, after)),
+        }
+    }
+}
+
+// st without the assignments to stack variables that are not read afterwards when observed is
+// read at the end, and without skips in sequences, along with a line for everything removed
+pub fn eliminateDeadStores(st: &Statement, observed: &BTreeSet<String>) -> (Statement, Vec<String>) {
+    let mut eliminator = Eliminator {
+        removed: Some(Vec::new()),
+        within: String::new(),
+    };
+    let none = Jumps {
+        break_live: BTreeSet::new(),
+        continue_live: BTreeSet::new(),
+    };
+    let (st, _) = eliminator.eliminate(st, observed, &none);
+    (st, eliminator.removed.unwrap_or_default())
+}
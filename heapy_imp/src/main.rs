@@ -1,15 +1,112 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::io::Read;
 
+use crate::imp::definedVariables;
+use crate::imp::expressionVariables;
 use crate::imp::printExpression;
 use crate::imp::printStatement;
 use crate::imp::printType;
+use crate::imp::recordType;
 use crate::imp::typeCheck;
 use crate::imp::typeCheckExp;
+use crate::imp::typeCheckStrict;
+use crate::imp::usedVariables;
 use crate::imp::ExType;
 use crate::imp::Expression;
 use crate::imp::Statement;
+use crate::bounds::checkBounds;
+use crate::cfg::buildCfg;
+use crate::cfg::dominators;
+use crate::cfg::edges;
+use crate::cfg::loopDepth;
+use crate::cfg::loopForest;
+use crate::cfg::postDominators;
+use crate::cfg::printDot;
+use crate::cfg::procedureCfgs;
+use crate::cfg::successors;
+use crate::cfg::Cfg;
+use crate::constants::foldConstants;
+use crate::constants::foldExpression;
+use crate::definite::checkDefiniteAssignment;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Lattice;
+use crate::dataflow::Point;
+use crate::eval::execute;
+use crate::eval::executeAll;
+use crate::eval::executeIn;
+use crate::eval::executeSeeded;
+use crate::eval::executeWithIo;
+use crate::eval::expectedValue;
+use crate::eval::outputDistribution;
+use crate::eval::printState;
+use crate::eval::printValue;
+use crate::eval::ScriptedIo;
+use crate::eval::Env;
+use crate::eval::Ending;
+use crate::eval::HeapCell;
+use crate::eval::Outcome;
+use crate::eval::Run;
+use crate::eval::State;
+use crate::eval::Value;
+use crate::infer::inferTypes;
+use crate::infer::printInferenceError;
+use crate::infer::InferenceError;
+use crate::intervals::checkRanges;
+use crate::intervals::intervals;
+use crate::intervals::printIntervals;
+use crate::intervals::printRanges;
+use crate::intervals::Interval;
+use crate::lint::applyFixes;
+use crate::lint::findRule;
+use crate::lint::lint;
+use crate::lint::lintOptions;
+use crate::lint::printLint;
+use crate::lint::LintConfig;
+use crate::lint::Rule;
+use crate::octagon::loopInvariants;
+use crate::octagon::octagons;
+use crate::octagon::printOctagon;
+use crate::pointsto::mayAlias;
+use crate::pointsto::mustAlias;
+use crate::pointsto::pointsTo;
+use crate::pointsto::printPointsTo;
+use crate::termination::checkTermination;
+use crate::termination::terminationReport;
+use crate::liveness::eliminateDeadStores;
+use crate::liveness::liveVariables;
+use crate::parallel::exploreInterleavings;
+use crate::parse::parseProgram;
+use crate::reaching::reachingDefinitions;
+use crate::reaching::Definition;
+use crate::rational::printRational;
+use crate::rational::Rational;
+use crate::security::printSecLabel;
+use crate::security::securityCheck;
+use crate::security::SecLabel;
+use crate::security::SecType;
 
+mod bounds;
+mod cfg;
+mod constants;
+mod dataflow;
+mod definite;
+mod eval;
 mod imp;
+mod infer;
+mod intervals;
+mod lint;
+mod liveness;
+mod octagon;
+mod parallel;
+mod parse;
+mod pointsto;
+mod rational;
+mod reaching;
+mod security;
+mod termination;
 
 fn printHashMap(hashmap_to_pass: HashMap<String, ExType>) {
     print!("[ ");
@@ -488,6 +585,4592 @@ fn testIfThenElse(
 
 }
 
+fn testWhile(hashmapWithNatStackVar: HashMap<String, ExType>) {
+    // y is only assigned inside the body, so it must not leak out of the loop
+    let while_local_valid = Statement::While {
+        condition: Expression::Comparision {
+            ex1: Box::new(Expression::StackVar { x: "n".to_string() }),
+            ex2: Box::new(Expression::NatConstant { n: 10 }),
+        },
+        st: Box::new(Statement::Sequence {
+            st1: Box::new(Statement::StackAssignment {
+                x: "y".to_string(),
+                ex1: Expression::StackVar { x: "n".to_string() },
+            }),
+            st2: Box::new(Statement::StackAssignment {
+                x: "n".to_string(),
+                ex1: Expression::Add {
+                    ex1: Box::new(Expression::StackVar { x: "y".to_string() }),
+                    ex2: Box::new(Expression::NatConstant { n: 1 }),
+                },
+            }),
+        }),
+    };
+
+    typeCheckStatementPass(
+        while_local_valid.clone(),
+        hashmapWithNatStackVar.clone(),
+        hashmapWithNatStackVar.clone(),
+    );
+    let hashmap1 = &mut hashmapWithNatStackVar.clone();
+    assert!(typeCheck(while_local_valid.clone(), hashmap1).is_none());
+    assert!(!hashmap1.contains_key("y"));
+
+    // reading y after the loop is unsound if the loop ran zero times
+    let while_read_after_invalid = Statement::Sequence {
+        st1: Box::new(while_local_valid),
+        st2: Box::new(Statement::StackAssignment {
+            x: "n".to_string(),
+            ex1: Expression::StackVar { x: "y".to_string() },
+        }),
+    };
+
+    typeCheckStatementFail(while_read_after_invalid.clone(), hashmapWithNatStackVar.clone());
+
+    // the body reads y before the only assignment to it
+    let while_read_before_assign_invalid = Statement::While {
+        condition: Expression::BoolConstant { b: true },
+        st: Box::new(Statement::Sequence {
+            st1: Box::new(Statement::StackAssignment {
+                x: "n".to_string(),
+                ex1: Expression::StackVar { x: "y".to_string() },
+            }),
+            st2: Box::new(Statement::StackAssignment {
+                x: "y".to_string(),
+                ex1: Expression::NatConstant { n: 1 },
+            }),
+        }),
+    };
+
+    typeCheckStatementFail(
+        while_read_before_assign_invalid.clone(),
+        hashmapWithNatStackVar.clone(),
+    );
+
+    // both reads of the possibly-unassigned y are reported as such
+    for program in [while_read_after_invalid, while_read_before_assign_invalid] {
+        let error = typeCheck(program, &mut hashmapWithNatStackVar.clone()).unwrap();
+        assert!(error.contains("undeclared stack variable: y"));
+    }
+}
+
+fn typeCheckStrictStatement(
+    syntax_tree: Statement,
+    hashmap_to_pass: HashMap<String, ExType>,
+    expect_pass: bool,
+) {
+    print!(
+        "Strictly Type Checking Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Current Typing Context: ");
+    printHashMap(hashmap_to_pass.clone());
+    let hashmap_to_pass = &mut hashmap_to_pass.clone();
+    match typeCheckStrict(syntax_tree, hashmap_to_pass) {
+        None => {
+            assert!(expect_pass);
+            print!("Type Check Passed\n\n")
+        }
+        Some(x) => {
+            assert!(!expect_pass);
+            print!("Type Check Failed as Expected the error message is:\n{}\n\n", x)
+        }
+    }
+}
+
+fn testDeclarations(
+    empty_hashmap: HashMap<String, ExType>,
+    hashmapWithNatStackVar: HashMap<String, ExType>,
+) {
+    let declare_x_nat = Statement::Declaration {
+        x: "x".to_string(),
+        tp: ExType::NatType,
+    };
+    let declare_p_ptr = Statement::Declaration {
+        x: "p".to_string(),
+        tp: ExType::PointerType,
+    };
+
+    let decl_valid = Statement::Sequence {
+        st1: Box::new(declare_x_nat.clone()),
+        st2: Box::new(Statement::Sequence {
+            st1: Box::new(declare_p_ptr.clone()),
+            st2: Box::new(Statement::Sequence {
+                st1: Box::new(Statement::StackAssignment {
+                    x: "x".to_string(),
+                    ex1: Expression::NatConstant { n: 5 },
+                }),
+                st2: Box::new(Statement::HeapNew {
+                    x: "p".to_string(),
+                    ex1: Expression::StackVar { x: "x".to_string() },
+                }),
+            }),
+        }),
+    };
+
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    hashmap1.insert("p".to_string(), ExType::PointerType);
+    typeCheckStatementPass(decl_valid.clone(), empty_hashmap.clone(), hashmap1.clone());
+    typeCheckStrictStatement(decl_valid, empty_hashmap.clone(), true);
+
+    // the mismatch is reported against the declaration of x
+    let decl_mismatch_invalid = Statement::Sequence {
+        st1: Box::new(declare_x_nat.clone()),
+        st2: Box::new(Statement::StackAssignment {
+            x: "x".to_string(),
+            ex1: Expression::BoolConstant { b: true },
+        }),
+    };
+    typeCheckStatementFail(decl_mismatch_invalid, empty_hashmap.clone());
+
+    let decl_ptr_mismatch_invalid = Statement::Sequence {
+        st1: Box::new(declare_x_nat.clone()),
+        st2: Box::new(Statement::HeapNew {
+            x: "x".to_string(),
+            ex1: Expression::NatConstant { n: 1 },
+        }),
+    };
+    typeCheckStatementFail(decl_ptr_mismatch_invalid, empty_hashmap.clone());
+
+    let redeclare_invalid = Statement::Sequence {
+        st1: Box::new(declare_x_nat.clone()),
+        st2: Box::new(Statement::Declaration {
+            x: "x".to_string(),
+            tp: ExType::BoolType,
+        }),
+    };
+    typeCheckStatementFail(redeclare_invalid, empty_hashmap.clone());
+
+    let declare_existing_invalid = Statement::Declaration {
+        x: "n".to_string(),
+        tp: ExType::BoolType,
+    };
+    typeCheckStatementFail(declare_existing_invalid, hashmapWithNatStackVar.clone());
+
+    let annotated_valid = Statement::AnnotatedAssignment {
+        x: "b".to_string(),
+        tp: ExType::BoolType,
+        ex1: Expression::Negation {
+            ex1: Box::new(Expression::BoolConstant { b: false }),
+        },
+    };
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("b".to_string(), ExType::BoolType);
+    typeCheckStatementPass(annotated_valid.clone(), empty_hashmap.clone(), hashmap2.clone());
+    typeCheckStrictStatement(annotated_valid, empty_hashmap.clone(), true);
+
+    let annotated_invalid = Statement::AnnotatedAssignment {
+        x: "b".to_string(),
+        tp: ExType::BoolType,
+        ex1: Expression::NatConstant { n: 3 },
+    };
+    typeCheckStatementFail(annotated_invalid, empty_hashmap.clone());
+
+    // strict mode: x is never declared
+    let undeclared_strict_invalid = Statement::StackAssignment {
+        x: "x".to_string(),
+        ex1: Expression::NatConstant { n: 5 },
+    };
+    typeCheckStrictStatement(undeclared_strict_invalid.clone(), empty_hashmap.clone(), false);
+    typeCheckStatementPass(
+        undeclared_strict_invalid,
+        empty_hashmap.clone(),
+        empty_hashmap.clone(),
+    );
+
+    // variables of the initial context count as declared
+    let context_var_strict_valid = Statement::StackAssignment {
+        x: "n".to_string(),
+        ex1: Expression::NatConstant { n: 7 },
+    };
+    typeCheckStrictStatement(context_var_strict_valid, hashmapWithNatStackVar.clone(), true);
+
+    // declared but never assigned, so reading it is still an error
+    let declared_unassigned_invalid = Statement::Sequence {
+        st1: Box::new(declare_x_nat),
+        st2: Box::new(Statement::StackAssignment {
+            x: "n".to_string(),
+            ex1: Expression::StackVar { x: "x".to_string() },
+        }),
+    };
+    typeCheckStrictStatement(
+        declared_unassigned_invalid,
+        hashmapWithNatStackVar.clone(),
+        false,
+    );
+
+    // a declaration is scoped like a binding: it does not outlive a loop body or a single branch
+    let declare_y = Statement::Sequence {
+        st1: Box::new(Statement::Declaration {
+            x: "y".to_string(),
+            tp: ExType::NatType,
+        }),
+        st2: Box::new(Statement::StackAssignment {
+            x: "y".to_string(),
+            ex1: Expression::StackVar { x: "n".to_string() },
+        }),
+    };
+    let n_small = Expression::LessThan {
+        ex1: Box::new(Expression::StackVar { x: "n".to_string() }),
+        ex2: Box::new(Expression::NatConstant { n: 3 }),
+    };
+    let assign_y = Statement::StackAssignment {
+        x: "y".to_string(),
+        ex1: Expression::NatConstant { n: 1 },
+    };
+    let loop_declaration_invalid = Statement::Sequence {
+        st1: Box::new(Statement::While {
+            condition: n_small.clone(),
+            st: Box::new(declare_y.clone()),
+        }),
+        st2: Box::new(assign_y.clone()),
+    };
+    typeCheckStrictStatement(loop_declaration_invalid, hashmapWithNatStackVar.clone(), false);
+    let branch_declaration_invalid = Statement::Sequence {
+        st1: Box::new(Statement::IfThenElse {
+            condition: n_small.clone(),
+            then_branch: Box::new(Statement::Declaration {
+                x: "y".to_string(),
+                tp: ExType::NatType,
+            }),
+            else_branch: Box::new(Statement::Skip),
+        }),
+        st2: Box::new(assign_y.clone()),
+    };
+    typeCheckStrictStatement(branch_declaration_invalid, hashmapWithNatStackVar.clone(), false);
+    let both_branches_valid = Statement::Sequence {
+        st1: Box::new(Statement::IfThenElse {
+            condition: n_small.clone(),
+            then_branch: Box::new(declare_y.clone()),
+            else_branch: Box::new(declare_y.clone()),
+        }),
+        st2: Box::new(assign_y.clone()),
+    };
+    typeCheckStrictStatement(both_branches_valid, hashmapWithNatStackVar.clone(), true);
+    // the body of a do-while always runs, so its declarations survive
+    let do_while_declaration_valid = Statement::Sequence {
+        st1: Box::new(Statement::DoWhile {
+            st: Box::new(declare_y),
+            condition: Expression::BoolConstant { b: false },
+        }),
+        st2: Box::new(Statement::StackAssignment {
+            x: "n".to_string(),
+            ex1: Expression::StackVar { x: "y".to_string() },
+        }),
+    };
+    typeCheckStrictStatement(do_while_declaration_valid, hashmapWithNatStackVar.clone(), true);
+
+    // in strict mode a read of an undeclared variable is reported as such
+    let undeclared_read_invalid = Statement::Print {
+        ex1: Expression::StackVar { x: "z".to_string() },
+    };
+    typeCheckStrictStatement(undeclared_read_invalid.clone(), hashmapWithNatStackVar.clone(), false);
+    assert_eq!(
+        typeCheckStrict(undeclared_read_invalid, &mut hashmapWithNatStackVar.clone()),
+        Some("Variable z must be declared before use".to_string())
+    );
+}
+
+fn inferPass(
+    syntax_tree: Statement,
+    hashmap_to_pass: HashMap<String, ExType>,
+    hash_expected: HashMap<String, ExType>,
+) {
+    print!(
+        "Inferring Types of Valid Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Current Typing Context: ");
+    printHashMap(hashmap_to_pass.clone());
+    let inferred = inferTypes(syntax_tree, &hashmap_to_pass);
+    assert!(inferred.is_ok());
+    let inferred = inferred.unwrap();
+    print!("Inferred Typing Context: ");
+    printHashMap(inferred.clone());
+    checkHashMap(inferred.clone(), hash_expected.clone());
+    assert_eq!(inferred, hash_expected);
+    print!("Inference Passed\n\n")
+}
+
+fn inferFail(syntax_tree: Statement, hashmap_to_pass: HashMap<String, ExType>) -> InferenceError {
+    print!(
+        "Inferring Types of Invalid Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Current Typing Context: ");
+    printHashMap(hashmap_to_pass.clone());
+    let inferred = inferTypes(syntax_tree, &hashmap_to_pass);
+    assert!(inferred.is_err());
+    let e = inferred.err().unwrap();
+    print!(
+        "Inference Failed as Expected the error message is:\n{}\n\n",
+        printInferenceError(e.clone())
+    );
+    e
+}
+
+fn testInference(empty_hashmap: HashMap<String, ExType>) {
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+
+    // x is first assigned in the else branch, which typeCheck rejects
+    let else_branch_first = seq(
+        Statement::IfThenElse {
+            condition: Expression::BoolConstant { b: true },
+            then_branch: Box::new(Statement::Skip),
+            else_branch: Box::new(assign("x", Expression::NatConstant { n: 5 })),
+        },
+        assign(
+            "y",
+            Expression::Add {
+                ex1: Box::new(var("x")),
+                ex2: Box::new(Expression::NatConstant { n: 1 }),
+            },
+        ),
+    );
+    typeCheckStatementFail(else_branch_first.clone(), empty_hashmap.clone());
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    hashmap1.insert("y".to_string(), ExType::NatType);
+    inferPass(else_branch_first, empty_hashmap.clone(), hashmap1.clone());
+
+    // a and b only get a type from the later use of a, and p is aliased before allocation
+    let later_use = seq(
+        assign("a", var("b")),
+        seq(
+            Statement::HeapAlias {
+                x: "p".to_string(),
+                y: "q".to_string(),
+            },
+            seq(
+                Statement::HeapNew {
+                    x: "q".to_string(),
+                    ex1: var("a"),
+                },
+                assign(
+                    "c",
+                    Expression::Comparision {
+                        ex1: Box::new(var("a")),
+                        ex2: Box::new(Expression::HeapRead { x: "p".to_string() }),
+                    },
+                ),
+            ),
+        ),
+    );
+    let reordered = seq(
+        assign(
+            "c",
+            Expression::Comparision {
+                ex1: Box::new(var("a")),
+                ex2: Box::new(Expression::HeapRead { x: "p".to_string() }),
+            },
+        ),
+        seq(
+            Statement::HeapNew {
+                x: "q".to_string(),
+                ex1: var("a"),
+            },
+            seq(
+                Statement::HeapAlias {
+                    x: "p".to_string(),
+                    y: "q".to_string(),
+                },
+                assign("a", var("b")),
+            ),
+        ),
+    );
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("a".to_string(), ExType::NatType);
+    hashmap2.insert("b".to_string(), ExType::NatType);
+    hashmap2.insert("c".to_string(), ExType::BoolType);
+    hashmap2.insert("p".to_string(), ExType::PointerType);
+    hashmap2.insert("q".to_string(), ExType::PointerType);
+    typeCheckStatementFail(later_use.clone(), empty_hashmap.clone());
+    inferPass(later_use, empty_hashmap.clone(), hashmap2.clone());
+    inferPass(reordered, empty_hashmap.clone(), hashmap2.clone());
+
+    // only the two assignments to x conflict, the rest is left out of the report
+    let conflicting = seq(
+        assign("x", Expression::NatConstant { n: 5 }),
+        seq(
+            assign("z", Expression::BoolConstant { b: false }),
+            seq(
+                assign("y", var("x")),
+                Statement::While {
+                    condition: var("z"),
+                    st: Box::new(assign("x", Expression::BoolConstant { b: true })),
+                },
+            ),
+        ),
+    );
+    match inferFail(conflicting, empty_hashmap.clone()) {
+        InferenceError::Conflict(core) => assert_eq!(core.len(), 2),
+        InferenceError::Ambiguous(_) => panic!("expected a conflict"),
+    }
+
+    let pointer_on_stack = seq(
+        Statement::HeapNew {
+            x: "p".to_string(),
+            ex1: Expression::NatConstant { n: 1 },
+        },
+        assign("x", var("p")),
+    );
+    match inferFail(pointer_on_stack, empty_hashmap.clone()) {
+        InferenceError::Conflict(core) => assert_eq!(core.len(), 3),
+        InferenceError::Ambiguous(_) => panic!("expected a conflict"),
+    }
+
+    // nothing fixes the type of x or y
+    let unconstrained = assign("x", var("y"));
+    assert_eq!(
+        inferFail(unconstrained, empty_hashmap.clone()),
+        InferenceError::Ambiguous(vec!["x".to_string(), "y".to_string()])
+    );
+}
+
+fn printSecurityContext(ctx: HashMap<String, SecType>) {
+    print!("[ ");
+    for (key, value) in &ctx {
+        print!("\"{}\": {} {}", key, printType(value.tp.clone()), printSecLabel(value.label));
+        match value.cell {
+            Some(cell) => print!(" (cell {}), ", printSecLabel(cell)),
+            None => print!(", "),
+        }
+    }
+    print!(" ]\n")
+}
+
+fn securityCheckPass(syntax_tree: Statement, ctx: HashMap<String, SecType>) {
+    print!(
+        "Security Checking Valid Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Current Security Context: ");
+    printSecurityContext(ctx.clone());
+    let ctx = &mut ctx.clone();
+    assert!(securityCheck(syntax_tree, ctx).is_none());
+    print!("Security Context after check: ");
+    printSecurityContext(ctx.clone());
+    print!("Security Check Passed\n\n")
+}
+
+fn securityCheckFail(syntax_tree: Statement, ctx: HashMap<String, SecType>) {
+    print!(
+        "Security Checking Invalid Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Current Security Context: ");
+    printSecurityContext(ctx.clone());
+    let result = securityCheck(syntax_tree, &mut ctx.clone());
+    assert!(result.is_some());
+    print!(
+        "Security Check Failed as Expected the error message is:\n{}\n\n",
+        result.unwrap()
+    );
+}
+
+fn testSecurity() {
+    let mut ctx = HashMap::new();
+    ctx.insert("h".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("lp".to_string(), SecType::pointer(SecLabel::Low, SecLabel::Low));
+    ctx.insert("hp".to_string(), SecType::pointer(SecLabel::Low, SecLabel::High));
+
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let high_condition = Expression::Comparision {
+        ex1: Box::new(var("h")),
+        ex2: Box::new(Expression::NatConstant { n: 5 }),
+    };
+
+    // t takes the label of h on its first assignment
+    let valid_high_temp = Statement::Sequence {
+        st1: Box::new(Statement::StackAssignment {
+            x: "t".to_string(),
+            ex1: Expression::Add {
+                ex1: Box::new(var("h")),
+                ex2: Box::new(var("l")),
+            },
+        }),
+        st2: Box::new(Statement::IfThenElse {
+            condition: high_condition.clone(),
+            then_branch: Box::new(Statement::HeapUpdate {
+                x: "hp".to_string(),
+                ex1: var("t"),
+            }),
+            else_branch: Box::new(Statement::StackAssignment {
+                x: "t".to_string(),
+                ex1: Expression::NatConstant { n: 0 },
+            }),
+        }),
+    };
+    securityCheckPass(valid_high_temp, ctx.clone());
+
+    let explicit_invalid = Statement::StackAssignment {
+        x: "l".to_string(),
+        ex1: Expression::Add {
+            ex1: Box::new(var("h")),
+            ex2: Box::new(Expression::NatConstant { n: 1 }),
+        },
+    };
+    securityCheckFail(explicit_invalid, ctx.clone());
+
+    let implicit_if_invalid = Statement::IfThenElse {
+        condition: high_condition.clone(),
+        then_branch: Box::new(Statement::StackAssignment {
+            x: "l".to_string(),
+            ex1: Expression::NatConstant { n: 1 },
+        }),
+        else_branch: Box::new(Statement::Skip),
+    };
+    securityCheckFail(implicit_if_invalid, ctx.clone());
+
+    let implicit_while_invalid = Statement::While {
+        condition: high_condition.clone(),
+        st: Box::new(Statement::HeapUpdate {
+            x: "lp".to_string(),
+            ex1: Expression::NatConstant { n: 1 },
+        }),
+    };
+    securityCheckFail(implicit_while_invalid, ctx.clone());
+
+    let heap_explicit_invalid = Statement::HeapUpdate {
+        x: "lp".to_string(),
+        ex1: Expression::HeapRead { x: "hp".to_string() },
+    };
+    securityCheckFail(heap_explicit_invalid, ctx.clone());
+
+    // aliasing must not turn the High cell of hp into a Low one
+    let alias_launder_invalid = Statement::HeapAlias {
+        x: "lp".to_string(),
+        y: "hp".to_string(),
+    };
+    securityCheckFail(alias_launder_invalid, ctx.clone());
+
+    // a fresh alias inherits the cell label, so reading through it is still High
+    let alias_read_invalid = Statement::Sequence {
+        st1: Box::new(Statement::HeapAlias {
+            x: "r".to_string(),
+            y: "hp".to_string(),
+        }),
+        st2: Box::new(Statement::StackAssignment {
+            x: "l".to_string(),
+            ex1: Expression::HeapRead { x: "r".to_string() },
+        }),
+    };
+    securityCheckFail(alias_read_invalid, ctx.clone());
+
+    // allocating a new cell for a Low pointer depends on h
+    let new_under_high_invalid = Statement::IfThenElse {
+        condition: high_condition,
+        then_branch: Box::new(Statement::HeapNew {
+            x: "lp".to_string(),
+            ex1: Expression::NatConstant { n: 3 },
+        }),
+        else_branch: Box::new(Statement::Skip),
+    };
+    securityCheckFail(new_under_high_invalid, ctx.clone());
+}
+
+fn executePass(syntax_tree: Statement, state: State, expected: Vec<(&str, Value)>) -> State {
+    print!(
+        "Executing Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Initial State: {}\n", printState(&state));
+    let state = &mut state.clone();
+    let result = execute(&syntax_tree, state);
+    assert!(result.is_ok());
+    print!("Final State: {}\n", printState(state));
+    for (x, v) in expected {
+        assert_eq!(state.store.get(x), Some(&v));
+    }
+    print!("Execution Passed\n\n");
+    state.clone()
+}
+
+fn executeFail(syntax_tree: Statement, state: State) {
+    print!(
+        "Executing Failing Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    print!("Initial State: {}\n", printState(&state));
+    let result = execute(&syntax_tree, &mut state.clone());
+    assert!(result.is_err());
+    print!(
+        "Execution Failed as Expected the error message is:\n{}\n\n",
+        result.err().unwrap()
+    );
+}
+
+fn runArithmeticExamples(
+    empty_hashmap: HashMap<String, ExType>,
+    hashmapWithHeapRead: HashMap<String, ExType>,
+) {
+    let nat = |n: i32| Box::new(Expression::NatConstant { n });
+    let var = |x: &str| Box::new(Expression::StackVar { x: x.to_string() });
+
+    let validSub_heapVar = Expression::Sub {
+        ex1: Box::new(Expression::HeapRead { x: "h".to_string() }),
+        ex2: nat(3),
+    };
+    let validMul_nested = Expression::Mul {
+        ex1: nat(6),
+        ex2: Box::new(Expression::Div {
+            ex1: nat(9),
+            ex2: Box::new(Expression::Mod {
+                ex1: nat(7),
+                ex2: nat(4),
+            }),
+        }),
+    };
+    let invalidDiv_bool = Expression::Div {
+        ex1: nat(6),
+        ex2: Box::new(Expression::BoolConstant { b: true }),
+    };
+    let invalidMod_bool = Expression::Mod {
+        ex1: Box::new(Expression::BoolConstant { b: false }),
+        ex2: nat(2),
+    };
+
+    typeCheckExpressionPass(validSub_heapVar, hashmapWithHeapRead.clone());
+    typeCheckExpressionPass(validMul_nested, empty_hashmap.clone());
+    typeCheckExpressionFail(invalidDiv_bool, empty_hashmap.clone());
+    typeCheckExpressionFail(invalidMod_bool, empty_hashmap.clone());
+
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+
+    // subtraction is truncated, so 3 - 5 stays a natural number
+    let monus = seq(
+        assign("a", Expression::Sub { ex1: nat(3), ex2: nat(5) }),
+        assign("b", Expression::Sub { ex1: nat(5), ex2: nat(3) }),
+    );
+    executePass(
+        monus,
+        State::default(),
+        vec![("a", Value::Nat(0)), ("b", Value::Nat(2))],
+    );
+
+    // factorial of 6, accumulated in a heap cell
+    let factorial = seq(
+        assign("n", Expression::NatConstant { n: 6 }),
+        seq(
+            Statement::HeapNew {
+                x: "acc".to_string(),
+                ex1: Expression::NatConstant { n: 1 },
+            },
+            seq(
+                Statement::While {
+                    condition: Expression::Comparision {
+                        ex1: nat(1),
+                        ex2: var("n"),
+                    },
+                    st: Box::new(seq(
+                        Statement::HeapUpdate {
+                            x: "acc".to_string(),
+                            ex1: Expression::Mul {
+                                ex1: Box::new(Expression::HeapRead { x: "acc".to_string() }),
+                                ex2: var("n"),
+                            },
+                        },
+                        assign("n", Expression::Sub { ex1: var("n"), ex2: nat(1) }),
+                    )),
+                },
+                assign("result", Expression::HeapRead { x: "acc".to_string() }),
+            ),
+        ),
+    );
+    typeCheckStatementPass(factorial.clone(), empty_hashmap.clone(), empty_hashmap.clone());
+    executePass(factorial, State::default(), vec![("result", Value::Nat(720))]);
+
+    // Euclid's algorithm: gcd(84, 36) = 12
+    let gcd = seq(
+        assign("a", Expression::NatConstant { n: 84 }),
+        seq(
+            assign("b", Expression::NatConstant { n: 36 }),
+            Statement::While {
+                condition: Expression::Negation {
+                    ex1: Box::new(Expression::Comparision {
+                        ex1: var("b"),
+                        ex2: nat(0),
+                    }),
+                },
+                st: Box::new(seq(
+                    assign("t", Expression::Mod { ex1: var("a"), ex2: var("b") }),
+                    seq(
+                        assign("a", Expression::StackVar { x: "b".to_string() }),
+                        assign("b", Expression::StackVar { x: "t".to_string() }),
+                    ),
+                )),
+            },
+        ),
+    );
+    typeCheckStatementPass(gcd.clone(), empty_hashmap.clone(), empty_hashmap.clone());
+    executePass(gcd, State::default(), vec![("a", Value::Nat(12))]);
+
+    let division_by_zero = seq(
+        assign("z", Expression::Sub { ex1: nat(2), ex2: nat(2) }),
+        assign("q", Expression::Div { ex1: nat(10), ex2: var("z") }),
+    );
+    typeCheckStatementPass(
+        division_by_zero.clone(),
+        empty_hashmap.clone(),
+        empty_hashmap.clone(),
+    );
+    executeFail(division_by_zero, State::default());
+    executeFail(
+        assign("r", Expression::Mod { ex1: nat(10), ex2: nat(0) }),
+        State::default(),
+    );
+    executeFail(
+        assign("big", Expression::Mul { ex1: nat(65536), ex2: nat(65536) }),
+        State::default(),
+    );
+}
+
+fn runLogicAndComparisonExamples(
+    empty_hashmap: HashMap<String, ExType>,
+    hashmapWithHeapRead: HashMap<String, ExType>,
+) {
+    let tru = || Box::new(Expression::BoolConstant { b: true });
+    let nat = |n: i32| Box::new(Expression::NatConstant { n });
+    let var = |x: &str| Box::new(Expression::StackVar { x: x.to_string() });
+
+    let validOr_implies = Expression::Disjunction {
+        ex1: Box::new(Expression::Implication {
+            ex1: tru(),
+            ex2: Box::new(Expression::LessThan { ex1: nat(1), ex2: nat(2) }),
+        }),
+        ex2: Box::new(Expression::GreaterThan {
+            ex1: Box::new(Expression::HeapRead { x: "h".to_string() }),
+            ex2: nat(0),
+        }),
+    };
+    let validEq_bools = Expression::Equal {
+        ex1: tru(),
+        ex2: Box::new(Expression::GreaterEqual { ex1: nat(3), ex2: nat(3) }),
+    };
+    let validNeq_pointers = Expression::NotEqual {
+        ex1: var("h"),
+        ex2: var("h"),
+    };
+    // the right operand of `or` is the wrong one
+    let invalidOr_nat = Expression::Disjunction {
+        ex1: tru(),
+        ex2: nat(4),
+    };
+    let invalidImplies_both = Expression::Implication {
+        ex1: nat(4),
+        ex2: nat(5),
+    };
+    // the left operand of `<` is the wrong one
+    let invalidLess_bool = Expression::LessThan {
+        ex1: tru(),
+        ex2: nat(5),
+    };
+    let invalidEq_mixed = Expression::Equal {
+        ex1: nat(1),
+        ex2: tru(),
+    };
+    let invalidEq_pointerNat = Expression::Equal {
+        ex1: var("h"),
+        ex2: Box::new(Expression::HeapRead { x: "h".to_string() }),
+    };
+
+    typeCheckExpressionPass(validOr_implies, hashmapWithHeapRead.clone());
+    typeCheckExpressionPass(validEq_bools, empty_hashmap.clone());
+    typeCheckExpressionPass(validNeq_pointers, hashmapWithHeapRead.clone());
+    typeCheckExpressionFail(invalidOr_nat, empty_hashmap.clone());
+    typeCheckExpressionFail(invalidImplies_both, empty_hashmap.clone());
+    typeCheckExpressionFail(invalidLess_bool, empty_hashmap.clone());
+    typeCheckExpressionFail(invalidEq_mixed, empty_hashmap.clone());
+    typeCheckExpressionFail(invalidEq_pointerNat, hashmapWithHeapRead.clone());
+
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+
+    // p and q alias, r holds an equal value at a different location
+    let pointer_equality = seq(
+        Statement::HeapNew {
+            x: "p".to_string(),
+            ex1: Expression::NatConstant { n: 1 },
+        },
+        seq(
+            Statement::HeapAlias {
+                x: "q".to_string(),
+                y: "p".to_string(),
+            },
+            seq(
+                Statement::HeapNew {
+                    x: "r".to_string(),
+                    ex1: Expression::NatConstant { n: 1 },
+                },
+                seq(
+                    assign("same", Expression::Equal { ex1: var("p"), ex2: var("q") }),
+                    seq(
+                        assign("other", Expression::Equal { ex1: var("p"), ex2: var("r") }),
+                        assign(
+                            "values",
+                            Expression::Equal {
+                                ex1: Box::new(Expression::HeapRead { x: "p".to_string() }),
+                                ex2: Box::new(Expression::HeapRead { x: "r".to_string() }),
+                            },
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    );
+    typeCheckStatementPass(
+        pointer_equality.clone(),
+        empty_hashmap.clone(),
+        empty_hashmap.clone(),
+    );
+    executePass(
+        pointer_equality,
+        State::default(),
+        vec![
+            ("same", Value::Bool(true)),
+            ("other", Value::Bool(false)),
+            ("values", Value::Bool(true)),
+        ],
+    );
+
+    let comparisons = seq(
+        assign("lt", Expression::LessThan { ex1: nat(2), ex2: nat(2) }),
+        seq(
+            assign("ge", Expression::GreaterEqual { ex1: nat(2), ex2: nat(2) }),
+            seq(
+                assign("gt", Expression::GreaterThan { ex1: nat(3), ex2: nat(2) }),
+                seq(
+                    assign("ne", Expression::NotEqual { ex1: nat(3), ex2: nat(2) }),
+                    seq(
+                        assign(
+                            "imp",
+                            Expression::Implication {
+                                ex1: Box::new(Expression::BoolConstant { b: false }),
+                                ex2: Box::new(Expression::BoolConstant { b: false }),
+                            },
+                        ),
+                        assign(
+                            "or",
+                            Expression::Disjunction {
+                                ex1: Box::new(Expression::BoolConstant { b: false }),
+                                ex2: tru(),
+                            },
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    );
+    executePass(
+        comparisons,
+        State::default(),
+        vec![
+            ("lt", Value::Bool(false)),
+            ("ge", Value::Bool(true)),
+            ("gt", Value::Bool(true)),
+            ("ne", Value::Bool(true)),
+            ("imp", Value::Bool(true)),
+            ("or", Value::Bool(true)),
+        ],
+    );
+}
+
+fn runRecordExamples(
+    empty_hashmap: HashMap<String, ExType>,
+    hashmapWithHeapRead: HashMap<String, ExType>,
+) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let field = |x: &str, f: &str| Expression::FieldRead {
+        x: x.to_string(),
+        field: f.to_string(),
+    };
+    let new_record = |x: &str, fields: Vec<(&str, Expression)>| Statement::RecordNew {
+        x: x.to_string(),
+        fields: fields
+            .into_iter()
+            .map(|(f, e)| (f.to_string(), e))
+            .collect(),
+    };
+    let alloc_r = new_record(
+        "r",
+        vec![
+            ("val", Expression::NatConstant { n: 5 }),
+            ("flag", Expression::BoolConstant { b: true }),
+        ],
+    );
+    let record_tp = recordType(vec![
+        ("val".to_string(), ExType::NatType),
+        ("flag".to_string(), ExType::BoolType),
+    ]);
+
+    // s lists its fields in another order but has the same structural type as r
+    let records_valid = seq(
+        alloc_r.clone(),
+        seq(
+            Statement::FieldUpdate {
+                x: "r".to_string(),
+                field: "val".to_string(),
+                ex1: Expression::Add {
+                    ex1: Box::new(field("r", "val")),
+                    ex2: Box::new(Expression::NatConstant { n: 1 }),
+                },
+            },
+            seq(
+                assign("b", field("r", "flag")),
+                seq(
+                    new_record(
+                        "s",
+                        vec![
+                            ("flag", Expression::BoolConstant { b: false }),
+                            ("val", Expression::NatConstant { n: 0 }),
+                        ],
+                    ),
+                    seq(
+                        Statement::HeapAlias {
+                            x: "s".to_string(),
+                            y: "r".to_string(),
+                        },
+                        seq(
+                            Statement::FieldUpdate {
+                                x: "s".to_string(),
+                                field: "val".to_string(),
+                                ex1: Expression::NatConstant { n: 7 },
+                            },
+                            assign("v", field("r", "val")),
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    );
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("r".to_string(), record_tp.clone());
+    hashmap1.insert("s".to_string(), record_tp.clone());
+    hashmap1.insert("b".to_string(), ExType::BoolType);
+    hashmap1.insert("v".to_string(), ExType::NatType);
+    typeCheckStatementPass(records_valid.clone(), empty_hashmap.clone(), hashmap1.clone());
+    // the update through the alias s is visible through r
+    executePass(
+        records_valid.clone(),
+        State::default(),
+        vec![("b", Value::Bool(true)), ("v", Value::Nat(7))],
+    );
+    inferPass(records_valid, empty_hashmap.clone(), hashmap1.clone());
+
+    let mut hashmapWithRecord = empty_hashmap.clone();
+    hashmapWithRecord.insert("r".to_string(), record_tp.clone());
+    typeCheckExpressionFail(field("r", "missing"), hashmapWithRecord.clone());
+    typeCheckExpressionFail(field("h", "val"), hashmapWithHeapRead.clone());
+    typeCheckExpressionFail(
+        Expression::HeapRead { x: "r".to_string() },
+        hashmapWithRecord.clone(),
+    );
+    typeCheckStatementFail(
+        Statement::FieldUpdate {
+            x: "r".to_string(),
+            field: "val".to_string(),
+            ex1: Expression::BoolConstant { b: false },
+        },
+        hashmapWithRecord.clone(),
+    );
+    // { val: nat } and { val: nat, flag: bool } are different record types
+    typeCheckStatementFail(
+        seq(
+            new_record("q", vec![("val", Expression::NatConstant { n: 1 })]),
+            Statement::HeapAlias {
+                x: "q".to_string(),
+                y: "r".to_string(),
+            },
+        ),
+        hashmapWithRecord.clone(),
+    );
+    typeCheckStatementFail(
+        new_record(
+            "q",
+            vec![
+                ("val", Expression::NatConstant { n: 1 }),
+                ("val", Expression::NatConstant { n: 2 }),
+            ],
+        ),
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        new_record("q", vec![("p", Expression::StackVar { x: "h".to_string() })]),
+        hashmapWithHeapRead.clone(),
+    );
+    typeCheckStatementFail(assign("x", Expression::StackVar { x: "r".to_string() }), hashmapWithRecord.clone());
+
+    // the type of r is only fixed by the allocation after the read
+    let record_read_first = seq(
+        Statement::IfThenElse {
+            condition: Expression::BoolConstant { b: false },
+            then_branch: Box::new(assign(
+                "y",
+                Expression::Add {
+                    ex1: Box::new(field("r", "val")),
+                    ex2: Box::new(Expression::NatConstant { n: 1 }),
+                },
+            )),
+            else_branch: Box::new(Statement::Skip),
+        },
+        alloc_r.clone(),
+    );
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("r".to_string(), record_tp.clone());
+    hashmap2.insert("y".to_string(), ExType::NatType);
+    inferPass(record_read_first, empty_hashmap.clone(), hashmap2.clone());
+    match inferFail(
+        seq(alloc_r.clone(), assign("z", field("r", "size"))),
+        empty_hashmap.clone(),
+    ) {
+        InferenceError::Conflict(core) => assert_eq!(core.len(), 2),
+        InferenceError::Ambiguous(_) => panic!("expected a conflict"),
+    }
+
+    // all fields of a record share the label of its cell
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert(
+        "hr".to_string(),
+        SecType {
+            tp: record_tp,
+            label: SecLabel::Low,
+            cell: Some(SecLabel::High),
+        },
+    );
+    securityCheckFail(assign("l", field("hr", "val")), ctx.clone());
+    securityCheckPass(
+        Statement::FieldUpdate {
+            x: "hr".to_string(),
+            field: "val".to_string(),
+            ex1: Expression::StackVar { x: "l".to_string() },
+        },
+        ctx.clone(),
+    );
+}
+
+fn checkBoundsReports(syntax_tree: Statement, expected: usize) {
+    print!(
+        "Checking Array Bounds of Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    let reports = checkBounds(&syntax_tree);
+    for report in reports.iter() {
+        print!("{}\n", report);
+    }
+    assert_eq!(reports.len(), expected);
+    print!("Bounds Check found {} out-of-bounds accesses as expected\n\n", expected);
+}
+
+fn runArrayExamples(
+    empty_hashmap: HashMap<String, ExType>,
+    hashmapWithHeapRead: HashMap<String, ExType>,
+) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let read = |x: &str, index: Expression| Expression::ArrayRead {
+        x: x.to_string(),
+        index: Box::new(index),
+    };
+    let write = |x: &str, index: Expression, ex1: Expression| Statement::ArrayUpdate {
+        x: x.to_string(),
+        index,
+        ex1,
+    };
+    let new_array = |x: &str, size: Expression, ex1: Expression| Statement::ArrayNew {
+        x: x.to_string(),
+        size,
+        ex1,
+    };
+    let len = |x: &str| Expression::ArrayLength { x: x.to_string() };
+
+    // fills a with the squares of its indices and sums them up
+    let squares = seq(
+        new_array("a", nat(4), nat(0)),
+        seq(
+            Statement::StackAssignment {
+                x: "i".to_string(),
+                ex1: nat(0),
+            },
+            seq(
+                Statement::StackAssignment {
+                    x: "sum".to_string(),
+                    ex1: nat(0),
+                },
+                Statement::While {
+                    condition: Expression::LessThan {
+                        ex1: Box::new(var("i")),
+                        ex2: Box::new(len("a")),
+                    },
+                    st: Box::new(seq(
+                        write(
+                            "a",
+                            var("i"),
+                            Expression::Mul {
+                                ex1: Box::new(var("i")),
+                                ex2: Box::new(var("i")),
+                            },
+                        ),
+                        seq(
+                            Statement::StackAssignment {
+                                x: "sum".to_string(),
+                                ex1: Expression::Add {
+                                    ex1: Box::new(var("sum")),
+                                    ex2: Box::new(read("a", var("i"))),
+                                },
+                            },
+                            Statement::StackAssignment {
+                                x: "i".to_string(),
+                                ex1: Expression::Add {
+                                    ex1: Box::new(var("i")),
+                                    ex2: Box::new(nat(1)),
+                                },
+                            },
+                        ),
+                    )),
+                },
+            ),
+        ),
+    );
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("a".to_string(), ExType::ArrayType);
+    hashmap1.insert("i".to_string(), ExType::NatType);
+    hashmap1.insert("sum".to_string(), ExType::NatType);
+    typeCheckStatementPass(squares.clone(), empty_hashmap.clone(), hashmap1.clone());
+    let state = executePass(
+        squares.clone(),
+        State::default(),
+        vec![("i", Value::Nat(4)), ("sum", Value::Nat(14))],
+    );
+    assert_eq!(state.heap[0], HeapCell::Array(vec![0, 1, 4, 9]));
+    inferPass(squares.clone(), empty_hashmap.clone(), hashmap1.clone());
+    checkBoundsReports(squares, 0);
+
+    let mut hashmapWithArray = empty_hashmap.clone();
+    hashmapWithArray.insert("a".to_string(), ExType::ArrayType);
+    typeCheckExpressionPass(len("a"), hashmapWithArray.clone());
+    typeCheckExpressionFail(read("a", Expression::BoolConstant { b: true }), hashmapWithArray.clone());
+    typeCheckExpressionFail(read("h", nat(0)), hashmapWithHeapRead.clone());
+    typeCheckExpressionFail(len("h"), hashmapWithHeapRead.clone());
+    typeCheckExpressionFail(Expression::HeapRead { x: "a".to_string() }, hashmapWithArray.clone());
+    typeCheckStatementFail(write("a", nat(0), Expression::BoolConstant { b: false }), hashmapWithArray.clone());
+    typeCheckStatementFail(new_array("b", Expression::BoolConstant { b: true }, nat(0)), empty_hashmap.clone());
+    typeCheckStatementFail(new_array("h", nat(1), nat(0)), hashmapWithHeapRead.clone());
+    typeCheckStatementFail(
+        Statement::StackAssignment {
+            x: "x".to_string(),
+            ex1: var("a"),
+        },
+        hashmapWithArray.clone(),
+    );
+
+    // accesses past the end fail at run time, and are found statically when the index is known
+    let read_past_end = seq(
+        new_array("a", nat(3), nat(1)),
+        Statement::StackAssignment {
+            x: "x".to_string(),
+            ex1: read("a", len("a")),
+        },
+    );
+    executeFail(read_past_end.clone(), State::default());
+    checkBoundsReports(read_past_end, 1);
+    let write_past_end = seq(
+        new_array("a", nat(2), nat(0)),
+        seq(
+            Statement::StackAssignment {
+                x: "k".to_string(),
+                ex1: Expression::Add {
+                    ex1: Box::new(nat(1)),
+                    ex2: Box::new(nat(1)),
+                },
+            },
+            write("a", var("k"), nat(5)),
+        ),
+    );
+    executeFail(write_past_end.clone(), State::default());
+    checkBoundsReports(write_past_end, 1);
+    executeFail(new_array("a", nat(2000000000), nat(0)), State::default());
+    // the length of a depends on the branch taken, so nothing is known about a[2]
+    let unknown_length = seq(
+        Statement::IfThenElse {
+            condition: Expression::BoolConstant { b: true },
+            then_branch: Box::new(new_array("a", nat(3), nat(0))),
+            else_branch: Box::new(new_array("a", nat(1), nat(0))),
+        },
+        write("a", nat(2), nat(5)),
+    );
+    executePass(unknown_length.clone(), State::default(), vec![]);
+    checkBoundsReports(unknown_length, 0);
+    // k changes inside the loop, so its value before the loop says nothing about a[k]
+    let changing_index = seq(
+        new_array("a", nat(2), nat(0)),
+        seq(
+            Statement::StackAssignment {
+                x: "k".to_string(),
+                ex1: nat(5),
+            },
+            Statement::While {
+                condition: Expression::GreaterEqual {
+                    ex1: Box::new(var("k")),
+                    ex2: Box::new(len("a")),
+                },
+                st: Box::new(Statement::StackAssignment {
+                    x: "k".to_string(),
+                    ex1: Expression::Sub {
+                        ex1: Box::new(var("k")),
+                        ex2: Box::new(nat(1)),
+                    },
+                }),
+            },
+        ),
+    );
+    let changing_index = seq(changing_index, write("a", var("k"), nat(1)));
+    executePass(changing_index.clone(), State::default(), vec![("k", Value::Nat(1))]);
+    checkBoundsReports(changing_index, 0);
+
+    // all elements share the label of the array's cell, and a High index leaks through a write
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    ctx.insert(
+        "la".to_string(),
+        SecType {
+            tp: ExType::ArrayType,
+            label: SecLabel::Low,
+            cell: Some(SecLabel::Low),
+        },
+    );
+    securityCheckPass(write("la", var("l"), var("l")), ctx.clone());
+    securityCheckFail(write("la", var("s"), nat(1)), ctx.clone());
+    securityCheckFail(
+        Statement::StackAssignment {
+            x: "l".to_string(),
+            ex1: read("la", var("s")),
+        },
+        ctx.clone(),
+    );
+}
+
+fn runProcedureExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let call = |x: Option<&str>, name: &str, args: Vec<Expression>| Statement::Call {
+        x: x.map(|x| x.to_string()),
+        name: name.to_string(),
+        args,
+    };
+    let param = |p: &str, tp: ExType| (p.to_string(), tp);
+
+    // the pointers are copied, the cells they point to are shared with the caller
+    let swap = Statement::Procedure {
+        name: "swap".to_string(),
+        params: vec![param("p", ExType::PointerType), param("q", ExType::PointerType)],
+        body: Box::new(seq(
+            assign("t", Expression::HeapRead { x: "p".to_string() }),
+            seq(
+                Statement::HeapUpdate {
+                    x: "p".to_string(),
+                    ex1: Expression::HeapRead { x: "q".to_string() },
+                },
+                Statement::HeapUpdate {
+                    x: "q".to_string(),
+                    ex1: var("t"),
+                },
+            ),
+        )),
+        ret: None,
+    };
+    let swap_program = seq(
+        swap.clone(),
+        seq(
+            Statement::HeapNew {
+                x: "a".to_string(),
+                ex1: nat(1),
+            },
+            seq(
+                Statement::HeapNew {
+                    x: "b".to_string(),
+                    ex1: nat(2),
+                },
+                seq(
+                    call(None, "swap", vec![var("a"), var("b")]),
+                    seq(
+                        assign("x", Expression::HeapRead { x: "a".to_string() }),
+                        assign("y", Expression::HeapRead { x: "b".to_string() }),
+                    ),
+                ),
+            ),
+        ),
+    );
+    // t is local to swap and does not show up in the caller's context
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("a".to_string(), ExType::PointerType);
+    hashmap1.insert("b".to_string(), ExType::PointerType);
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    hashmap1.insert("y".to_string(), ExType::NatType);
+    typeCheckStatementPass(swap_program.clone(), empty_hashmap.clone(), hashmap1.clone());
+    executePass(
+        swap_program.clone(),
+        State::default(),
+        vec![("x", Value::Nat(2)), ("y", Value::Nat(1))],
+    );
+    inferPass(swap_program, empty_hashmap.clone(), hashmap1.clone());
+
+    // fact is called before it is declared and calls itself
+    let fact = Statement::Procedure {
+        name: "fact".to_string(),
+        params: vec![param("n", ExType::NatType)],
+        body: Box::new(Statement::IfThenElse {
+            condition: Expression::Comparision {
+                ex1: Box::new(var("n")),
+                ex2: Box::new(nat(1)),
+            },
+            then_branch: Box::new(assign("r", nat(1))),
+            else_branch: Box::new(seq(
+                call(
+                    Some("r"),
+                    "fact",
+                    vec![Expression::Sub {
+                        ex1: Box::new(var("n")),
+                        ex2: Box::new(nat(1)),
+                    }],
+                ),
+                assign(
+                    "r",
+                    Expression::Mul {
+                        ex1: Box::new(var("n")),
+                        ex2: Box::new(var("r")),
+                    },
+                ),
+            )),
+        }),
+        ret: Some((ExType::NatType, var("r"))),
+    };
+    let fact_program = seq(call(Some("f"), "fact", vec![nat(5)]), fact.clone());
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("f".to_string(), ExType::NatType);
+    typeCheckStatementPass(fact_program.clone(), empty_hashmap.clone(), hashmap2.clone());
+    executePass(fact_program.clone(), State::default(), vec![("f", Value::Nat(120))]);
+    inferPass(fact_program, empty_hashmap.clone(), hashmap2.clone());
+
+    let mut hashmapWithPointer = empty_hashmap.clone();
+    hashmapWithPointer.insert("a".to_string(), ExType::PointerType);
+    typeCheckStatementFail(
+        seq(swap.clone(), call(None, "swap", vec![var("a"), nat(1)])),
+        hashmapWithPointer.clone(),
+    );
+    typeCheckStatementFail(
+        seq(swap.clone(), call(None, "swap", vec![var("a")])),
+        hashmapWithPointer.clone(),
+    );
+    typeCheckStatementFail(
+        seq(swap.clone(), call(Some("z"), "swap", vec![var("a"), var("a")])),
+        hashmapWithPointer.clone(),
+    );
+    typeCheckStatementFail(call(None, "missing", vec![]), empty_hashmap.clone());
+    typeCheckStatementFail(seq(swap.clone(), swap.clone()), empty_hashmap.clone());
+    // a procedure declared in a branch that never runs cannot be called after it
+    typeCheckStatementFail(
+        seq(
+            Statement::IfThenElse {
+                condition: Expression::BoolConstant { b: false },
+                then_branch: Box::new(swap.clone()),
+                else_branch: Box::new(Statement::Skip),
+            },
+            call(None, "swap", vec![var("a"), var("a")]),
+        ),
+        hashmapWithPointer.clone(),
+    );
+    typeCheckStatementFail(
+        Statement::While {
+            condition: Expression::BoolConstant { b: false },
+            st: Box::new(swap.clone()),
+        },
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        seq(fact.clone(), seq(call(Some("f"), "fact", vec![nat(3)]), assign("z", var("r")))),
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        Statement::Procedure {
+            name: "alloc".to_string(),
+            params: vec![],
+            body: Box::new(Statement::HeapNew {
+                x: "p".to_string(),
+                ex1: nat(0),
+            }),
+            ret: Some((ExType::PointerType, var("p"))),
+        },
+        empty_hashmap.clone(),
+    );
+
+    // unbounded recursion stops at the depth limit instead of overflowing the stack
+    let forever = seq(
+        Statement::Procedure {
+            name: "forever".to_string(),
+            params: vec![param("n", ExType::NatType)],
+            body: Box::new(call(
+                None,
+                "forever",
+                vec![Expression::Add {
+                    ex1: Box::new(var("n")),
+                    ex2: Box::new(nat(1)),
+                }],
+            )),
+            ret: None,
+        },
+        call(None, "forever", vec![nat(0)]),
+    );
+    typeCheckStatementPass(forever.clone(), empty_hashmap.clone(), empty_hashmap.clone());
+    executeFail(forever, State::default());
+
+    // a call mixes everything it is given into its result and into every cell it is given
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    ctx.insert("lp".to_string(), SecType::pointer(SecLabel::Low, SecLabel::Low));
+    ctx.insert("hp".to_string(), SecType::pointer(SecLabel::Low, SecLabel::High));
+    securityCheckPass(seq(fact.clone(), call(Some("l"), "fact", vec![var("l")])), ctx.clone());
+    securityCheckFail(seq(fact.clone(), call(Some("l"), "fact", vec![var("s")])), ctx.clone());
+    securityCheckPass(seq(swap.clone(), call(None, "swap", vec![var("hp"), var("hp")])), ctx.clone());
+    securityCheckFail(seq(swap.clone(), call(None, "swap", vec![var("lp"), var("hp")])), ctx.clone());
+}
+
+fn runAssertExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let positive = |x: &str| Expression::GreaterThan {
+        ex1: Box::new(var(x)),
+        ex2: Box::new(nat(0)),
+    };
+
+    let assert_holds = seq(
+        assign("x", nat(5)),
+        Statement::Assert {
+            condition: positive("x"),
+        },
+    );
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    typeCheckStatementPass(assert_holds.clone(), empty_hashmap.clone(), hashmap1.clone());
+    executePass(assert_holds, State::default(), vec![("x", Value::Nat(5))]);
+    typeCheckStatementFail(Statement::Assert { condition: nat(5) }, empty_hashmap.clone());
+    typeCheckStatementFail(Statement::Assume { condition: var("x") }, hashmap1.clone());
+
+    // the error names the procedure the assertion failed in and the state at that point
+    let assert_fails = seq(
+        Statement::Procedure {
+            name: "check".to_string(),
+            params: vec![("n".to_string(), ExType::NatType)],
+            body: Box::new(Statement::Assert {
+                condition: positive("n"),
+            }),
+            ret: None,
+        },
+        Statement::Call {
+            x: None,
+            name: "check".to_string(),
+            args: vec![nat(0)],
+        },
+    );
+    typeCheckStatementPass(assert_fails.clone(), empty_hashmap.clone(), empty_hashmap.clone());
+    executeFail(assert_fails, State::default());
+
+    // a failing assume ends the run without an error, so y is never assigned
+    let assume_fails = seq(
+        assign("x", nat(0)),
+        seq(
+            Statement::Assume {
+                condition: positive("x"),
+            },
+            assign("y", nat(1)),
+        ),
+    );
+    let state = &mut State::default();
+    assert_eq!(execute(&assume_fails, state), Ok(Outcome::Blocked));
+    assert_eq!(state.store.get("y"), None);
+    let assume_in_loop = seq(
+        assign("i", nat(0)),
+        Statement::While {
+            condition: Expression::BoolConstant { b: true },
+            st: Box::new(seq(
+                Statement::Assume {
+                    condition: Expression::LessThan {
+                        ex1: Box::new(var("i")),
+                        ex2: Box::new(nat(3)),
+                    },
+                },
+                assign(
+                    "i",
+                    Expression::Add {
+                        ex1: Box::new(var("i")),
+                        ex2: Box::new(nat(1)),
+                    },
+                ),
+            )),
+        },
+    );
+    let state = &mut State::default();
+    assert_eq!(execute(&assume_in_loop, state), Ok(Outcome::Blocked));
+    assert_eq!(state.store.get("i"), Some(&Value::Nat(3)));
+
+    // assumptions are hypotheses and assertions are obligations for the static checks
+    let assumed_index = seq(
+        Statement::ArrayNew {
+            x: "a".to_string(),
+            size: nat(3),
+            ex1: nat(0),
+        },
+        seq(
+            Statement::Assume {
+                condition: Expression::Equal {
+                    ex1: Box::new(var("k")),
+                    ex2: Box::new(nat(3)),
+                },
+            },
+            Statement::ArrayUpdate {
+                x: "a".to_string(),
+                index: var("k"),
+                ex1: nat(1),
+            },
+        ),
+    );
+    checkBoundsReports(assumed_index, 1);
+    checkBoundsReports(
+        seq(
+            assign("x", nat(2)),
+            Statement::Assert {
+                condition: Expression::GreaterThan {
+                    ex1: Box::new(var("x")),
+                    ex2: Box::new(nat(5)),
+                },
+            },
+        ),
+        1,
+    );
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("b".to_string(), ExType::BoolType);
+    inferPass(
+        Statement::Assume { condition: var("b") },
+        empty_hashmap.clone(),
+        hashmap2.clone(),
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    securityCheckPass(
+        Statement::Assert {
+            condition: positive("s"),
+        },
+        ctx.clone(),
+    );
+}
+
+fn executeWithInput(syntax_tree: Statement, input: Vec<Value>, expected: Vec<Value>) {
+    print!(
+        "Executing Statement with Input: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    let io = &mut ScriptedIo::new(input);
+    let result = executeWithIo(&syntax_tree, &mut State::default(), io);
+    assert_eq!(result, Ok(Outcome::Normal));
+    let printed: Vec<String> = io.output.iter().map(|v| printValue(v.clone())).collect();
+    print!("Output: [ {} ]\n", printed.join(", "));
+    assert_eq!(io.output, expected);
+    print!("Execution Passed\n\n");
+}
+
+fn runIoExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let read = |x: &str, tp: Option<ExType>| Statement::Read {
+        x: x.to_string(),
+        tp,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+
+    // reads a count n followed by n numbers and prints their sum
+    let sum_input = seq(
+        read("n", Some(ExType::NatType)),
+        seq(
+            assign("sum", nat(0)),
+            seq(
+                assign("i", nat(0)),
+                seq(
+                    Statement::While {
+                        condition: Expression::LessThan {
+                            ex1: Box::new(var("i")),
+                            ex2: Box::new(var("n")),
+                        },
+                        st: Box::new(seq(
+                            read("x", Some(ExType::NatType)),
+                            seq(
+                                assign("sum", add(var("sum"), var("x"))),
+                                assign("i", add(var("i"), nat(1))),
+                            ),
+                        )),
+                    },
+                    Statement::Print { ex1: var("sum") },
+                ),
+            ),
+        ),
+    );
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("n".to_string(), ExType::NatType);
+    hashmap1.insert("sum".to_string(), ExType::NatType);
+    hashmap1.insert("i".to_string(), ExType::NatType);
+    typeCheckStatementPass(sum_input.clone(), empty_hashmap.clone(), hashmap1.clone());
+    executeWithInput(
+        sum_input.clone(),
+        vec![Value::Nat(3), Value::Nat(4), Value::Nat(5), Value::Nat(6)],
+        vec![Value::Nat(15)],
+    );
+    // inference is over the whole program, so it also types x from inside the loop
+    let mut inferred = hashmap1.clone();
+    inferred.insert("x".to_string(), ExType::NatType);
+    inferPass(sum_input.clone(), empty_hashmap.clone(), inferred);
+    // the input runs out before the third number
+    let io = &mut ScriptedIo::new(vec![Value::Nat(3), Value::Nat(4), Value::Nat(5)]);
+    assert!(executeWithIo(&sum_input, &mut State::default(), io).is_err());
+    let io = &mut ScriptedIo::new(vec![Value::Bool(true)]);
+    assert!(executeWithIo(&sum_input, &mut State::default(), io).is_err());
+
+    // an untyped read takes the type b is declared with
+    let read_declared = seq(
+        Statement::Declaration {
+            x: "b".to_string(),
+            tp: ExType::BoolType,
+        },
+        seq(
+            read("b", None),
+            Statement::IfThenElse {
+                condition: var("b"),
+                then_branch: Box::new(Statement::Print { ex1: nat(1) }),
+                else_branch: Box::new(Statement::Print {
+                    ex1: Expression::BoolConstant { b: false },
+                }),
+            },
+        ),
+    );
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("b".to_string(), ExType::BoolType);
+    typeCheckStatementPass(read_declared.clone(), empty_hashmap.clone(), hashmap2.clone());
+    executeWithInput(read_declared.clone(), vec![Value::Bool(true)], vec![Value::Nat(1)]);
+    executeWithInput(read_declared.clone(), vec![Value::Bool(false)], vec![Value::Bool(false)]);
+    inferPass(read_declared, empty_hashmap.clone(), hashmap2.clone());
+
+    typeCheckStatementFail(read("y", None), empty_hashmap.clone());
+    typeCheckStatementFail(read("p", Some(ExType::PointerType)), empty_hashmap.clone());
+    typeCheckStatementFail(
+        seq(read("y", Some(ExType::NatType)), read("y", Some(ExType::BoolType))),
+        empty_hashmap.clone(),
+    );
+    let mut hashmapWithPointer = empty_hashmap.clone();
+    hashmapWithPointer.insert("h".to_string(), ExType::PointerType);
+    typeCheckStatementFail(Statement::Print { ex1: var("h") }, hashmapWithPointer.clone());
+
+    // the output is Low, and which input is read must not depend on High data
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    securityCheckPass(Statement::Print { ex1: var("l") }, ctx.clone());
+    securityCheckFail(
+        Statement::Print {
+            ex1: add(var("l"), var("s")),
+        },
+        ctx.clone(),
+    );
+    securityCheckFail(
+        Statement::IfThenElse {
+            condition: Expression::GreaterThan {
+                ex1: Box::new(var("s")),
+                ex2: Box::new(nat(0)),
+            },
+            then_branch: Box::new(read("s", None)),
+            else_branch: Box::new(Statement::Skip),
+        },
+        ctx.clone(),
+    );
+    securityCheckPass(seq(read("x", Some(ExType::NatType)), assign("l", var("x"))), ctx.clone());
+
+    // a procedure that prints, itself or through another one, is a print of its arguments under pc
+    let leak = Statement::Procedure {
+        name: "leak".to_string(),
+        params: vec![("a".to_string(), ExType::NatType)],
+        body: Box::new(Statement::Print { ex1: var("a") }),
+        ret: None,
+    };
+    let tick = Statement::Procedure {
+        name: "tick".to_string(),
+        params: vec![],
+        body: Box::new(Statement::Call {
+            x: None,
+            name: "leak".to_string(),
+            args: vec![nat(1)],
+        }),
+        ret: None,
+    };
+    let call = |name: &str, args: Vec<Expression>| Statement::Call {
+        x: None,
+        name: name.to_string(),
+        args,
+    };
+    securityCheckPass(seq(leak.clone(), call("leak", vec![var("l")])), ctx.clone());
+    securityCheckFail(seq(leak.clone(), call("leak", vec![var("s")])), ctx.clone());
+    securityCheckPass(seq(leak.clone(), seq(tick.clone(), call("tick", vec![]))), ctx.clone());
+    securityCheckFail(
+        seq(
+            leak.clone(),
+            seq(
+                tick.clone(),
+                Statement::IfThenElse {
+                    condition: Expression::GreaterThan {
+                        ex1: Box::new(var("s")),
+                        ex2: Box::new(nat(0)),
+                    },
+                    then_branch: Box::new(call("tick", vec![])),
+                    else_branch: Box::new(Statement::Skip),
+                },
+            ),
+        ),
+        ctx.clone(),
+    );
+}
+
+fn runNondeterminismExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let choice = |st1: Statement, st2: Statement| Statement::Choice {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let havoc = |x: &str| Statement::Havoc { x: x.to_string() };
+    let declare = |x: &str, tp: ExType| Statement::Declaration {
+        x: x.to_string(),
+        tp,
+    };
+
+    // two choices of x and two values of b give four runs
+    let pick = seq(
+        choice(assign("x", nat(1)), assign("x", nat(2))),
+        seq(declare("b", ExType::BoolType), havoc("b")),
+    );
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    hashmap1.insert("b".to_string(), ExType::BoolType);
+    typeCheckStatementPass(pick.clone(), empty_hashmap.clone(), hashmap1.clone());
+    inferPass(pick.clone(), empty_hashmap.clone(), hashmap1.clone());
+    let runs = executeAll(&pick, &State::default(), 0, 10).unwrap();
+    assert_eq!(runs.len(), 4);
+    let mut outcomes: Vec<(Option<&Value>, Option<&Value>)> = runs
+        .iter()
+        .map(|run| (run.state.store.get("x"), run.state.store.get("b")))
+        .collect();
+    outcomes.sort_by_key(|(x, b)| (x.map(|v| printValue(v.clone())), b.map(|v| printValue(v.clone()))));
+    assert_eq!(
+        outcomes,
+        vec![
+            (Some(&Value::Nat(1)), Some(&Value::Bool(false))),
+            (Some(&Value::Nat(1)), Some(&Value::Bool(true))),
+            (Some(&Value::Nat(2)), Some(&Value::Bool(false))),
+            (Some(&Value::Nat(2)), Some(&Value::Bool(true))),
+        ]
+    );
+    print!("Explored every run of: \n{}\n", printStatement(pick.clone()));
+    for run in runs.iter() {
+        print!("{:?} -> {}\n", run.choices, printState(&run.state));
+    }
+    print!("\n");
+
+    // the same seed makes the same decisions
+    let first = &mut State::default();
+    let second = &mut State::default();
+    assert!(executeSeeded(&pick, first, 7).is_ok());
+    assert!(executeSeeded(&pick, second, 7).is_ok());
+    assert_eq!(first, second);
+
+    // havoc of a nat picks from 0 to havoc_max
+    let guess = seq(
+        assign("n", nat(0)),
+        seq(
+            havoc("n"),
+            Statement::Assert {
+                condition: Expression::LessThan {
+                    ex1: Box::new(var("n")),
+                    ex2: Box::new(nat(3)),
+                },
+            },
+        ),
+    );
+    let runs = executeAll(&guess, &State::default(), 2, 10).unwrap();
+    assert_eq!(runs.len(), 3);
+    assert!(runs.iter().all(|run| run.result.is_ok()));
+    // with one more value the assertion fails on exactly one run
+    let runs = executeAll(&guess, &State::default(), 3, 10).unwrap();
+    let failing: Vec<&Run> = runs.iter().filter(|run| run.result.is_err()).collect();
+    assert_eq!(failing.len(), 1);
+    assert_eq!(failing[0].choices, vec![3]);
+    assert!(executeAll(&guess, &State::default(), -1, 10).is_err());
+    let mut env = Env::new(&guess);
+    env.havoc_max = -1;
+    assert!(executeIn(&guess, &mut State::default(), &mut env).is_err());
+    print!(
+        "Exhaustive execution found the failing run {:?}:\n{}\n\n",
+        failing[0].choices,
+        failing[0].result.clone().err().unwrap()
+    );
+    assert!(executeAll(&guess, &State::default(), 100, 10).is_err());
+
+    // x is bound by only one branch
+    typeCheckStatementFail(
+        choice(assign("x", nat(1)), Statement::Skip),
+        empty_hashmap.clone(),
+    );
+    // the same for an if whose else branch binds x, which only the size of the contexts tells apart
+    typeCheckStatementFail(
+        Statement::IfThenElse {
+            condition: Expression::BoolConstant { b: true },
+            then_branch: Box::new(Statement::Skip),
+            else_branch: Box::new(assign("x", nat(1))),
+        },
+        empty_hashmap.clone(),
+    );
+    // the type of an undeclared variable is unknown
+    typeCheckStatementFail(havoc("y"), empty_hashmap.clone());
+    let mut hashmapWithPointer = empty_hashmap.clone();
+    hashmapWithPointer.insert("p".to_string(), ExType::PointerType);
+    typeCheckStatementFail(havoc("p"), hashmapWithPointer);
+
+    // a branch may write past the end of the array
+    checkBoundsReports(
+        seq(
+            Statement::ArrayNew {
+                x: "a".to_string(),
+                size: nat(2),
+                ex1: nat(0),
+            },
+            seq(
+                choice(assign("k", nat(1)), assign("k", nat(0))),
+                Statement::ArrayUpdate {
+                    x: "a".to_string(),
+                    index: var("k"),
+                    ex1: nat(1),
+                },
+            ),
+        ),
+        0,
+    );
+    checkBoundsReports(
+        seq(
+            Statement::ArrayNew {
+                x: "a".to_string(),
+                size: nat(2),
+                ex1: nat(0),
+            },
+            seq(
+                assign("k", nat(5)),
+                seq(
+                    havoc("k"),
+                    Statement::ArrayUpdate {
+                        x: "a".to_string(),
+                        index: var("k"),
+                        ex1: nat(1),
+                    },
+                ),
+            ),
+        ),
+        0,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    securityCheckPass(choice(assign("l", nat(1)), assign("l", nat(2))), ctx.clone());
+    securityCheckPass(havoc("l"), ctx.clone());
+    securityCheckFail(choice(assign("l", var("s")), Statement::Skip), ctx.clone());
+    // havoc under a secret condition reveals it
+    securityCheckFail(
+        Statement::IfThenElse {
+            condition: Expression::GreaterThan {
+                ex1: Box::new(var("s")),
+                ex2: Box::new(nat(0)),
+            },
+            then_branch: Box::new(havoc("l")),
+            else_branch: Box::new(Statement::Skip),
+        },
+        ctx.clone(),
+    );
+}
+
+fn runParallelExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let par = |st1: Statement, st2: Statement| Statement::Parallel {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let atomic = |st: Statement| Statement::Atomic { st: Box::new(st) };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let update = |x: &str, ex1: Expression| Statement::HeapUpdate {
+        x: x.to_string(),
+        ex1,
+    };
+    let raceBetween = |st: Statement, expected: &str| {
+        print!("Executing Racy Statement: \n{}\n", printStatement(st.clone()));
+        let result = execute(&st, &mut State::default());
+        assert!(result.is_err());
+        let e = result.err().unwrap();
+        print!("Race Detected as Expected:\n{}\n\n", e);
+        assert!(e.starts_with(expected));
+    };
+
+    // the branches touch different variables, so every interleaving ends the same
+    let disjoint = par(assign("x", nat(1)), assign("y", nat(2)));
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    hashmap1.insert("y".to_string(), ExType::NatType);
+    typeCheckStatementPass(disjoint.clone(), empty_hashmap.clone(), hashmap1.clone());
+    inferPass(disjoint.clone(), empty_hashmap.clone(), hashmap1.clone());
+    executePass(
+        disjoint.clone(),
+        State::default(),
+        vec![("x", Value::Nat(1)), ("y", Value::Nat(2))],
+    );
+    assert_eq!(executeAll(&disjoint, &State::default(), 0, 10).unwrap().len(), 2);
+    assert_eq!(exploreInterleavings(&disjoint, &State::default(), 0, 10).unwrap().len(), 1);
+
+    // the atomic blocks run in either order
+    let either_order = seq(
+        assign("x", nat(1)),
+        par(
+            atomic(assign("x", add(var("x"), nat(1)))),
+            atomic(assign(
+                "x",
+                Expression::Mul {
+                    ex1: Box::new(var("x")),
+                    ex2: Box::new(nat(3)),
+                },
+            )),
+        ),
+    );
+    let endings = exploreInterleavings(&either_order, &State::default(), 0, 10).unwrap();
+    print!("Interleavings of: \n{}\n", printStatement(either_order.clone()));
+    for run in endings.iter() {
+        print!("{:?} -> {}\n", run.choices, printState(&run.state));
+    }
+    print!("\n");
+    let mut xs: Vec<Option<&Value>> = endings.iter().map(|run| run.state.store.get("x")).collect();
+    xs.sort_by_key(|v| v.map(|v| printValue(v.clone())));
+    assert_eq!(xs, vec![Some(&Value::Nat(4)), Some(&Value::Nat(6))]);
+    let first = &mut State::default();
+    let second = &mut State::default();
+    assert!(executeSeeded(&either_order, first, 3).is_ok());
+    assert!(executeSeeded(&either_order, second, 3).is_ok());
+    assert_eq!(first, second);
+
+    // loops interleave with the other branch one condition or statement at a time
+    let count_both = seq(
+        assign("i", nat(0)),
+        seq(
+            assign("j", nat(0)),
+            par(
+                Statement::While {
+                    condition: Expression::LessThan {
+                        ex1: Box::new(var("i")),
+                        ex2: Box::new(nat(2)),
+                    },
+                    st: Box::new(assign("i", add(var("i"), nat(1)))),
+                },
+                Statement::For {
+                    x: "k".to_string(),
+                    from: nat(1),
+                    to: nat(2),
+                    st: Box::new(assign("j", add(var("j"), var("k")))),
+                },
+            ),
+        ),
+    );
+    let runs = executeAll(&count_both, &State::default(), 0, 1000).unwrap();
+    assert!(runs.len() > 1);
+    assert!(runs
+        .iter()
+        .all(|run| run.state.store.get("i") == Some(&Value::Nat(2)) && run.state.store.get("j") == Some(&Value::Nat(3))));
+
+    // two pointers to the same cell
+    let alias = seq(
+        Statement::HeapNew {
+            x: "p".to_string(),
+            ex1: nat(0),
+        },
+        Statement::HeapAlias {
+            x: "q".to_string(),
+            y: "p".to_string(),
+        },
+    );
+    raceBetween(
+        seq(alias.clone(), par(update("p", nat(1)), update("q", nat(2)))),
+        "data race on loc 0 between `!p = 1` and `!q = 2`",
+    );
+    raceBetween(
+        seq(
+            alias.clone(),
+            par(
+                update("p", nat(1)),
+                assign("y", Expression::HeapRead { x: "q".to_string() }),
+            ),
+        ),
+        "data race on loc 0",
+    );
+    raceBetween(
+        seq(assign("x", nat(0)), par(assign("x", nat(1)), assign("y", var("x")))),
+        "data race on variable x",
+    );
+    // only one side of the race is atomic
+    let half_atomic = seq(
+        assign("x", nat(0)),
+        par(atomic(assign("x", nat(1))), assign("x", nat(2))),
+    );
+    assert!(executeAll(&half_atomic, &State::default(), 0, 10)
+        .unwrap()
+        .iter()
+        .all(|run| run.result.is_err()));
+    // reads alone do not race
+    executePass(
+        seq(
+            alias.clone(),
+            par(
+                assign("a", Expression::HeapRead { x: "p".to_string() }),
+                assign("b", Expression::HeapRead { x: "q".to_string() }),
+            ),
+        ),
+        State::default(),
+        vec![("a", Value::Nat(0)), ("b", Value::Nat(0))],
+    );
+
+    // different elements of one array do not race, the same element does
+    let array = Statement::ArrayNew {
+        x: "a".to_string(),
+        size: nat(2),
+        ex1: nat(0),
+    };
+    let set = |index: i32, value: i32| Statement::ArrayUpdate {
+        x: "a".to_string(),
+        index: nat(index),
+        ex1: nat(value),
+    };
+    executePass(seq(array.clone(), par(set(0, 1), set(1, 2))), State::default(), vec![]);
+    raceBetween(
+        seq(array.clone(), par(set(0, 1), set(0, 2))),
+        "data race on element 0 of loc 0",
+    );
+
+    // a call may write every cell its pointers reach
+    let increment = Statement::Procedure {
+        name: "increment".to_string(),
+        params: vec![("r".to_string(), ExType::PointerType)],
+        body: Box::new(update("r", add(Expression::HeapRead { x: "r".to_string() }, nat(1)))),
+        ret: None,
+    };
+    let call = |x: &str| Statement::Call {
+        x: None,
+        name: "increment".to_string(),
+        args: vec![var(x)],
+    };
+    executePass(
+        seq(
+            increment.clone(),
+            seq(
+                Statement::HeapNew {
+                    x: "p".to_string(),
+                    ex1: nat(0),
+                },
+                seq(
+                    Statement::HeapNew {
+                        x: "q".to_string(),
+                        ex1: nat(0),
+                    },
+                    par(call("p"), call("q")),
+                ),
+            ),
+        ),
+        State::default(),
+        vec![],
+    );
+    raceBetween(
+        seq(increment.clone(), seq(alias.clone(), par(call("p"), call("q")))),
+        "data race on loc 0",
+    );
+
+    // neither branch sees what the other one binds
+    typeCheckStatementFail(par(assign("x", nat(1)), assign("y", var("x"))), empty_hashmap.clone());
+    typeCheckStatementFail(
+        par(assign("x", nat(1)), assign("x", Expression::BoolConstant { b: true })),
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        Statement::While {
+            condition: Expression::BoolConstant { b: true },
+            st: Box::new(par(Statement::Break, Statement::Skip)),
+        },
+        empty_hashmap.clone(),
+    );
+
+    // k may be 5 or 0 when the other branch indexes with it
+    checkBoundsReports(
+        seq(
+            array.clone(),
+            seq(
+                assign("k", nat(0)),
+                par(
+                    assign("k", nat(5)),
+                    Statement::ArrayUpdate {
+                        x: "a".to_string(),
+                        index: var("k"),
+                        ex1: nat(1),
+                    },
+                ),
+            ),
+        ),
+        0,
+    );
+    checkBoundsReports(
+        seq(
+            array.clone(),
+            par(
+                seq(
+                    assign("k", nat(5)),
+                    Statement::ArrayUpdate {
+                        x: "a".to_string(),
+                        index: var("k"),
+                        ex1: nat(1),
+                    },
+                ),
+                Statement::Skip,
+            ),
+        ),
+        1,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    securityCheckPass(par(assign("l", nat(1)), atomic(assign("s", var("l")))), ctx.clone());
+    securityCheckFail(par(Statement::Skip, atomic(assign("l", var("s")))), ctx.clone());
+}
+
+fn runProbabilityExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let flip = |st1: Statement, p: Rational, st2: Statement| Statement::ProbChoice {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+        p,
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let distributionOf = |st: &Statement, max_runs: usize| {
+        let endings = outputDistribution(st, &State::default(), max_runs).unwrap();
+        print!("Distribution of: \n{}\n", printStatement(st.clone()));
+        for ending in endings.iter() {
+            // an error message already shows the state
+            let result = match &ending.result {
+                Ok(outcome) => format!("{:?} {}", outcome, printState(&ending.state)),
+                Err(e) => e.clone(),
+            };
+            print!("{} -> {}\n", printRational(ending.probability), result);
+        }
+        print!("\n");
+        endings
+    };
+
+    assert_eq!(Rational::new(2, -4), Rational::new(-1, 2));
+    assert_eq!(Rational::new(1, 3).add(&Rational::new(1, 6)), Some(Rational::new(1, 2)));
+    assert_eq!(printRational(Rational::new(6, 3)), "2");
+
+    let third = flip(assign("x", nat(1)), Rational::new(1, 3), assign("x", nat(2)));
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("x".to_string(), ExType::NatType);
+    typeCheckStatementPass(third.clone(), empty_hashmap.clone(), hashmap1.clone());
+    inferPass(third.clone(), empty_hashmap.clone(), hashmap1.clone());
+    let endings = distributionOf(&third, 10);
+    assert_eq!(endings.len(), 2);
+    assert_eq!(endings[0].probability, Rational::new(1, 3));
+    assert_eq!(endings[1].probability, Rational::new(2, 3));
+    assert_eq!(expectedValue(&endings, "x"), Ok(Rational::new(5, 3)));
+
+    // the number of heads in three fair flips is binomially distributed
+    let heads = seq(
+        assign("n", nat(0)),
+        Statement::For {
+            x: "i".to_string(),
+            from: nat(1),
+            to: nat(3),
+            st: Box::new(flip(
+                assign("n", add(var("n"), nat(1))),
+                Rational::new(1, 2),
+                Statement::Skip,
+            )),
+        },
+    );
+    let endings = distributionOf(&heads, 100);
+    let mut probabilities: Vec<(Option<&Value>, Rational)> = endings
+        .iter()
+        .map(|ending| (ending.state.store.get("n"), ending.probability))
+        .collect();
+    probabilities.sort_by_key(|(n, _)| n.map(|v| printValue(v.clone())));
+    assert_eq!(
+        probabilities,
+        vec![
+            (Some(&Value::Nat(0)), Rational::new(1, 8)),
+            (Some(&Value::Nat(1)), Rational::new(3, 8)),
+            (Some(&Value::Nat(2)), Rational::new(3, 8)),
+            (Some(&Value::Nat(3)), Rational::new(1, 8)),
+        ]
+    );
+    assert_eq!(expectedValue(&endings, "n"), Ok(Rational::new(3, 2)));
+
+    // coin gives a bool, and runs cut off by an assume do not count towards the expected value
+    let at_least_one = seq(
+        Statement::Coin {
+            x: "b".to_string(),
+            p: Rational::new(1, 2),
+        },
+        seq(
+            Statement::Coin {
+                x: "c".to_string(),
+                p: Rational::new(1, 2),
+            },
+            seq(
+                Statement::Assume {
+                    condition: Expression::Disjunction {
+                        ex1: Box::new(var("b")),
+                        ex2: Box::new(var("c")),
+                    },
+                },
+                seq(
+                    assign("s", nat(0)),
+                    seq(
+                        Statement::IfThenElse {
+                            condition: var("b"),
+                            then_branch: Box::new(assign("s", add(var("s"), nat(1)))),
+                            else_branch: Box::new(Statement::Skip),
+                        },
+                        Statement::IfThenElse {
+                            condition: var("c"),
+                            then_branch: Box::new(assign("s", add(var("s"), nat(1)))),
+                            else_branch: Box::new(Statement::Skip),
+                        },
+                    ),
+                ),
+            ),
+        ),
+    );
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("b".to_string(), ExType::BoolType);
+    hashmap2.insert("c".to_string(), ExType::BoolType);
+    hashmap2.insert("s".to_string(), ExType::NatType);
+    typeCheckStatementPass(at_least_one.clone(), empty_hashmap.clone(), hashmap2.clone());
+    inferPass(at_least_one.clone(), empty_hashmap.clone(), hashmap2.clone());
+    let endings = distributionOf(&at_least_one, 10);
+    assert_eq!(endings.len(), 4);
+    assert_eq!(expectedValue(&endings, "s"), Ok(Rational::new(4, 3)));
+
+    // how likely an assertion is to fail
+    let unlucky = seq(
+        flip(assign("x", nat(1)), Rational::new(1, 10), assign("x", nat(0))),
+        Statement::Assert {
+            condition: Expression::Equal {
+                ex1: Box::new(var("x")),
+                ex2: Box::new(nat(0)),
+            },
+        },
+    );
+    let endings = distributionOf(&unlucky, 10);
+    let failing: Vec<&Ending> = endings.iter().filter(|ending| ending.result.is_err()).collect();
+    assert_eq!(failing.len(), 1);
+    assert_eq!(failing[0].probability, Rational::new(1, 10));
+
+    // a nondeterministic choice has no probability, and a loop running any number of times has too many runs
+    assert!(outputDistribution(
+        &Statement::Choice {
+            st1: Box::new(assign("x", nat(1))),
+            st2: Box::new(assign("x", nat(2))),
+        },
+        &State::default(),
+        10
+    )
+    .is_err());
+    let geometric = seq(
+        assign("n", nat(0)),
+        seq(
+            assign("c", Expression::BoolConstant { b: true }),
+            Statement::While {
+                condition: var("c"),
+                st: Box::new(seq(
+                    assign("n", add(var("n"), nat(1))),
+                    flip(
+                        assign("c", Expression::BoolConstant { b: false }),
+                        Rational::new(1, 2),
+                        Statement::Skip,
+                    ),
+                )),
+            },
+        ),
+    );
+    assert!(outputDistribution(&geometric, &State::default(), 50).is_err());
+
+    // sampling takes the first branch of a 1/4 choice about a quarter of the time
+    let quarter = flip(assign("x", nat(1)), Rational::new(1, 4), assign("x", nat(0)));
+    let mut taken = 0;
+    for seed in 0..1000 {
+        let state = &mut State::default();
+        assert!(executeSeeded(&quarter, state, seed).is_ok());
+        if state.store.get("x") == Some(&Value::Nat(1)) {
+            taken += 1;
+        }
+    }
+    print!("Sampling {} took the first branch {} times out of 1000\n\n", printStatement(quarter.clone()), taken);
+    assert!(taken > 200 && taken < 300);
+
+    typeCheckStatementFail(
+        flip(assign("x", nat(1)), Rational::new(3, 2), assign("x", nat(2))),
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        flip(assign("x", nat(1)), Rational::new(1, 2), Statement::Skip),
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        seq(
+            assign("x", nat(1)),
+            Statement::Coin {
+                x: "x".to_string(),
+                p: Rational::new(1, 2),
+            },
+        ),
+        empty_hashmap.clone(),
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::BoolType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::BoolType, SecLabel::High));
+    let coin = Statement::Coin {
+        x: "l".to_string(),
+        p: Rational::new(1, 2),
+    };
+    securityCheckPass(coin.clone(), ctx.clone());
+    securityCheckFail(
+        Statement::IfThenElse {
+            condition: var("s"),
+            then_branch: Box::new(coin),
+            else_branch: Box::new(Statement::Skip),
+        },
+        ctx.clone(),
+    );
+}
+
+fn runCfgExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let printCfg = |st: &Statement| {
+        let cfg = buildCfg(st);
+        print!("Control-Flow Graph of: \n{}\n{}\n", printStatement(st.clone()), printDot(&cfg));
+        cfg
+    };
+
+    // i = 0 | while header | i = i + 1 | y = i | exit
+    let count = seq(
+        assign("i", nat(0)),
+        seq(
+            Statement::While {
+                condition: less(var("i"), nat(3)),
+                st: Box::new(assign("i", add(var("i"), nat(1)))),
+            },
+            assign("y", var("i")),
+        ),
+    );
+    let cfg = printCfg(&count);
+    assert_eq!(cfg.blocks.len(), 5);
+    assert_eq!(cfg.blocks[0].statements.len(), 1);
+    assert_eq!(successors(&cfg, 1), vec![2, 3]);
+    assert_eq!(successors(&cfg, 2), vec![1]);
+    assert_eq!(cfg.exit, 4);
+    let dom = dominators(&cfg);
+    assert_eq!(dom.idom, vec![None, Some(0), Some(1), Some(1), Some(3)]);
+    assert!(dom.dominates(1, 2) && dom.dominates(1, 3) && !dom.dominates(2, 3));
+    let post = postDominators(&cfg);
+    assert!(post.dominates(3, 0) && post.dominates(1, 2) && !post.dominates(2, 1));
+    let forest = loopForest(&cfg);
+    assert_eq!(forest.len(), 1);
+    assert_eq!(forest[0].header, 1);
+    assert_eq!(forest[0].blocks, vec![1, 2]);
+    assert!(printDot(&cfg).contains("b1 -> b2 [label=\"(i ) < (3)\"]"));
+
+    // the branches of an if join again
+    let branch = seq(
+        Statement::IfThenElse {
+            condition: var("c"),
+            then_branch: Box::new(assign("x", nat(1))),
+            else_branch: Box::new(assign("x", nat(2))),
+        },
+        assign("y", var("x")),
+    );
+    let cfg = printCfg(&branch);
+    let conditions: Vec<bool> = edges(&cfg)
+        .iter()
+        .filter_map(|edge| edge.condition.as_ref().map(|(_, holds)| *holds))
+        .collect();
+    assert_eq!(conditions, vec![true, false]);
+    let post = postDominators(&cfg);
+    assert_eq!(post.idom[0], Some(3));
+    assert!(!post.dominates(1, 0));
+
+    // a for loop inside a while loop, both with a break
+    let nested = Statement::While {
+        condition: var("c"),
+        st: Box::new(seq(
+            Statement::For {
+                x: "i".to_string(),
+                from: nat(1),
+                to: var("n"),
+                st: Box::new(seq(
+                    Statement::IfThenElse {
+                        condition: var("d"),
+                        then_branch: Box::new(Statement::Break),
+                        else_branch: Box::new(Statement::Skip),
+                    },
+                    assign("s", add(var("s"), var("i"))),
+                )),
+            },
+            Statement::IfThenElse {
+                condition: var("e"),
+                then_branch: Box::new(Statement::Break),
+                else_branch: Box::new(Statement::Continue),
+            },
+        )),
+    };
+    let cfg = printCfg(&nested);
+    let forest = loopForest(&cfg);
+    assert_eq!(forest.len(), 2);
+    assert_eq!(forest[0].parent, None);
+    assert_eq!(forest[1].parent, Some(0));
+    assert_eq!(forest[0].children, vec![1]);
+    let inner_body = cfg
+        .blocks
+        .iter()
+        .position(|block| block.statements.iter().any(|st| printStatement(st.clone()).starts_with("s = ")))
+        .unwrap();
+    assert_eq!(loopDepth(&forest, inner_body), 2);
+    // the statements after a break can never run
+    let dom = dominators(&cfg);
+    assert!(dom.reachable.iter().any(|reachable| !reachable));
+    assert!(cfg.blocks[cfg.entry]
+        .statements
+        .is_empty());
+    assert!(cfg
+        .blocks
+        .iter()
+        .any(|block| block.statements.iter().any(|st| printStatement(st.clone()).starts_with("i#next = "))));
+
+    // procedures get graphs of their own
+    let program = seq(
+        Statement::Procedure {
+            name: "twice".to_string(),
+            params: vec![("n".to_string(), ExType::NatType)],
+            body: Box::new(assign("m", add(var("n"), var("n")))),
+            ret: Some((ExType::NatType, var("m"))),
+        },
+        Statement::Call {
+            x: Some("y".to_string()),
+            name: "twice".to_string(),
+            args: vec![nat(2)],
+        },
+    );
+    let cfg = printCfg(&program);
+    assert_eq!(cfg.blocks[cfg.entry].statements.len(), 1);
+    let procedures = procedureCfgs(&program);
+    assert_eq!(procedures.len(), 1);
+    assert_eq!(procedures[0].0, "twice");
+    assert_eq!(procedures[0].1.blocks[0].statements.len(), 1);
+}
+
+// the variables that may still be read, without killing at assignments
+struct ReadLater;
+
+impl Analysis for ReadLater {
+    type Fact = BTreeSet<String>;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, st: &Statement, _at: Point, fact: &Self::Fact) -> Self::Fact {
+        let mut out = fact.clone();
+        out.extend(usedVariables(st));
+        out
+    }
+
+    fn edge(&self, condition: &Expression, _holds: bool, fact: &Self::Fact) -> Self::Fact {
+        let mut read = Vec::new();
+        expressionVariables(condition, &mut read);
+        let mut out = fact.clone();
+        out.extend(read);
+        out
+    }
+}
+
+// an upper bound on a counter, None before anything is known
+#[derive(Clone, PartialEq, Debug)]
+struct UpperBound(Option<i32>);
+
+impl Lattice for UpperBound {
+    fn bottom() -> Self {
+        UpperBound(None)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        UpperBound(self.0.max(other.0))
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.0 <= other.0
+    }
+
+    fn widen(&self, next: &Self) -> Self {
+        if next.0 > self.0 && self.0.is_some() {
+            UpperBound(Some(i32::MAX))
+        } else {
+            self.join(next)
+        }
+    }
+}
+
+// the bound of one counter, kept through constant assignments and increments
+struct CounterBound {
+    x: String,
+}
+
+impl Analysis for CounterBound {
+    type Fact = UpperBound;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        UpperBound(Some(0))
+    }
+
+    fn transfer(&self, st: &Statement, _at: Point, fact: &Self::Fact) -> Self::Fact {
+        match st {
+            Statement::StackAssignment { x, ex1 } if *x == self.x => match ex1 {
+                Expression::NatConstant { n } => UpperBound(Some(*n)),
+                Expression::Add { ex1, ex2 } => match (&**ex1, &**ex2) {
+                    (Expression::StackVar { x }, Expression::NatConstant { n }) if *x == self.x => {
+                        UpperBound(fact.0.map(|m| m.saturating_add(*n)))
+                    }
+                    _ => UpperBound(Some(i32::MAX)),
+                },
+                _ => UpperBound(Some(i32::MAX)),
+            },
+            _ => fact.clone(),
+        }
+    }
+}
+
+fn runDataflowExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let definition = |x: &str, block: usize, index: usize| Definition {
+        x: x.to_string(),
+        at: Point { block, index },
+    };
+    let names = |set: &BTreeSet<String>| set.iter().cloned().collect::<Vec<String>>().join(", ");
+    let printCfg = |st: &Statement| -> Cfg {
+        print!("Dataflow of: \n{}\n", printStatement(st.clone()));
+        buildCfg(st)
+    };
+
+    // i = 0 | while header | i = i + 1 | y = i | exit
+    let count = seq(
+        assign("i", nat(0)),
+        seq(
+            Statement::While {
+                condition: less(var("i"), nat(3)),
+                st: Box::new(assign("i", add(var("i"), nat(1)))),
+            },
+            assign("y", var("i")),
+        ),
+    );
+    let cfg = printCfg(&count);
+    let reaching = reachingDefinitions(&cfg);
+    let at_y: Vec<Definition> = reaching.at(3, 0).iter().cloned().collect();
+    assert_eq!(at_y, vec![definition("i", 0, 0), definition("i", 2, 0)]);
+    assert_eq!(reaching.blockEntry(0).len(), 0);
+    assert!(reaching.blockExit(3).contains(&definition("y", 3, 0)));
+    assert!(!reaching.blockExit(2).contains(&definition("i", 0, 0)));
+
+    // backwards, the condition of the loop is read at its header
+    let read = solve(&ReadLater, &cfg);
+    println!("Read after the first statement: {}", names(read.at(0, 1)));
+    assert_eq!(names(read.at(0, 1)), "i");
+    assert_eq!(read.blockExit(4).len(), 0);
+    assert_eq!(names(read.blockEntry(1)), "i");
+
+    // without widening the bound of i would climb one step at a time
+    let bound = solve(&CounterBound { x: "i".to_string() }, &cfg);
+    assert_eq!(bound.at(0, 1), &UpperBound(Some(0)));
+    assert_eq!(bound.blockEntry(1), &UpperBound(Some(i32::MAX)));
+    let straight = seq(assign("i", nat(4)), assign("i", add(var("i"), nat(1))));
+    let bound = solve(&CounterBound { x: "i".to_string() }, &buildCfg(&straight));
+    assert_eq!(bound.blockExit(0), &UpperBound(Some(5)));
+
+    // an assignment in a branch does not kill the one before the if
+    let branch = seq(
+        assign("x", nat(1)),
+        seq(
+            Statement::IfThenElse {
+                condition: var("c"),
+                then_branch: Box::new(assign("x", nat(2))),
+                else_branch: Box::new(Statement::Skip),
+            },
+            assign("y", var("x")),
+        ),
+    );
+    let cfg = printCfg(&branch);
+    let join = cfg.exit;
+    let reaching = reachingDefinitions(&cfg);
+    let xs: Vec<Definition> = reaching
+        .blockEntry(join)
+        .iter()
+        .filter(|d| d.x == "x")
+        .cloned()
+        .collect();
+    assert_eq!(xs.len(), 2);
+    assert!(xs.contains(&definition("x", 0, 0)));
+
+    // both branches of a parallel composition may or may not have run their assignment
+    let parallel = seq(
+        assign("x", nat(1)),
+        Statement::Parallel {
+            st1: Box::new(assign("x", nat(2))),
+            st2: Box::new(assign("z", nat(3))),
+        },
+    );
+    let cfg = printCfg(&parallel);
+    let reaching = reachingDefinitions(&cfg);
+    assert_eq!(reaching.blockExit(0).len(), 3);
+    assert_eq!(definedVariables(&cfg.blocks[0].statements[1]), vec!["x", "z"]);
+
+    // the statement after a break is never reached and keeps bottom
+    let broken = Statement::While {
+        condition: less(var("i"), nat(3)),
+        st: Box::new(seq(Statement::Break, assign("i", nat(5)))),
+    };
+    let cfg = printCfg(&broken);
+    let dead = (0..cfg.blocks.len())
+        .find(|b| !cfg.blocks[*b].statements.is_empty())
+        .unwrap();
+    let bound = solve(&CounterBound { x: "i".to_string() }, &cfg);
+    assert_eq!(bound.blockEntry(dead), &UpperBound(None));
+    assert_eq!(bound.blockEntry(cfg.exit), &UpperBound(Some(0)));
+    println!("Dataflow examples passed");
+}
+
+fn checkAssignmentReports(syntax_tree: Statement, expected: usize) -> Vec<String> {
+    print!(
+        "Checking Definite Assignment of Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    let reports = checkDefiniteAssignment(&syntax_tree);
+    for report in reports.iter() {
+        print!("{}\n", report);
+    }
+    assert_eq!(reports.len(), expected);
+    print!("Definite Assignment found {} reads before assignment as expected\n\n", expected);
+    reports
+}
+
+fn runDefiniteAssignmentExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let alloc = |x: &str, ex1: Expression| Statement::HeapNew {
+        x: x.to_string(),
+        ex1,
+    };
+    let update = |x: &str, ex1: Expression| Statement::HeapUpdate {
+        x: x.to_string(),
+        ex1,
+    };
+    let ifThen = |condition: Expression, st: Statement| Statement::IfThenElse {
+        condition,
+        then_branch: Box::new(st),
+        else_branch: Box::new(Statement::Skip),
+    };
+    let print = |ex1: Expression| Statement::Print { ex1 };
+
+    // assigned on both branches, or before the if
+    let both = seq(
+        assign("c", Expression::BoolConstant { b: true }),
+        seq(
+            Statement::IfThenElse {
+                condition: var("c"),
+                then_branch: Box::new(assign("x", nat(1))),
+                else_branch: Box::new(assign("x", nat(2))),
+            },
+            assign("y", var("x")),
+        ),
+    );
+    checkAssignmentReports(both, 0);
+
+    // the else branch leaves x unassigned
+    let one = seq(
+        assign("c", Expression::BoolConstant { b: true }),
+        seq(ifThen(var("c"), assign("x", nat(1))), assign("y", var("x"))),
+    );
+    let reports = checkAssignmentReports(one, 1);
+    assert_eq!(
+        reports[0],
+        "Variable x may be unassigned when `y = x ` reads it, on the path b0 -[not c]-> b2 -> b3"
+    );
+
+    // the body of a while loop may not run at all
+    let body = seq(
+        assign("i", nat(0)),
+        seq(
+            Statement::While {
+                condition: less(var("i"), nat(3)),
+                st: Box::new(seq(assign("x", var("i")), assign("i", add(var("i"), nat(1))))),
+            },
+            print(var("x")),
+        ),
+    );
+    let reports = checkAssignmentReports(body, 1);
+    assert!(reports[0].contains("-[not (i ) < (3)]->"));
+
+    // but the one of a do-while loop does, and a for loop assigns its variable before its body
+    let once = seq(
+        Statement::DoWhile {
+            st: Box::new(assign("x", nat(1))),
+            condition: Expression::BoolConstant { b: false },
+        },
+        seq(
+            Statement::For {
+                x: "k".to_string(),
+                from: nat(1),
+                to: var("x"),
+                st: Box::new(print(var("k"))),
+            },
+            print(var("x")),
+        ),
+    );
+    checkAssignmentReports(once, 0);
+
+    // a declaration does not assign, and the condition of a branch reads too
+    let declared = seq(
+        Statement::Declaration {
+            x: "b".to_string(),
+            tp: ExType::BoolType,
+        },
+        ifThen(var("b"), print(nat(1))),
+    );
+    let reports = checkAssignmentReports(declared, 1);
+    assert!(reports[0].contains("the condition `b`"));
+
+    // dereferences need an allocation on every path, aliasing passes it on
+    let pointers = seq(
+        assign("c", Expression::BoolConstant { b: false }),
+        seq(
+            ifThen(var("c"), alloc("p", nat(1))),
+            seq(
+                alloc("q", nat(2)),
+                seq(
+                    Statement::HeapAlias {
+                        x: "r".to_string(),
+                        y: "q".to_string(),
+                    },
+                    seq(update("r", Expression::HeapRead { x: "q".to_string() }), update("p", nat(3))),
+                ),
+            ),
+        ),
+    );
+    let reports = checkAssignmentReports(pointers, 1);
+    assert!(reports[0].starts_with("Pointer p may be unallocated when `!p = 3` dereferences it"));
+    let alias = Statement::HeapAlias {
+        x: "r".to_string(),
+        y: "p".to_string(),
+    };
+    checkAssignmentReports(alias, 1);
+
+    // a procedure only has its parameters, and returns what it may not have assigned
+    let param = |x: &str, tp: ExType| (x.to_string(), tp);
+    let half = Statement::Procedure {
+        name: "half".to_string(),
+        params: vec![param("n", ExType::NatType)],
+        body: Box::new(ifThen(less(nat(0), var("n")), assign("r", Expression::Div {
+            ex1: Box::new(var("n")),
+            ex2: Box::new(nat(2)),
+        }))),
+        ret: Some((ExType::NatType, var("r"))),
+    };
+    let reports = checkAssignmentReports(half, 1);
+    assert!(reports[0].contains("`return r` reads it") && reports[0].ends_with("of procedure half"));
+
+    // both branches of a parallel composition run to the end, but neither waits for the other
+    let parallel = seq(
+        Statement::Parallel {
+            st1: Box::new(assign("x", nat(1))),
+            st2: Box::new(assign("y", nat(2))),
+        },
+        print(add(var("x"), var("y"))),
+    );
+    checkAssignmentReports(parallel, 0);
+    let racy = Statement::Parallel {
+        st1: Box::new(assign("x", nat(1))),
+        st2: Box::new(assign("y", var("x"))),
+    };
+    let reports = checkAssignmentReports(racy, 1);
+    assert!(reports[0].ends_with("of `y = x ` reached by b0"));
+
+    // conditions are not evaluated, so the exit of a loop that never ends still counts as a path
+    let forever = seq(
+        Statement::While {
+            condition: Expression::BoolConstant { b: true },
+            st: Box::new(Statement::Skip),
+        },
+        print(var("x")),
+    );
+    checkAssignmentReports(forever, 1);
+}
+
+// removes the dead stores of syntax_tree and checks that observed ends up the same
+fn eliminateDeadStoresOf(syntax_tree: Statement, observed: &[&str], expected: usize) -> Statement {
+    print!(
+        "Eliminating Dead Stores of Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    let observed: BTreeSet<String> = observed.iter().map(|x| x.to_string()).collect();
+    let (result, removed) = eliminateDeadStores(&syntax_tree, &observed);
+    for line in removed.iter() {
+        print!("{}\n", line);
+    }
+    print!("Result: \n{}\n", printStatement(result.clone()));
+    assert_eq!(removed.len(), expected);
+    let (mut before, mut after) = (State::default(), State::default());
+    let outcome = execute(&syntax_tree, &mut before);
+    assert_eq!(outcome.is_ok(), execute(&result, &mut after).is_ok());
+    for x in observed.iter() {
+        assert_eq!(
+            before.store.get(x).cloned().map(printValue),
+            after.store.get(x).cloned().map(printValue)
+        );
+    }
+    print!("Dead-Store Elimination removed {} statements as expected\n\n", expected);
+    result
+}
+
+fn runLivenessExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let names = |set: &BTreeSet<String>| set.iter().cloned().collect::<Vec<String>>().join(", ");
+
+    // i = 0; s = 0 | while header | s = s + i; t = i; i = i + 1 | exit
+    let sum = seq(
+        assign("i", nat(0)),
+        seq(
+            assign("s", nat(0)),
+            Statement::While {
+                condition: less(var("i"), nat(3)),
+                st: Box::new(seq(
+                    assign("s", add(var("s"), var("i"))),
+                    seq(assign("t", var("i")), assign("i", add(var("i"), nat(1)))),
+                )),
+            },
+        ),
+    );
+    let cfg = buildCfg(&sum);
+    let live = liveVariables(&cfg, &["s".to_string()].into_iter().collect());
+    assert_eq!(names(live.blockEntry(0)), "");
+    assert_eq!(names(live.at(0, 1)), "i");
+    assert_eq!(names(live.blockEntry(1)), "i, s");
+    assert_eq!(names(live.at(2, 2)), "i, s");
+    assert!(!live.at(2, 2).contains("t"));
+    let result = eliminateDeadStoresOf(sum, &["s"], 1);
+    assert!(!printStatement(result).contains("t = i"));
+
+    // an overwritten store, and a chain of stores only read by dead ones
+    let overwritten = seq(
+        assign("x", nat(1)),
+        seq(
+            assign("x", nat(2)),
+            seq(assign("a", nat(3)), seq(assign("b", var("a")), assign("y", var("x")))),
+        ),
+    );
+    let result = eliminateDeadStoresOf(overwritten, &["y"], 3);
+    assert_eq!(printStatement(result), "x = 2; y = x ");
+
+    // skips in sequences go, but a skip is still needed as a branch
+    let skips = seq(
+        Statement::Skip,
+        seq(
+            Statement::IfThenElse {
+                condition: Expression::BoolConstant { b: true },
+                then_branch: Box::new(assign("x", nat(1))),
+                else_branch: Box::new(assign("z", nat(2))),
+            },
+            Statement::Skip,
+        ),
+    );
+    let result = eliminateDeadStoresOf(skips, &["x"], 3);
+    assert_eq!(printStatement(result), "if (true) then { x = 1 } else { skip }");
+
+    // heap updates stay, and keep their pointer live, as the cell may be read through an alias
+    let aliased = seq(
+        Statement::HeapNew {
+            x: "p".to_string(),
+            ex1: nat(0),
+        },
+        seq(
+            Statement::HeapAlias {
+                x: "q".to_string(),
+                y: "p".to_string(),
+            },
+            seq(
+                Statement::HeapUpdate {
+                    x: "p".to_string(),
+                    ex1: nat(1),
+                },
+                seq(
+                    Statement::HeapUpdate {
+                        x: "p".to_string(),
+                        ex1: nat(5),
+                    },
+                    assign("x", Expression::HeapRead { x: "q".to_string() }),
+                ),
+            ),
+        ),
+    );
+    eliminateDeadStoresOf(aliased, &["x"], 0);
+
+    // an assignment that might fail stays, even if nobody reads it
+    let failing = seq(
+        assign("z", nat(0)),
+        seq(
+            assign(
+                "x",
+                Expression::Div {
+                    ex1: Box::new(nat(1)),
+                    ex2: Box::new(var("z")),
+                },
+            ),
+            assign("y", nat(2)),
+        ),
+    );
+    eliminateDeadStoresOf(failing, &["y"], 0);
+
+    // a multiplication may overflow, so it stays unless it folds to a constant
+    let overflowing = seq(
+        assign("y", nat(100000)),
+        seq(
+            assign(
+                "x",
+                Expression::Mul {
+                    ex1: Box::new(var("y")),
+                    ex2: Box::new(var("y")),
+                },
+            ),
+            assign(
+                "z",
+                Expression::Mul {
+                    ex1: Box::new(nat(2)),
+                    ex2: Box::new(nat(3)),
+                },
+            ),
+        ),
+    );
+    let result = eliminateDeadStoresOf(overflowing, &["y"], 1);
+    assert!(printStatement(result).contains("x = (y ) * (y )"));
+
+    // what a break leaves the loop with, and the loop variable of a for loop
+    let broken = seq(
+        Statement::For {
+            x: "k".to_string(),
+            from: nat(1),
+            to: nat(5),
+            st: Box::new(seq(
+                assign("x", var("k")),
+                Statement::IfThenElse {
+                    condition: less(nat(2), var("k")),
+                    then_branch: Box::new(Statement::Break),
+                    else_branch: Box::new(assign("x", nat(0))),
+                },
+            )),
+        },
+        assign("y", var("x")),
+    );
+    eliminateDeadStoresOf(broken, &["y"], 0);
+    let last = seq(
+        assign("k", nat(7)),
+        Statement::For {
+            x: "k".to_string(),
+            from: nat(1),
+            to: nat(0),
+            st: Box::new(assign("w", var("k"))),
+        },
+    );
+    eliminateDeadStoresOf(last, &["k"], 1);
+
+    // a parallel composition is left alone, and a procedure only keeps what it returns
+    let parallel = seq(
+        assign("x", nat(1)),
+        Statement::Parallel {
+            st1: Box::new(assign("y", var("x"))),
+            st2: Box::new(assign("x", nat(2))),
+        },
+    );
+    eliminateDeadStoresOf(parallel, &["y"], 0);
+    let proc = Statement::Procedure {
+        name: "inc".to_string(),
+        params: vec![("n".to_string(), ExType::NatType)],
+        body: Box::new(seq(assign("t", var("n")), assign("r", add(var("n"), nat(1))))),
+        ret: Some((ExType::NatType, var("r"))),
+    };
+    let (_, removed) = eliminateDeadStores(&proc, &BTreeSet::new());
+    assert_eq!(removed, vec!["Removed dead store `t = n ` in procedure inc"]);
+}
+
+// folds the constants of syntax_tree and checks that the result type checks and behaves the same on input
+fn foldConstantsOf(syntax_tree: Statement, input: Vec<Value>, expected: &str) {
+    print!(
+        "Folding Constants of Statement: \n{}\n",
+        printStatement(syntax_tree.clone())
+    );
+    let result = foldConstants(&syntax_tree);
+    print!("Result: \n{}\n", printStatement(result.clone()));
+    assert_eq!(printStatement(result.clone()), expected);
+    assert!(typeCheck(result.clone(), &mut HashMap::new()).is_none());
+    let run = |st: &Statement| {
+        let (state, io) = (&mut State::default(), &mut ScriptedIo::new(input.clone()));
+        // an error message shows the folded expression, so only whether there is one is compared
+        let outcome = executeWithIo(st, state, io).is_ok();
+        (outcome, printState(state), io.output.iter().cloned().map(printValue).collect::<Vec<String>>())
+    };
+    assert_eq!(run(&syntax_tree), run(&result));
+    print!("Constant Folding kept the behaviour as expected\n\n");
+}
+
+fn runConstantFoldingExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let print = |ex1: Expression| Statement::Print { ex1 };
+    let folded = |exp: Expression| printExpression(foldExpression(&exp));
+
+    assert_eq!(folded(add(nat(49), nat(23))), "72");
+    assert_eq!(
+        folded(Expression::Negation {
+            ex1: Box::new(Expression::BoolConstant { b: true })
+        }),
+        "false"
+    );
+    assert_eq!(
+        folded(Expression::Comparision {
+            ex1: Box::new(nat(5)),
+            ex2: Box::new(nat(5)),
+        }),
+        "true"
+    );
+    assert_eq!(
+        folded(Expression::Sub {
+            ex1: Box::new(nat(3)),
+            ex2: Box::new(add(nat(2), nat(2))),
+        }),
+        "0"
+    );
+    // what fails at run time is left to fail
+    assert_eq!(
+        folded(Expression::Div {
+            ex1: Box::new(nat(1)),
+            ex2: Box::new(Expression::Sub {
+                ex1: Box::new(nat(2)),
+                ex2: Box::new(nat(2)),
+            }),
+        }),
+        "(1) / (0)"
+    );
+    assert_eq!(folded(add(nat(i32::MAX), nat(1))), format!("({}) + (1)", i32::MAX));
+    assert_eq!(folded(add(var("x"), add(nat(1), nat(2)))), "(x ) + (3)");
+
+    // values flow into the branch, which is decided, and the loop that never runs goes
+    let straight = seq(
+        assign("x", add(nat(49), nat(23))),
+        seq(
+            assign("y", Expression::Mul {
+                ex1: Box::new(var("x")),
+                ex2: Box::new(nat(2)),
+            }),
+            seq(
+                Statement::IfThenElse {
+                    condition: Expression::GreaterThan {
+                        ex1: Box::new(var("y")),
+                        ex2: Box::new(nat(100)),
+                    },
+                    then_branch: Box::new(assign("z", nat(1))),
+                    else_branch: Box::new(assign("z", nat(2))),
+                },
+                seq(
+                    Statement::While {
+                        condition: less(var("x"), nat(10)),
+                        st: Box::new(assign("x", add(var("x"), nat(1)))),
+                    },
+                    print(var("z")),
+                ),
+            ),
+        ),
+    );
+    foldConstantsOf(straight, vec![], "x = 72; y = 144; z = 1; print (1)");
+
+    // a loop forgets what its body assigns, the rest is still known after it
+    let counting = seq(
+        assign("i", nat(0)),
+        seq(
+            assign("s", nat(5)),
+            seq(
+                Statement::While {
+                    condition: less(var("i"), var("s")),
+                    st: Box::new(assign("i", add(var("i"), nat(1)))),
+                },
+                assign("t", add(var("s"), var("i"))),
+            ),
+        ),
+    );
+    foldConstantsOf(
+        counting,
+        vec![],
+        "i = 0; s = 5; while ((i ) < (5)) do { i = (i ) + (1)}; t = (5) + (i )",
+    );
+
+    // branches keep what they agree on, and what is read is unknown
+    let branches = seq(
+        Statement::Read {
+            x: "c".to_string(),
+            tp: Some(ExType::BoolType),
+        },
+        seq(
+            Statement::IfThenElse {
+                condition: var("c"),
+                then_branch: Box::new(seq(assign("a", nat(1)), assign("b", nat(2)))),
+                else_branch: Box::new(seq(assign("a", nat(1)), assign("b", nat(3)))),
+            },
+            print(add(var("a"), var("b"))),
+        ),
+    );
+    let expected = "read c : bool; if (c ) then { a = 1; b = 2 } else { a = 1; b = 3 }; print ((1) + (b ))";
+    foldConstantsOf(branches.clone(), vec![Value::Bool(true)], expected);
+    foldConstantsOf(branches, vec![Value::Bool(false)], expected);
+
+    // a parallel branch cannot rely on what the other one assigns
+    let parallel = seq(
+        assign("x", nat(1)),
+        seq(
+            assign("w", nat(4)),
+            Statement::Parallel {
+                st1: Box::new(seq(assign("y", var("x")), assign("v", var("w")))),
+                st2: Box::new(assign("x", nat(2))),
+            },
+        ),
+    );
+    foldConstantsOf(parallel, vec![], "x = 1; w = 4; { y = x ; v = 4 } || { x = 2 }");
+
+    // a division that fails still does, and an empty for loop goes
+    let failing = seq(
+        assign("z", nat(0)),
+        seq(
+            Statement::For {
+                x: "k".to_string(),
+                from: nat(3),
+                to: var("z"),
+                st: Box::new(print(var("k"))),
+            },
+            assign("q", Expression::Div {
+                ex1: Box::new(nat(7)),
+                ex2: Box::new(var("z")),
+            }),
+        ),
+    );
+    foldConstantsOf(failing, vec![], "z = 0; q = (7) / (0)");
+}
+
+fn checkRangesReports(syntax_tree: Statement, expected: usize) -> Vec<String> {
+    print!(
+        "Checking Ranges of Statement: \n{}\n{}",
+        printStatement(syntax_tree.clone()),
+        printIntervals(&syntax_tree)
+    );
+    let reports = checkRanges(&syntax_tree);
+    for report in reports.iter() {
+        print!("{}\n", report);
+    }
+    assert_eq!(reports.len(), expected);
+    print!("Range Check found {} possible overflows and constant conditions as expected\n\n", expected);
+    reports
+}
+
+fn runIntervalExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let atMost = |ex1: Expression, ex2: Expression| Expression::Comparision {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let cellRead = |x: &str| Expression::HeapRead { x: x.to_string() };
+
+    // counter = 2 | while header | body | exit, where the sum really overflows long before counter reaches 50
+    let fibonacci = seq(
+        assign("fibonacci_index", nat(50)),
+        seq(
+            assign("prev", nat(0)),
+            seq(
+                assign("curr", nat(1)),
+                seq(
+                    assign("counter", nat(2)),
+                    Statement::While {
+                        condition: atMost(var("counter"), var("fibonacci_index")),
+                        st: Box::new(seq(
+                            assign("counter", add(var("counter"), nat(1))),
+                            seq(
+                                assign("fibonacci_number", add(var("curr"), var("prev"))),
+                                seq(assign("prev", var("curr")), assign("curr", var("fibonacci_number"))),
+                            ),
+                        )),
+                    },
+                ),
+            ),
+        ),
+    );
+    let cfg = buildCfg(&fibonacci);
+    let solution = intervals(&cfg);
+    println!("At the loop header: {}", printRanges(solution.blockEntry(1)));
+    assert_eq!(solution.blockEntry(1).get("counter"), Some(Interval::new(2, 51)));
+    assert_eq!(solution.blockEntry(2).get("counter"), Some(Interval::new(2, 50)));
+    assert_eq!(solution.blockEntry(3).get("counter"), Some(Interval::new(51, 51)));
+    assert!(printRanges(solution.blockEntry(1)).contains("counter ∈ [2, 51]"));
+    let reports = checkRangesReports(fibonacci, 1);
+    assert!(reports[0].starts_with("`(curr ) + (prev )` may overflow"));
+
+    // conditions the loop decides
+    let decided = seq(
+        assign("i", nat(0)),
+        seq(
+            Statement::While {
+                condition: less(var("i"), nat(100)),
+                st: Box::new(seq(
+                    assign("i", add(var("i"), nat(1))),
+                    Statement::IfThenElse {
+                        condition: Expression::GreaterThan {
+                            ex1: Box::new(var("i")),
+                            ex2: Box::new(nat(200)),
+                        },
+                        then_branch: Box::new(assign("j", nat(1))),
+                        else_branch: Box::new(Statement::Skip),
+                    },
+                )),
+            },
+            Statement::IfThenElse {
+                condition: Expression::Equal {
+                    ex1: Box::new(var("i")),
+                    ex2: Box::new(nat(100)),
+                },
+                then_branch: Box::new(Statement::Skip),
+                else_branch: Box::new(assign("j", nat(2))),
+            },
+        ),
+    );
+    let reports = checkRangesReports(decided, 2);
+    assert_eq!(reports[0], "Condition `(i ) > (200)` is always false, with i ∈ [1, 100]");
+    assert_eq!(reports[1], "Condition `(i ) == (100)` is always true, with i ∈ [100, 100]");
+
+    // assume narrows, and an unknown input makes no report
+    let assumed = seq(
+        Statement::Read {
+            x: "n".to_string(),
+            tp: Some(ExType::NatType),
+        },
+        seq(
+            Statement::Assume {
+                condition: Expression::Conjunction {
+                    ex1: Box::new(less(var("n"), nat(1000))),
+                    ex2: Box::new(atMost(nat(0), var("n"))),
+                },
+            },
+            seq(
+                assign("m", Expression::Mul {
+                    ex1: Box::new(var("n")),
+                    ex2: Box::new(var("n")),
+                }),
+                seq(
+                    assign("k", Expression::Mul {
+                        ex1: Box::new(var("m")),
+                        ex2: Box::new(var("m")),
+                    }),
+                    Statement::Read {
+                        x: "n".to_string(),
+                        tp: None,
+                    },
+                ),
+            ),
+        ),
+    );
+    let cfg = buildCfg(&assumed);
+    let solution = intervals(&cfg);
+    assert_eq!(solution.at(0, 3).get("m"), Some(Interval::new(0, 998001)));
+    assert_eq!(solution.blockExit(0).get("n"), None);
+    let reports = checkRangesReports(assumed, 1);
+    assert!(reports[0].contains("as its operands are in [0, 998001] and [0, 998001]"));
+
+    // cells, with a weak update through a possible alias
+    let cells = seq(
+        Statement::HeapNew {
+            x: "p".to_string(),
+            ex1: nat(3),
+        },
+        seq(
+            Statement::HeapUpdate {
+                x: "p".to_string(),
+                ex1: add(cellRead("p"), nat(1)),
+            },
+            seq(
+                Statement::HeapNew {
+                    x: "q".to_string(),
+                    ex1: nat(7),
+                },
+                Statement::HeapUpdate {
+                    x: "q".to_string(),
+                    ex1: nat(10),
+                },
+            ),
+        ),
+    );
+    let cfg = buildCfg(&cells);
+    let solution = intervals(&cfg);
+    assert_eq!(solution.at(0, 2).get("!p"), Some(Interval::new(4, 4)));
+    assert_eq!(solution.blockExit(0).get("!q"), Some(Interval::new(10, 10)));
+    assert_eq!(solution.blockExit(0).get("!p"), Some(Interval::new(4, 10)));
+    checkRangesReports(cells, 0);
+
+    // a for loop counts on without overflowing, and a branch that cannot be taken is unreachable
+    let counting = seq(
+        Statement::Read {
+            x: "n".to_string(),
+            tp: Some(ExType::NatType),
+        },
+        seq(
+            Statement::For {
+                x: "k".to_string(),
+                from: nat(1),
+                to: var("n"),
+                st: Box::new(Statement::Print { ex1: var("k") }),
+            },
+            seq(
+                assign("x", nat(5)),
+                Statement::IfThenElse {
+                    condition: less(var("x"), nat(3)),
+                    then_branch: Box::new(assign("y", Expression::Mul {
+                        ex1: Box::new(nat(i32::MAX)),
+                        ex2: Box::new(nat(2)),
+                    })),
+                    else_branch: Box::new(Statement::Skip),
+                },
+            ),
+        ),
+    );
+    let reports = checkRangesReports(counting, 1);
+    assert!(reports[0].starts_with("Condition `(x ) < (3)` is always false"));
+}
+
+fn loopInvariantsOf(syntax_tree: Statement) -> Vec<String> {
+    print!("Loop Invariants of Statement: \n{}\n", printStatement(syntax_tree.clone()));
+    let invariants = loopInvariants(&syntax_tree);
+    for invariant in invariants.iter() {
+        print!("{}\n", invariant);
+    }
+    print!("\n");
+    invariants
+}
+
+fn runOctagonExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let atMost = |ex1: Expression, ex2: Expression| Expression::Comparision {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+
+    // the sum is at least curr, since prev never goes below 0
+    let fibonacci = seq(
+        assign("fibonacci_index", nat(50)),
+        seq(
+            assign("prev", nat(0)),
+            seq(
+                assign("curr", nat(1)),
+                seq(
+                    assign("counter", nat(2)),
+                    Statement::While {
+                        condition: atMost(var("counter"), var("fibonacci_index")),
+                        st: Box::new(seq(
+                            assign("counter", add(var("counter"), nat(1))),
+                            seq(
+                                assign("fibonacci_number", add(var("curr"), var("prev"))),
+                                seq(assign("prev", var("curr")), assign("curr", var("fibonacci_number"))),
+                            ),
+                        )),
+                    },
+                ),
+            ),
+        ),
+    );
+    let invariants = loopInvariantsOf(fibonacci);
+    assert_eq!(invariants.len(), 1);
+    assert!(invariants[0].starts_with("At `while (counter ) <= (fibonacci_index )`: "));
+    assert!(invariants[0].contains("prev ≤ curr"));
+    assert!(invariants[0].contains("counter ≤ 51"));
+    assert!(invariants[0].contains("fibonacci_index = 50"));
+
+    // i and j move towards each other, so the loop keeps their sum, which no interval shows
+    let together = seq(
+        assign("i", nat(0)),
+        seq(
+            assign("j", nat(10)),
+            Statement::While {
+                condition: less(var("i"), var("j")),
+                st: Box::new(seq(
+                    assign("i", add(var("i"), nat(1))),
+                    assign("j", Expression::Sub {
+                        ex1: Box::new(var("j")),
+                        ex2: Box::new(nat(1)),
+                    }),
+                )),
+            },
+        ),
+    );
+    let cfg = buildCfg(&together);
+    let solution = octagons(&cfg);
+    println!("At the loop header: {}", printOctagon(solution.blockEntry(1)));
+    println!("After the loop: {}", printOctagon(solution.blockEntry(cfg.exit)));
+    assert!(printOctagon(solution.blockEntry(1)).contains("i + j ≤ 10, i + j ≥ 10"));
+    assert!(printOctagon(solution.blockEntry(cfg.exit)).contains("i = 5, j = 5"));
+
+    // the guards contradict each other through y, so the branch is never taken
+    let contradiction = seq(
+        Statement::Assume {
+            condition: less(var("x"), var("y")),
+        },
+        seq(
+            Statement::Assume {
+                condition: less(var("y"), add(var("x"), nat(1))),
+            },
+            assign("z", nat(1)),
+        ),
+    );
+    let cfg = buildCfg(&contradiction);
+    let solution = octagons(&cfg);
+    assert_eq!(printOctagon(solution.blockEntry(cfg.exit)), "unreachable");
+}
+
+// the point of the first statement of cfg that prints as text
+fn pointOf(cfg: &Cfg, text: &str) -> Point {
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        for (i, st) in block.statements.iter().enumerate() {
+            if printStatement(st.clone()) == text {
+                return Point { block: b, index: i };
+            }
+        }
+    }
+    panic!("no statement {} in the graph", text);
+}
+
+fn runPointsToExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let new = |x: &str, n: i32| Statement::HeapNew {
+        x: x.to_string(),
+        ex1: nat(n),
+    };
+    let alias = |x: &str, y: &str| Statement::HeapAlias {
+        x: x.to_string(),
+        y: y.to_string(),
+    };
+    let name = |x: &str| x.to_string();
+
+    // r is either a fresh cell or the one p and q share
+    let branches = seq(
+        new("s", 3),
+        seq(
+            new("p", 1),
+            seq(
+                alias("q", "p"),
+                seq(
+                    Statement::Choice {
+                        st1: Box::new(new("r", 2)),
+                        st2: Box::new(alias("r", "q")),
+                    },
+                    Statement::HeapUpdate {
+                        x: name("r"),
+                        ex1: nat(5),
+                    },
+                ),
+            ),
+        ),
+    );
+    print!("Points-to Sets of Statement: \n{}\n{}\n", printStatement(branches.clone()), printPointsTo(&branches));
+    let cfg = buildCfg(&branches);
+    let solution = pointsTo(&cfg);
+    let update = pointOf(&cfg, "!r = 5");
+    assert!(mustAlias(&solution, &name("p"), &name("q"), update));
+    assert!(mayAlias(&solution, &name("r"), &name("p"), update));
+    assert!(!mustAlias(&solution, &name("r"), &name("p"), update));
+    assert!(!mayAlias(&solution, &name("s"), &name("p"), update));
+    assert!(!mayAlias(&solution, &name("s"), &name("r"), update));
+    // before q is assigned, it points nowhere
+    assert!(!mayAlias(&solution, &name("q"), &name("p"), pointOf(&cfg, "q = p")));
+
+    // both come from the same site, but from different runs of it after the first iteration
+    let list = seq(
+        new("curr", 0),
+        seq(
+            Statement::StackAssignment {
+                x: name("i"),
+                ex1: nat(0),
+            },
+            Statement::While {
+                condition: Expression::LessThan {
+                    ex1: Box::new(var("i")),
+                    ex2: Box::new(nat(3)),
+                },
+                st: Box::new(seq(
+                    Statement::StackAssignment {
+                        x: name("prev"),
+                        ex1: var("curr"),
+                    },
+                    seq(
+                        new("curr", 1),
+                        Statement::StackAssignment {
+                            x: name("i"),
+                            ex1: Expression::Add {
+                                ex1: Box::new(var("i")),
+                                ex2: Box::new(nat(1)),
+                            },
+                        },
+                    ),
+                )),
+            },
+        ),
+    );
+    print!("Points-to Sets of Statement: \n{}\n{}\n", printStatement(list.clone()), printPointsTo(&list));
+    let cfg = buildCfg(&list);
+    let solution = pointsTo(&cfg);
+    let step = pointOf(&cfg, "i = (i ) + (1)");
+    assert!(mayAlias(&solution, &name("prev"), &name("curr"), step));
+    assert!(!mustAlias(&solution, &name("prev"), &name("curr"), step));
+    assert!(mustAlias(&solution, &name("prev"), &name("curr"), pointOf(&cfg, "curr = new(1)")));
+    assert!(!mayAlias(&solution, &name("i"), &name("curr"), step));
+}
+
+fn terminationReportOf(syntax_tree: Statement) -> Vec<String> {
+    print!("Termination of Loops in Statement: \n{}\n", printStatement(syntax_tree.clone()));
+    let report = terminationReport(&syntax_tree);
+    for line in report.iter() {
+        print!("{}\n", line);
+    }
+    print!("\n");
+    report
+}
+
+fn runTerminationExamples() {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let less = |ex1: Expression, ex2: Expression| Expression::LessThan {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let atMost = |ex1: Expression, ex2: Expression| Expression::Comparision {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let whileLoop = |condition: Expression, st: Statement| Statement::While {
+        condition,
+        st: Box::new(st),
+    };
+
+    let fibonacci = seq(
+        assign("fibonacci_index", nat(50)),
+        seq(
+            assign("prev", nat(0)),
+            seq(
+                assign("curr", nat(1)),
+                seq(
+                    assign("counter", nat(2)),
+                    whileLoop(
+                        atMost(var("counter"), var("fibonacci_index")),
+                        seq(
+                            assign("counter", add(var("counter"), nat(1))),
+                            seq(
+                                assign("fibonacci_number", add(var("curr"), var("prev"))),
+                                seq(assign("prev", var("curr")), assign("curr", var("fibonacci_number"))),
+                            ),
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    );
+    let report = terminationReportOf(fibonacci.clone());
+    assert_eq!(
+        report,
+        vec!["Loop `while (counter ) <= (fibonacci_index )`: terminates (ranking function fibonacci_index - counter)"]
+    );
+    assert!(checkTermination(&fibonacci).is_empty());
+
+    // n is only decreased where the condition shows it is at least 1, so it does not stop at 0
+    let countdown = whileLoop(
+        Expression::GreaterThan {
+            ex1: Box::new(var("n")),
+            ex2: Box::new(nat(0)),
+        },
+        assign("n", Expression::Sub {
+            ex1: Box::new(var("n")),
+            ex2: Box::new(nat(1)),
+        }),
+    );
+    let report = terminationReportOf(countdown);
+    assert!(report[0].ends_with("terminates (ranking function n)"));
+
+    // the first iteration runs before the condition is checked
+    let steps = seq(
+        assign("i", nat(0)),
+        Statement::DoWhile {
+            st: Box::new(assign("i", add(var("i"), nat(2)))),
+            condition: less(var("i"), nat(10)),
+        },
+    );
+    let report = terminationReportOf(steps);
+    assert_eq!(report, vec!["Loop `do ... while (i ) < (10)`: terminates (ranking function 10 - i)"]);
+
+    // the first loop counts the wrong variable, the second never starts, the third runs n down
+    // by halves or up again and is not known to terminate, and the fourth never stops
+    let stuck = seq(
+        assign("i", nat(0)),
+        seq(
+            whileLoop(less(var("i"), nat(10)), assign("j", add(var("j"), nat(1)))),
+            seq(
+                whileLoop(less(nat(1), nat(0)), assign("m", nat(1))),
+                seq(
+                    whileLoop(
+                        less(nat(1), var("n")),
+                        Statement::IfThenElse {
+                            condition: Expression::Equal {
+                                ex1: Box::new(Expression::Mod {
+                                    ex1: Box::new(var("n")),
+                                    ex2: Box::new(nat(2)),
+                                }),
+                                ex2: Box::new(nat(0)),
+                            },
+                            then_branch: Box::new(assign("n", Expression::Div {
+                                ex1: Box::new(var("n")),
+                                ex2: Box::new(nat(2)),
+                            })),
+                            else_branch: Box::new(assign("n", add(Expression::Mul {
+                                ex1: Box::new(nat(3)),
+                                ex2: Box::new(var("n")),
+                            }, nat(1)))),
+                        },
+                    ),
+                    whileLoop(
+                        Expression::BoolConstant { b: true },
+                        assign("k", add(var("k"), nat(1))),
+                    ),
+                ),
+            ),
+        ),
+    );
+    let report = terminationReportOf(stuck.clone());
+    assert_eq!(report.len(), 4);
+    assert!(report[0].ends_with("may not terminate (the body assigns none of the variables of its condition)"));
+    assert!(report[1].ends_with("terminates (ranking function 0)"));
+    assert!(report[2].ends_with("unknown"));
+    assert!(report[3].ends_with("may not terminate (its condition is always true)"));
+    let lints = checkTermination(&stuck);
+    assert_eq!(lints.len(), 3);
+    assert_eq!(
+        lints[1],
+        "Loop `while (1) < (n )` has no ranking function that shows it terminates"
+    );
+
+    // a loop in a procedure counts its parameter down, breaking out early
+    let procedure = Statement::Procedure {
+        name: "search".to_string(),
+        params: vec![("n".to_string(), ExType::NatType)],
+        body: Box::new(whileLoop(
+            Expression::GreaterEqual {
+                ex1: Box::new(var("n")),
+                ex2: Box::new(nat(1)),
+            },
+            seq(
+                Statement::IfThenElse {
+                    condition: Expression::Equal {
+                        ex1: Box::new(var("n")),
+                        ex2: Box::new(nat(7)),
+                    },
+                    then_branch: Box::new(Statement::Break),
+                    else_branch: Box::new(Statement::Skip),
+                },
+                assign("n", Expression::Sub {
+                    ex1: Box::new(var("n")),
+                    ex2: Box::new(nat(1)),
+                }),
+            ),
+        )),
+        ret: Some((ExType::NatType, var("n"))),
+    };
+    let report = terminationReportOf(procedure);
+    assert_eq!(
+        report,
+        vec!["Loop `while (n ) >= (1)` in procedure search: terminates (ranking function n - 1)"]
+    );
+}
+
+// a program without lints and one with something for every rule
+fn lintSamples() -> (Statement, Statement) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let heapUpdate = |x: &str, ex1: Expression| Statement::HeapUpdate {
+        x: x.to_string(),
+        ex1,
+    };
+
+    let fibonacci = seq(
+        assign("fibonacci_index", nat(50)),
+        seq(
+            assign("prev", nat(0)),
+            seq(
+                assign("curr", nat(1)),
+                seq(
+                    assign("counter", nat(2)),
+                    Statement::While {
+                        condition: Expression::Comparision {
+                            ex1: Box::new(var("counter")),
+                            ex2: Box::new(var("fibonacci_index")),
+                        },
+                        st: Box::new(seq(
+                            assign("counter", add(var("counter"), nat(1))),
+                            seq(
+                                assign("fibonacci_number", add(var("curr"), var("prev"))),
+                                seq(assign("prev", var("curr")), assign("curr", var("fibonacci_number"))),
+                            ),
+                        )),
+                    },
+                ),
+            ),
+        ),
+    );
+    // something for every rule
+    let untidy = seq(
+        assign("unused", nat(1)),
+        seq(
+            assign("x", nat(2)),
+            seq(
+                assign("x", var("x")),
+                seq(
+                    Statement::HeapNew {
+                        x: "p".to_string(),
+                        ex1: nat(0),
+                    },
+                    seq(
+                        Statement::HeapAlias {
+                            x: "p".to_string(),
+                            y: "p".to_string(),
+                        },
+                        seq(
+                            Statement::HeapNew {
+                                x: "q".to_string(),
+                                ex1: nat(0),
+                            },
+                            seq(
+                                heapUpdate("q", nat(1)),
+                                seq(
+                                    heapUpdate("q", nat(2)),
+                                    seq(
+                                        Statement::IfThenElse {
+                                            condition: Expression::LessThan {
+                                                ex1: Box::new(nat(1)),
+                                                ex2: Box::new(nat(2)),
+                                            },
+                                            then_branch: Box::new(Statement::Print { ex1: var("x") }),
+                                            else_branch: Box::new(Statement::Skip),
+                                        },
+                                        seq(
+                                            Statement::IfThenElse {
+                                                condition: Expression::Equal {
+                                                    ex1: Box::new(var("x")),
+                                                    ex2: Box::new(nat(2)),
+                                                },
+                                                then_branch: Box::new(Statement::Print { ex1: var("x") }),
+                                                else_branch: Box::new(Statement::Print { ex1: var("x") }),
+                                            },
+                                            Statement::While {
+                                                condition: Expression::LessThan {
+                                                    ex1: Box::new(var("x")),
+                                                    ex2: Box::new(nat(10)),
+                                                },
+                                                st: Box::new(Statement::Skip),
+                                            },
+                                        ),
+                                    ),
+                                ),
+                            ),
+                        ),
+                    ),
+                ),
+            ),
+        ),
+    );
+    (fibonacci, untidy)
+}
+
+// the lints of every program given, read from its file or from the standard input if there is none
+fn lintCommand(args: &[String]) -> Result<String, String> {
+    let options = lintOptions(args)?;
+    let mut sources = Vec::new();
+    if options.files.is_empty() {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("cannot read the standard input: {}", e))?;
+        sources.push((String::from("the standard input"), text));
+    }
+    for file in options.files.iter() {
+        let text = std::fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e))?;
+        sources.push((file.clone(), text));
+    }
+    let mut out = String::new();
+    for (name, text) in sources {
+        let program = parseProgram(&text).map_err(|e| format!("{}: {}", name, e))?;
+        out += &format!("Linting {}: \n{}\n", name, printStatement(program.clone()));
+        let lints = lint(&program, &options.config);
+        for l in lints.iter() {
+            out += &format!("{}\n", printLint(l));
+        }
+        if options.fix {
+            out += &format!("Fixed: \n{}\n", printStatement(applyFixes(&program, &lints)));
+        }
+        out += "\n";
+    }
+    Ok(out)
+}
+
+// heapy_imp lint [--enable rule] [--disable rule] [--only rule] [--fix] [file ...]
+fn runLintCommand(args: &[String]) {
+    match lintCommand(args) {
+        Ok(out) => print!("{}", out),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn lintOf(syntax_tree: &Statement, config: &LintConfig) -> Vec<String> {
+    print!("Linting Statement: \n{}\n", printStatement(syntax_tree.clone()));
+    let lints: Vec<String> = lint(syntax_tree, config).iter().map(printLint).collect();
+    for l in lints.iter() {
+        print!("{}\n", l);
+    }
+    print!("\n");
+    lints
+}
+
+fn runLintExamples() {
+    let (fibonacci, untidy) = &lintSamples();
+
+    // fibonacci_number is read by the loop
+    assert!(lintOf(fibonacci, &LintConfig::default()).is_empty());
+
+    let lints = lintOf(untidy, &LintConfig::default());
+    let expected = vec![
+        "warning[L001] Variable unused is assigned by `unused = 1` but never read (fix: remove it)",
+        "warning[L002] Self-assignment `x = x` has no effect (fix: remove it)",
+        "warning[L003] Self-alias `p = p` has no effect (fix: remove it)",
+        "warning[L004] Condition `(1) < (2)` is always true (fix: keep the branch)",
+        "warning[L005] Loop `while (x ) < (10)` has an empty body",
+        "warning[L006] Both branches of the if on `(x ) == (2)` are `print (x )` (fix: keep the branch)",
+        "warning[L007] `p = new(0)` allocates a cell that is never dereferenced",
+        "warning[L008] The cell `!q = 1` writes is overwritten by `!q = 2` before it is read",
+    ];
+    assert_eq!(lints, expected);
+
+    // rules can be turned off and on one by one, by code or by name
+    let mut config = LintConfig::none();
+    config.enable(findRule("L002").unwrap());
+    config.enable(findRule("self-alias").unwrap());
+    assert_eq!(lintOf(untidy, &config).len(), 2);
+    let mut config = LintConfig::default();
+    config.disable(Rule::EmptyLoopBody);
+    assert_eq!(lintOf(untidy, &config).len(), 7);
+    assert!(findRule("L042").is_none());
+
+    // a procedure is its own scope, so its x is not the x of the main program
+    let procedure = Statement::Sequence {
+        st1: Box::new(Statement::Procedure {
+            name: "twice".to_string(),
+            params: vec![("x".to_string(), ExType::NatType), ("y".to_string(), ExType::NatType)],
+            body: Box::new(Statement::StackAssignment {
+                x: "r".to_string(),
+                ex1: Expression::Add {
+                    ex1: Box::new(Expression::StackVar { x: "x".to_string() }),
+                    ex2: Box::new(Expression::StackVar { x: "x".to_string() }),
+                },
+            }),
+            ret: Some((ExType::NatType, Expression::StackVar { x: "r".to_string() })),
+        }),
+        st2: Box::new(Statement::StackAssignment {
+            x: "x".to_string(),
+            ex1: Expression::NatConstant { n: 1 },
+        }),
+    };
+    let mut config = LintConfig::none();
+    config.enable(Rule::UnusedVariable);
+    assert_eq!(
+        lintOf(&procedure, &config),
+        vec![
+            "warning[L001] Variable x is assigned by `x = 1` but never read (fix: remove it)",
+            "warning[L001] Parameter y is never read in procedure twice",
+        ]
+    );
+
+    // a fix never drops what can fail: n + 1 may overflow, and n may not be assigned
+    let failing = Statement::Sequence {
+        st1: Box::new(Statement::StackAssignment {
+            x: "y".to_string(),
+            ex1: Expression::Add {
+                ex1: Box::new(Expression::StackVar { x: "n".to_string() }),
+                ex2: Box::new(Expression::NatConstant { n: 1 }),
+            },
+        }),
+        st2: Box::new(Statement::StackAssignment {
+            x: "n".to_string(),
+            ex1: Expression::StackVar { x: "n".to_string() },
+        }),
+    };
+    let mut config = LintConfig::none();
+    config.enable(Rule::UnusedVariable);
+    config.enable(Rule::SelfAssignment);
+    assert_eq!(
+        lintOf(&failing, &config),
+        vec![
+            "warning[L001] Variable y is assigned by `y = (n ) + (1)` but never read",
+            "warning[L002] Self-assignment `n = n` has no effect",
+        ]
+    );
+
+    // only the lints without a fix are left after the fixes
+    let fixed = applyFixes(untidy, &lint(untidy, &LintConfig::default()));
+    print!("Fixed Statement: \n{}\n\n", printStatement(fixed.clone()));
+    assert_eq!(
+        printStatement(fixed.clone()),
+        "x = 2; p = new(0); q = new(0); !q = 1; !q = 2; print (x ); print (x ); while ((x ) < (10)) do { skip}"
+    );
+    let fixedLints = lint(&fixed, &LintConfig::default());
+    assert!(fixedLints.iter().all(|l| l.fix.is_none()));
+
+    // the subcommand reads the program from a file
+    let file = std::env::temp_dir().join("heapy_imp_lint_untidy.imp");
+    std::fs::write(&file, printStatement(untidy.clone())).unwrap();
+    let file = file.to_string_lossy().to_string();
+    let out = lintCommand(&["--only".to_string(), "self-assignment".to_string(), "--fix".to_string(), file.clone()]).unwrap();
+    print!("{}", out);
+    assert!(out.contains("warning[L002] Self-assignment `x = x` has no effect (fix: remove it)"));
+    assert!(!out.contains("L001"));
+    assert!(out.contains("Fixed: \nunused = 1; x = 2; p = new(0); p = p;"));
+    assert!(lintCommand(&["--disable".to_string(), "L042".to_string(), file]).is_err());
+    assert!(lintCommand(&["/nonexistent/program.imp".to_string()]).is_err());
+}
+
+fn parsePass(text: &str) -> Statement {
+    print!("Parsing Program: \n{}\n", text);
+    let parsed = parseProgram(text);
+    assert!(parsed.is_ok(), "{}", parsed.err().unwrap());
+    let parsed = parsed.unwrap();
+    print!("Parsed: \n{}\n\n", printStatement(parsed.clone()));
+    parsed
+}
+
+fn parseFail(text: &str) {
+    print!("Parsing Invalid Program: \n{}\n", text);
+    let parsed = parseProgram(text);
+    assert!(parsed.is_err());
+    print!("Parsing Failed as Expected the error message is:\n{}\n\n", parsed.err().unwrap());
+}
+
+fn runParseExamples() {
+    // a printed program parses back to itself
+    let (fibonacci, untidy) = lintSamples();
+    for program in [fibonacci, untidy] {
+        let printed = printStatement(program);
+        assert_eq!(printStatement(parsePass(&printed)), printed);
+    }
+    let everything = "
+        // one of every statement
+        var n : nat;
+        n := 3;
+        b : bool = not (n < 2) and true;
+        proc twice(x: nat): nat { r = x * 2; return r };
+        proc swap(p: ptr nat, q: ptr nat) { t = !p; !p = !q; !q = t };
+        p = new(n); q = p; swap(p, q);
+        r = new { val: 1, ok: b }; r.val = r.val + 1;
+        a = newarray(n, 0); a[0] = len(a) % 2;
+        m = twice(a[1]);
+        if n == 3 or b implies false then { skip } else { print n - 1 / 1 };
+        while n > 0 do { n = n - 1; if n != 1 then { continue } else { break } };
+        do { n = n + 1 } while n <= 2;
+        for i = 1 to 3 do { assert i >= 1 };
+        { havoc n } [] { read n : nat };
+        { c = coin(1/3) } [1/2] { c = true };
+        { atomic { n = 1 } } || { skip };
+    ";
+    let program = parsePass(everything);
+    let printed = printStatement(program.clone());
+    assert_eq!(printStatement(parsePass(&printed)), printed);
+    let checked = typeCheck(program, &mut HashMap::new());
+    assert!(checked.is_none(), "{}", checked.unwrap());
+    // q = p copies a pointer, so it is an alias, while a copy of a nat is an assignment
+    assert!(printed.contains("q = p;"));
+    assert!(printStatement(parsePass("x = 1; y = x")).ends_with("y = x "));
+
+    parseFail("x = ");
+    parseFail("x = (1 + 2");
+    parseFail("if x then { skip }");
+    parseFail("x = 99999999999");
+    parseFail("x = 1 $ 2");
+    parseFail("skip skip");
+}
+
+fn runLoopExamples(empty_hashmap: HashMap<String, ExType>) {
+    let seq = |st1: Statement, st2: Statement| Statement::Sequence {
+        st1: Box::new(st1),
+        st2: Box::new(st2),
+    };
+    let nat = |n: i32| Expression::NatConstant { n };
+    let var = |x: &str| Expression::StackVar { x: x.to_string() };
+    let assign = |x: &str, ex1: Expression| Statement::StackAssignment {
+        x: x.to_string(),
+        ex1,
+    };
+    let add = |ex1: Expression, ex2: Expression| Expression::Add {
+        ex1: Box::new(ex1),
+        ex2: Box::new(ex2),
+    };
+    let for_loop = |x: &str, from: Expression, to: Expression, st: Statement| Statement::For {
+        x: x.to_string(),
+        from,
+        to,
+        st: Box::new(st),
+    };
+    let when = |condition: Expression, st: Statement| Statement::IfThenElse {
+        condition,
+        then_branch: Box::new(st),
+        else_branch: Box::new(Statement::Skip),
+    };
+
+    // the Fibonacci example without a hand-written counter
+    let fibonacci_for = seq(
+        assign("prev", nat(0)),
+        seq(
+            assign("curr", nat(1)),
+            for_loop(
+                "i",
+                nat(2),
+                nat(20),
+                seq(
+                    assign("next", add(var("prev"), var("curr"))),
+                    seq(assign("prev", var("curr")), assign("curr", var("next"))),
+                ),
+            ),
+        ),
+    );
+    let hashmap1 = &mut empty_hashmap.clone();
+    hashmap1.insert("prev".to_string(), ExType::NatType);
+    hashmap1.insert("curr".to_string(), ExType::NatType);
+    typeCheckStatementPass(fibonacci_for.clone(), empty_hashmap.clone(), hashmap1.clone());
+    executePass(
+        fibonacci_for.clone(),
+        State::default(),
+        vec![("curr", Value::Nat(6765)), ("i", Value::Nat(20))],
+    );
+    let mut inferred = hashmap1.clone();
+    inferred.insert("i".to_string(), ExType::NatType);
+    inferred.insert("next".to_string(), ExType::NatType);
+    inferPass(fibonacci_for, empty_hashmap.clone(), inferred);
+
+    // stops adding once the sum passes 10
+    let sum_until = seq(
+        assign("sum", nat(0)),
+        for_loop(
+            "i",
+            nat(1),
+            nat(100),
+            seq(
+                when(
+                    Expression::GreaterThan {
+                        ex1: Box::new(var("sum")),
+                        ex2: Box::new(nat(10)),
+                    },
+                    Statement::Break,
+                ),
+                assign("sum", add(var("sum"), var("i"))),
+            ),
+        ),
+    );
+    let hashmap2 = &mut empty_hashmap.clone();
+    hashmap2.insert("sum".to_string(), ExType::NatType);
+    typeCheckStatementPass(sum_until.clone(), empty_hashmap.clone(), hashmap2.clone());
+    executePass(
+        sum_until,
+        State::default(),
+        vec![("sum", Value::Nat(15)), ("i", Value::Nat(6))],
+    );
+
+    // skips the even numbers
+    let sum_odd = seq(
+        assign("sum", nat(0)),
+        for_loop(
+            "i",
+            nat(1),
+            nat(9),
+            seq(
+                when(
+                    Expression::Equal {
+                        ex1: Box::new(Expression::Mod {
+                            ex1: Box::new(var("i")),
+                            ex2: Box::new(nat(2)),
+                        }),
+                        ex2: Box::new(nat(0)),
+                    },
+                    Statement::Continue,
+                ),
+                assign("sum", add(var("sum"), var("i"))),
+            ),
+        ),
+    );
+    typeCheckStatementPass(sum_odd.clone(), empty_hashmap.clone(), hashmap2.clone());
+    executePass(sum_odd, State::default(), vec![("sum", Value::Nat(25))]);
+
+    // the body of a do-while runs even though the condition never holds, so y survives it
+    let do_once = seq(
+        Statement::DoWhile {
+            st: Box::new(assign("y", nat(5))),
+            condition: Expression::BoolConstant { b: false },
+        },
+        assign("z", var("y")),
+    );
+    let hashmap3 = &mut empty_hashmap.clone();
+    hashmap3.insert("y".to_string(), ExType::NatType);
+    hashmap3.insert("z".to_string(), ExType::NatType);
+    typeCheckStatementPass(do_once.clone(), empty_hashmap.clone(), hashmap3.clone());
+    executePass(do_once, State::default(), vec![("z", Value::Nat(5))]);
+    let count_down = seq(
+        assign("n", nat(3)),
+        Statement::DoWhile {
+            st: Box::new(assign(
+                "n",
+                Expression::Sub {
+                    ex1: Box::new(var("n")),
+                    ex2: Box::new(nat(1)),
+                },
+            )),
+            condition: Expression::GreaterThan {
+                ex1: Box::new(var("n")),
+                ex2: Box::new(nat(0)),
+            },
+        },
+    );
+    executePass(count_down, State::default(), vec![("n", Value::Nat(0))]);
+    // a break can skip the assignment, so y is not bound after the loop
+    typeCheckStatementFail(
+        seq(
+            Statement::DoWhile {
+                st: Box::new(seq(Statement::Break, assign("y", nat(1)))),
+                condition: Expression::BoolConstant { b: true },
+            },
+            assign("z", var("y")),
+        ),
+        empty_hashmap.clone(),
+    );
+
+    typeCheckStatementFail(Statement::Break, empty_hashmap.clone());
+    typeCheckStatementFail(
+        when(Expression::BoolConstant { b: true }, Statement::Continue),
+        empty_hashmap.clone(),
+    );
+    // loops do not reach into procedure bodies
+    typeCheckStatementFail(
+        Statement::Sequence {
+            st1: Box::new(Statement::Procedure {
+                name: "leave".to_string(),
+                params: vec![],
+                body: Box::new(Statement::Break),
+                ret: None,
+            }),
+            st2: Box::new(Statement::While {
+                condition: Expression::BoolConstant { b: true },
+                st: Box::new(Statement::Call {
+                    x: None,
+                    name: "leave".to_string(),
+                    args: vec![],
+                }),
+            }),
+        },
+        empty_hashmap.clone(),
+    );
+    typeCheckStatementFail(
+        for_loop("i", nat(1), Expression::BoolConstant { b: true }, Statement::Skip),
+        empty_hashmap.clone(),
+    );
+    let mut hashmapWithBool = empty_hashmap.clone();
+    hashmapWithBool.insert("b".to_string(), ExType::BoolType);
+    typeCheckStatementFail(for_loop("b", nat(1), nat(2), Statement::Skip), hashmapWithBool.clone());
+
+    // k is 5 before and after every complete iteration, but 0 when the loop breaks
+    checkBoundsReports(
+        seq(
+            Statement::ArrayNew {
+                x: "a".to_string(),
+                size: nat(3),
+                ex1: nat(0),
+            },
+            seq(
+                assign("k", nat(5)),
+                seq(
+                    Statement::While {
+                        condition: var("c"),
+                        st: Box::new(seq(
+                            assign("k", nat(0)),
+                            seq(when(var("d"), Statement::Break), assign("k", nat(5))),
+                        )),
+                    },
+                    Statement::ArrayUpdate {
+                        x: "a".to_string(),
+                        index: var("k"),
+                        ex1: nat(1),
+                    },
+                ),
+            ),
+        ),
+        0,
+    );
+    // x is 100 after a complete iteration, but 5 when the body continues to the condition
+    checkBoundsReports(
+        seq(
+            Statement::ArrayNew {
+                x: "a".to_string(),
+                size: nat(10),
+                ex1: nat(0),
+            },
+            Statement::DoWhile {
+                st: Box::new(seq(
+                    assign("x", nat(5)),
+                    seq(
+                        Statement::Havoc { x: "c".to_string() },
+                        seq(
+                            when(
+                                Expression::Equal {
+                                    ex1: Box::new(var("c")),
+                                    ex2: Box::new(nat(0)),
+                                },
+                                Statement::Continue,
+                            ),
+                            assign("x", nat(100)),
+                        ),
+                    ),
+                )),
+                condition: Expression::LessThan {
+                    ex1: Box::new(Expression::ArrayRead {
+                        x: "a".to_string(),
+                        index: Box::new(var("x")),
+                    }),
+                    ex2: Box::new(nat(3)),
+                },
+            },
+        ),
+        0,
+    );
+
+    let mut ctx = HashMap::new();
+    ctx.insert("l".to_string(), SecType::new(ExType::NatType, SecLabel::Low));
+    ctx.insert("s".to_string(), SecType::new(ExType::NatType, SecLabel::High));
+    // how far l counts depends on s through the break
+    securityCheckFail(
+        Statement::While {
+            condition: Expression::LessThan {
+                ex1: Box::new(var("l")),
+                ex2: Box::new(nat(10)),
+            },
+            st: Box::new(seq(
+                when(
+                    Expression::GreaterThan {
+                        ex1: Box::new(var("s")),
+                        ex2: Box::new(nat(0)),
+                    },
+                    Statement::Break,
+                ),
+                assign("l", add(var("l"), nat(1))),
+            )),
+        },
+        ctx.clone(),
+    );
+    securityCheckFail(
+        for_loop("i", nat(1), var("s"), assign("l", add(var("l"), nat(1)))),
+        ctx.clone(),
+    );
+    securityCheckPass(
+        for_loop("i", nat(1), var("l"), assign("l", add(var("l"), nat(1)))),
+        ctx.clone(),
+    );
+}
+
 fn runFibonacci(empty_hashmap: HashMap<String, ExType>) {
     let fibonnaci_tree = Statement::Sequence {
         st1: Box::new(Statement::StackAssignment {
@@ -578,6 +5261,11 @@ fn runFibonacci(empty_hashmap: HashMap<String, ExType>) {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|arg| arg == "lint").unwrap_or(false) {
+        runLintCommand(&args[1..]);
+        return;
+    }
     let empty_hashmap = HashMap::new();
 
     let mut hashmapWithHeapRead = HashMap::new();
@@ -593,6 +5281,8 @@ fn main() {
     runNegationExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
     runConjunctionExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
     runComparisonExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
+    runArithmeticExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
+    runLogicAndComparisonExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
     runHeapReadExamples(
         empty_hashmap.clone(),
         hashmapWithHeapRead.clone(),
@@ -620,5 +5310,39 @@ fn main() {
         hashmapWithNatStackVar.clone(),
     );
 
+    testWhile(hashmapWithNatStackVar.clone());
+
+    testDeclarations(empty_hashmap.clone(), hashmapWithNatStackVar.clone());
+
+    testInference(empty_hashmap.clone());
+
+    testSecurity();
+
+    runRecordExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
+
+    runArrayExamples(empty_hashmap.clone(), hashmapWithHeapRead.clone());
+
+    runProcedureExamples(empty_hashmap.clone());
+
+    runAssertExamples(empty_hashmap.clone());
+
+    runIoExamples(empty_hashmap.clone());
+
+    runLoopExamples(empty_hashmap.clone());
+    runNondeterminismExamples(empty_hashmap.clone());
+    runParallelExamples(empty_hashmap.clone());
+    runProbabilityExamples(empty_hashmap.clone());
+    runCfgExamples();
+    runDataflowExamples();
+    runDefiniteAssignmentExamples();
+    runLivenessExamples();
+    runConstantFoldingExamples();
+    runIntervalExamples();
+    runOctagonExamples();
+    runPointsToExamples();
+    runTerminationExamples();
+    runLintExamples();
+    runParseExamples();
+
     runFibonacci(empty_hashmap.clone());
 }
@@ -0,0 +1,622 @@
+/*
+The octagon domain: relations ±x ±y ≤ c between nat variables.
+
+An octagon over the variables x_0, ..., x_n-1 is a difference-bound matrix over
+the 2n values v_2k = x_k and v_2k+1 = -x_k, where m[i][j] bounds v_j - v_i from
+above and None means no bound. So x - y ≤ c, x + y ≤ c and x ≤ c are entries as
+well, the last one as 2x ≤ 2c. Each constraint is stored twice, once for each
+way of writing it, e.g. x - y ≤ c also as (-y) - (-x) ≤ c.
+
+closure      shortest paths between all values, then tightening every 2x ≤ c to
+             an even c as the values are integers, then strengthening every
+             entry by the sum of the bounds on both values, v_j - v_i ≤
+             (v_j - (-v_j)) / 2 + ((-v_i) - v_i) / 2, which also shows emptiness
+join         the greater bound of each entry of the closed octagons
+widening     drops every bound that grew, and narrowing brings back the dropped ones
+x := c       forgets x, then x ≤ c and -x ≤ -c
+x := y + e   forgets x, then x - y lies in the interval of e, and likewise for
+             e + y, while x := x + c shifts every bound on x by c, as does
+             x := x - c where x is surely at least c
+x := e       forgets x, then x lies in the interval of e
+e1 <= e2     for e1 and e2 a variable plus a constant, or a constant, adds
+             e1 - e2 ≤ 0, and <, >=, >, == and their negations likewise
+
+Every other statement forgets the variables it may assign. The relations at
+the header of every loop are reported, leaving out the ones implied by the
+bounds of single variables.
+*/
+
+use std::collections::BTreeSet;
+
+use crate::cfg::buildCfg;
+use crate::cfg::loopForest;
+use crate::cfg::Cfg;
+use crate::cfg::Terminator;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Lattice;
+use crate::dataflow::Point;
+use crate::dataflow::Solution;
+use crate::imp::definedVariables;
+use crate::imp::expressionVariables;
+use crate::imp::printExpression;
+use crate::imp::usedVariables;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+type Bound = Option<i64>;
+
+fn addBounds(b1: Bound, b2: Bound) -> Bound {
+    b1?.checked_add(b2?)
+}
+
+fn minBound(b1: Bound, b2: Bound) -> Bound {
+    match (b1, b2) {
+        (Some(c1), Some(c2)) => Some(c1.min(c2)),
+        (None, b) | (b, None) => b,
+    }
+}
+
+fn maxBound(b1: Bound, b2: Bound) -> Bound {
+    Some(b1?.max(b2?))
+}
+
+fn leqBound(b1: Bound, b2: Bound) -> bool {
+    match (b1, b2) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(c1), Some(c2)) => c1 <= c2,
+    }
+}
+
+// the index of the value +x or -x for variable k
+fn value(k: usize, positive: bool) -> usize {
+    if positive {
+        2 * k
+    } else {
+        2 * k + 1
+    }
+}
+
+// m is None for the empty octagon, where no run gets to
+#[derive(Clone, PartialEq, Debug)]
+pub struct Octagon {
+    pub vars: Vec<String>,
+    m: Option<Vec<Vec<Bound>>>,
+}
+
+impl Octagon {
+    pub fn top(vars: Vec<String>) -> Octagon {
+        let n = 2 * vars.len();
+        let m = (0..n)
+            .map(|i| (0..n).map(|j| if i == j { Some(0) } else { None }).collect())
+            .collect();
+        Octagon { vars, m: Some(m) }
+    }
+
+    pub fn isEmpty(&self) -> bool {
+        self.m.is_none()
+    }
+
+    fn index(&self, x: &String) -> Option<usize> {
+        self.vars.iter().position(|y| y == x)
+    }
+
+    // v_j - v_i ≤ c
+    fn constrain(&mut self, i: usize, j: usize, c: i64) {
+        if let Some(m) = &mut self.m {
+            m[i][j] = minBound(m[i][j], Some(c));
+            m[j ^ 1][i ^ 1] = minBound(m[j ^ 1][i ^ 1], Some(c));
+        }
+    }
+
+    // sx x + sy y ≤ c, with y the same as x for a bound on x alone
+    fn constrainSum(&mut self, x: usize, sx: bool, y: usize, sy: bool, c: i64) {
+        let (p, q) = (value(x, sx), value(y, sy));
+        if p == q {
+            self.constrain(p ^ 1, p, 2 * c);
+        } else if p != q ^ 1 {
+            // v_p + v_q = v_p - v_q^1
+            self.constrain(q ^ 1, p, c);
+        } else if c < 0 {
+            self.m = None;
+        }
+    }
+
+    pub fn close(&mut self) {
+        let m = match &mut self.m {
+            Some(m) => m,
+            None => return,
+        };
+        let n = m.len();
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    let through = addBounds(m[i][k], m[k][j]);
+                    m[i][j] = minBound(m[i][j], through);
+                }
+            }
+        }
+        for i in 0..n {
+            if let Some(c) = m[i][i ^ 1] {
+                m[i][i ^ 1] = Some(2 * c.div_euclid(2));
+            }
+        }
+        for i in 0..n {
+            for j in 0..n {
+                let halves = addBounds(m[i][i ^ 1], m[j ^ 1][j]).map(|c| c.div_euclid(2));
+                m[i][j] = minBound(m[i][j], halves);
+            }
+        }
+        if (0..n).any(|i| m[i][i].map(|c| c < 0).unwrap_or(false)) {
+            self.m = None;
+        }
+    }
+
+    fn closed(&self) -> Octagon {
+        let mut closed = self.clone();
+        closed.close();
+        closed
+    }
+
+    // the bounds of x alone, from a closed octagon
+    pub fn interval(&self, x: &String) -> (Bound, Bound) {
+        match (&self.m, self.index(x)) {
+            (Some(m), Some(k)) => {
+                let (p, n) = (value(k, true), value(k, false));
+                (m[p][n].map(|c| -(c / 2)), m[n][p].map(|c| c / 2))
+            }
+            _ => (None, None),
+        }
+    }
+
+    // the upper bound of x - y, None if there is none
+    pub fn differenceBound(&self, x: &String, y: &String) -> Bound {
+        let closed = self.closed();
+        match (&closed.m, self.index(x), self.index(y)) {
+            (Some(m), Some(kx), Some(ky)) => m[value(ky, true)][value(kx, true)],
+            _ => None,
+        }
+    }
+
+    fn forget(&mut self, x: &String) {
+        self.close();
+        let k = self.index(x);
+        if let (Some(m), Some(k)) = (&mut self.m, k) {
+            for v in [value(k, true), value(k, false)] {
+                for i in 0..m.len() {
+                    if i != v {
+                        m[i][v] = None;
+                        m[v][i] = None;
+                    }
+                }
+            }
+        }
+    }
+
+    // x lies between lo and hi
+    fn bound(&mut self, x: &String, (lo, hi): (Bound, Bound)) {
+        if let Some(k) = self.index(x) {
+            if let Some(hi) = hi {
+                self.constrainSum(k, true, k, true, hi);
+            }
+            if let Some(lo) = lo {
+                self.constrainSum(k, false, k, false, -lo);
+            }
+        }
+    }
+
+    // x - y lies between lo and hi
+    fn difference(&mut self, x: &String, y: &String, (lo, hi): (Bound, Bound)) {
+        if let (Some(kx), Some(ky)) = (self.index(x), self.index(y)) {
+            if let Some(hi) = hi {
+                self.constrainSum(kx, true, ky, false, hi);
+            }
+            if let Some(lo) = lo {
+                self.constrainSum(ky, true, kx, false, -lo);
+            }
+        }
+    }
+
+    // adds c to x, moving every bound on it along
+    fn shift(&mut self, x: &String, c: i64) {
+        let k = self.index(x);
+        if let (Some(m), Some(k)) = (&mut self.m, k) {
+            let (p, n) = (value(k, true), value(k, false));
+            for i in 0..m.len() {
+                for j in 0..m.len() {
+                    let mut delta = 0;
+                    delta += if j == p { c } else if j == n { -c } else { 0 };
+                    delta += if i == p { -c } else if i == n { c } else { 0 };
+                    m[i][j] = m[i][j].and_then(|b| b.checked_add(delta));
+                }
+            }
+        }
+    }
+}
+
+impl Lattice for Octagon {
+    fn bottom() -> Self {
+        Octagon { vars: vec![], m: None }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let (o1, o2) = (self.closed(), other.closed());
+        match (&o1.m, &o2.m) {
+            (Some(m1), Some(m2)) => {
+                let m = m1
+                    .iter()
+                    .zip(m2.iter())
+                    .map(|(r1, r2)| r1.iter().zip(r2.iter()).map(|(b1, b2)| maxBound(*b1, *b2)).collect())
+                    .collect();
+                Octagon {
+                    vars: o1.vars.clone(),
+                    m: Some(m),
+                }
+            }
+            (Some(_), None) => o1,
+            (None, _) => o2,
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (&self.closed().m, &other.m) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(m1), Some(m2)) => m1
+                .iter()
+                .zip(m2.iter())
+                .all(|(r1, r2)| r1.iter().zip(r2.iter()).all(|(b1, b2)| leqBound(*b1, *b2))),
+        }
+    }
+
+    // self is not closed first, which would bring back bounds and keep the chain growing
+    fn widen(&self, next: &Self) -> Self {
+        let next = next.closed();
+        match (&self.m, &next.m) {
+            (Some(m1), Some(m2)) => {
+                let m = m1
+                    .iter()
+                    .zip(m2.iter())
+                    .map(|(r1, r2)| {
+                        r1.iter()
+                            .zip(r2.iter())
+                            .map(|(b1, b2)| if leqBound(*b2, *b1) { *b1 } else { None })
+                            .collect()
+                    })
+                    .collect();
+                Octagon {
+                    vars: self.vars.clone(),
+                    m: Some(m),
+                }
+            }
+            (None, _) => next,
+            (Some(_), None) => self.clone(),
+        }
+    }
+
+    fn narrow(&self, next: &Self) -> Self {
+        let next = next.closed();
+        match (&self.m, &next.m) {
+            (Some(m1), Some(m2)) => {
+                let m = m1
+                    .iter()
+                    .zip(m2.iter())
+                    .map(|(r1, r2)| {
+                        r1.iter()
+                            .zip(r2.iter())
+                            .map(|(b1, b2)| if b1.is_none() { *b2 } else { *b1 })
+                            .collect()
+                    })
+                    .collect();
+                Octagon {
+                    vars: self.vars.clone(),
+                    m: Some(m),
+                }
+            }
+            _ => next,
+        }
+    }
+}
+
+// the interval of exp, with None for no bound
+fn interval(exp: &Expression, oct: &Octagon) -> (Bound, Bound) {
+    match exp {
+        Expression::NatConstant { n } => (Some(*n as i64), Some(*n as i64)),
+        // variables in arithmetic are nats
+        Expression::StackVar { x } => {
+            let (lo, hi) = oct.interval(x);
+            (lo.map(|c| c.max(0)).or(Some(0)), hi)
+        }
+        Expression::Add { ex1, ex2 } => {
+            let ((lo1, hi1), (lo2, hi2)) = (interval(ex1, oct), interval(ex2, oct));
+            (addBounds(lo1, lo2), addBounds(hi1, hi2))
+        }
+        Expression::Sub { ex1, ex2 } => {
+            let ((lo1, hi1), (lo2, hi2)) = (interval(ex1, oct), interval(ex2, oct));
+            let lo = addBounds(lo1, hi2.map(|c| -c)).map(|c| c.max(0)).or(Some(0));
+            (lo, addBounds(hi1, lo2.map(|c| -c)).map(|c| c.max(0)))
+        }
+        Expression::Mul { ex1, ex2 } => match (interval(ex1, oct), interval(ex2, oct)) {
+            ((Some(lo1), hi1), (Some(lo2), hi2)) => (
+                lo1.checked_mul(lo2),
+                hi1.zip(hi2).and_then(|(h1, h2)| h1.checked_mul(h2)),
+            ),
+            _ => (Some(0), None),
+        },
+        _ => (None, None),
+    }
+}
+
+// exp as a variable plus a constant, or as a constant alone
+pub fn linear(exp: &Expression) -> Option<(Option<String>, i64)> {
+    match exp {
+        Expression::NatConstant { n } => Some((None, *n as i64)),
+        Expression::StackVar { x } => Some((Some(x.clone()), 0)),
+        Expression::Add { ex1, ex2 } => match (linear(ex1)?, linear(ex2)?) {
+            ((x, c1), (None, c2)) | ((None, c1), (x, c2)) => Some((x, c1 + c2)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn assign(x: &String, exp: &Expression, oct: &mut Octagon) {
+    oct.close();
+    if let Some((Some(y), c)) = linear(exp) {
+        if y == *x {
+            oct.shift(x, c);
+            return;
+        }
+    }
+    // x - c only stays linear where it does not stop at 0
+    if let Expression::Sub { ex1, ex2 } = exp {
+        if let (Expression::StackVar { x: y }, Expression::NatConstant { n }) = (&**ex1, &**ex2) {
+            if y == x && oct.interval(x).0.map(|lo| lo >= *n as i64).unwrap_or(false) {
+                oct.shift(x, -(*n as i64));
+                return;
+            }
+        }
+    }
+    let range = interval(exp, oct);
+    // the differences to the variables exp adds something to
+    let mut differences = Vec::new();
+    if let Expression::Add { ex1, ex2 } = exp {
+        for (e, rest) in [(ex1, ex2), (ex2, ex1)] {
+            if let Expression::StackVar { x: y } = &**e {
+                if y != x {
+                    differences.push((y.clone(), interval(rest, oct)));
+                }
+            }
+        }
+    }
+    if let Expression::StackVar { x: y } = exp {
+        differences.push((y.clone(), (Some(0), Some(0))));
+    }
+    oct.forget(x);
+    oct.bound(x, range);
+    for (y, range) in differences {
+        oct.difference(x, &y, range);
+    }
+    oct.close();
+}
+
+// the octagon of the runs on which condition evaluates to holds
+fn assume(condition: &Expression, holds: bool, oct: &Octagon) -> Octagon {
+    if oct.isEmpty() {
+        return oct.clone();
+    }
+    // e1 <= e2 + slack
+    let (ex1, ex2, slack) = match (condition, holds) {
+        (Expression::BoolConstant { b }, _) if *b != holds => return Octagon::bottom(),
+        (Expression::Negation { ex1 }, _) => return assume(ex1, !holds, oct),
+        (Expression::Conjunction { ex1, ex2 }, true) => return assume(ex2, true, &assume(ex1, true, oct)),
+        (Expression::Conjunction { ex1, ex2 }, false) => {
+            return assume(ex1, false, oct).join(&assume(ex2, false, oct))
+        }
+        (Expression::Disjunction { ex1, ex2 }, true) => {
+            return assume(ex1, true, oct).join(&assume(ex2, true, oct))
+        }
+        (Expression::Disjunction { ex1, ex2 }, false) => return assume(ex2, false, &assume(ex1, false, oct)),
+        (Expression::Equal { ex1, ex2 }, true) => {
+            let both = Expression::Conjunction {
+                ex1: Box::new(Expression::Comparision {
+                    ex1: ex1.clone(),
+                    ex2: ex2.clone(),
+                }),
+                ex2: Box::new(Expression::Comparision {
+                    ex1: ex2.clone(),
+                    ex2: ex1.clone(),
+                }),
+            };
+            return assume(&both, true, oct);
+        }
+        (Expression::NotEqual { ex1, ex2 }, false) => {
+            return assume(
+                &Expression::Equal {
+                    ex1: ex1.clone(),
+                    ex2: ex2.clone(),
+                },
+                true,
+                oct,
+            )
+        }
+        (Expression::Comparision { ex1, ex2 }, true) | (Expression::GreaterEqual { ex2: ex1, ex1: ex2 }, true) => {
+            (ex1, ex2, 0)
+        }
+        (Expression::LessThan { ex1, ex2 }, true) | (Expression::GreaterThan { ex2: ex1, ex1: ex2 }, true) => {
+            (ex1, ex2, -1)
+        }
+        // not e1 <= e2 is e2 < e1, and not e1 < e2 is e2 <= e1
+        (Expression::Comparision { ex1, ex2 }, false) | (Expression::GreaterEqual { ex2: ex1, ex1: ex2 }, false) => {
+            (ex2, ex1, -1)
+        }
+        (Expression::LessThan { ex1, ex2 }, false) | (Expression::GreaterThan { ex2: ex1, ex1: ex2 }, false) => {
+            (ex2, ex1, 0)
+        }
+        _ => return oct.clone(),
+    };
+    let mut oct = oct.clone();
+    match (linear(ex1), linear(ex2)) {
+        // x + c1 <= y + c2 + slack
+        (Some((Some(x), c1)), Some((Some(y), c2))) if x != y => {
+            oct.difference(&x, &y, (None, Some(c2 + slack - c1)));
+        }
+        (Some((Some(x), c1)), Some((Some(y), c2))) if x == y && c1 > c2 + slack => return Octagon::bottom(),
+        (Some((Some(x), c1)), Some((None, c2))) => oct.bound(&x, (None, Some(c2 + slack - c1))),
+        (Some((None, c1)), Some((Some(y), c2))) => oct.bound(&y, (Some(c1 - c2 - slack), None)),
+        (Some((None, c1)), Some((None, c2))) if c1 > c2 + slack => return Octagon::bottom(),
+        _ => {
+            // a variable on one side is bounded by the interval of the other side
+            let ((lo1, _), (_, hi2)) = (interval(ex1, &oct), interval(ex2, &oct));
+            if let Expression::StackVar { x } = &**ex1 {
+                oct.bound(x, (None, addBounds(hi2, Some(slack))));
+            }
+            if let Expression::StackVar { x } = &**ex2 {
+                oct.bound(x, (addBounds(lo1, Some(-slack)), None));
+            }
+        }
+    }
+    oct.close();
+    oct
+}
+
+struct Octagons {
+    vars: Vec<String>,
+}
+
+impl Analysis for Octagons {
+    type Fact = Octagon;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        Octagon::top(self.vars.clone())
+    }
+
+    fn transfer(&self, st: &Statement, _at: Point, fact: &Self::Fact) -> Self::Fact {
+        let mut oct = fact.clone();
+        if oct.isEmpty() {
+            return oct;
+        }
+        match st {
+            Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => {
+                assign(x, ex1, &mut oct)
+            }
+            Statement::Assert { condition } | Statement::Assume { condition } => {
+                return assume(condition, true, &oct)
+            }
+            _ => {
+                for x in definedVariables(st) {
+                    oct.forget(&x);
+                }
+            }
+        }
+        oct
+    }
+
+    fn edge(&self, condition: &Expression, holds: bool, fact: &Self::Fact) -> Self::Fact {
+        assume(condition, holds, fact)
+    }
+}
+
+// the stack variables of cfg, in order
+fn cfgVariables(cfg: &Cfg) -> Vec<String> {
+    let mut vars = BTreeSet::new();
+    for block in cfg.blocks.iter() {
+        for st in block.statements.iter() {
+            vars.extend(definedVariables(st));
+            vars.extend(usedVariables(st));
+        }
+        if let Terminator::Branch { condition, .. } = &block.terminator {
+            let mut read = Vec::new();
+            expressionVariables(condition, &mut read);
+            vars.extend(read);
+        }
+    }
+    vars.into_iter().collect()
+}
+
+// the octagon at every point of cfg, knowing nothing at the start
+pub fn octagons(cfg: &Cfg) -> Solution<Octagon> {
+    solve(&Octagons { vars: cfgVariables(cfg) }, cfg)
+}
+
+fn printBound(c: i64, x: &String, y: Option<(&String, bool)>) -> String {
+    match y {
+        None => format!("{} ≤ {}", x, c),
+        Some((y, true)) => format!("{} + {} ≤ {}", x, y, c),
+        Some((y, false)) if c == 0 => format!("{} ≤ {}", x, y),
+        Some((y, false)) if c > 0 => format!("{} ≤ {} + {}", x, y, c),
+        Some((y, false)) => format!("{} + {} ≤ {}", x, -c, y),
+    }
+}
+
+// the constraints of the octagon, the ones between two variables only if single bounds do not imply them
+pub fn printOctagon(oct: &Octagon) -> String {
+    let oct = oct.closed();
+    let m = match &oct.m {
+        Some(m) => m,
+        None => return String::from("unreachable"),
+    };
+    let bounds: Vec<(Bound, Bound)> = oct.vars.iter().map(|x| oct.interval(x)).collect();
+    let mut out = Vec::new();
+    for (k, x) in oct.vars.iter().enumerate() {
+        match bounds[k] {
+            (Some(lo), Some(hi)) if lo == hi => out.push(format!("{} = {}", x, lo)),
+            (lo, hi) => {
+                if let Some(lo) = lo {
+                    out.push(format!("{} ≥ {}", x, lo));
+                }
+                if let Some(hi) = hi {
+                    out.push(printBound(hi, x, None));
+                }
+            }
+        }
+    }
+    for k1 in 0..oct.vars.len() {
+        for k2 in k1 + 1..oct.vars.len() {
+            let (x, y) = (&oct.vars[k1], &oct.vars[k2]);
+            let ((lo1, hi1), (lo2, hi2)) = (bounds[k1], bounds[k2]);
+            // sx x + sy y ≤ c, and what the single bounds give for it
+            for (sx, sy) in [(true, false), (false, true), (true, true), (false, false)] {
+                let c = m[value(k2, !sy)][value(k1, sx)];
+                let implied = addBounds(
+                    if sx { hi1 } else { lo1.map(|c| -c) },
+                    if sy { hi2 } else { lo2.map(|c| -c) },
+                );
+                let c = match c {
+                    Some(c) if !leqBound(implied, Some(c)) => c,
+                    _ => continue,
+                };
+                out.push(match (sx, sy) {
+                    (true, false) => printBound(c, x, Some((y, false))),
+                    (false, true) => printBound(c, y, Some((x, false))),
+                    (true, true) => printBound(c, x, Some((y, true))),
+                    (false, false) => format!("{} + {} ≥ {}", x, y, -c),
+                });
+            }
+        }
+    }
+    out.join(", ")
+}
+
+// the relations at the header of every loop of st that checks a condition there
+pub fn loopInvariants(st: &Statement) -> Vec<String> {
+    let cfg = buildCfg(st);
+    let solution = octagons(&cfg);
+    let mut out = Vec::new();
+    for l in loopForest(&cfg) {
+        if let Terminator::Branch { condition, .. } = &cfg.blocks[l.header].terminator {
+            out.push(format!(
+                "At `while {}`: {}",
+                printExpression(condition.clone()).trim_end(),
+                printOctagon(solution.blockExit(l.header))
+            ));
+        }
+    }
+    out
+}
@@ -0,0 +1,532 @@
+/*
+Interleaving execution of s1 || s2 and detection of data races.
+
+Every branch of a parallel composition runs as a thread, and a scheduler
+repeatedly lets the Oracle pick which of the threads that can go on runs its
+next statement. One step of a thread is
+
+- an assignment, heap update, allocation, print, read, havoc, coin, assert,
+  assume or call, the latter including the whole procedure body
+- evaluating the condition of an if or while, or the bounds of a for
+- entering an atomic block
+
+while sequencing, nondeterministic and probabilistic choices, do-while, break, continue and forking the threads
+of a nested s1 || s2 only decide what the thread runs next. A thread waits
+for the threads it forked to finish before it goes on. While a thread is
+inside atomic { s }, only it and the threads it forks there may run.
+
+Every step records the places it reads and writes: store variables, heap
+cells, fields of records and elements of arrays. Two threads race when they
+access the same place, at least one of them writes it and the accesses are not
+both inside an atomic block. Each access is compared with those of every
+thread running in parallel since the last fork, so the race shows up as soon
+as its second access happens, on any interleaving, and the run stops with an
+error naming both statements. Which places a procedure body reaches is not
+known without running it, so a call counts as writing every heap cell
+reachable from the pointers passed to it.
+
+Picking a thread is one more decision of the Oracle, so executeAll enumerates
+the interleavings along with the other nondeterminism, and exploreInterleavings
+keeps one run for every different way the program can end.
+*/
+
+use crate::eval::evalExp;
+use crate::eval::executeAll;
+use crate::eval::executeIn;
+use crate::eval::Env;
+use crate::eval::HeapCell;
+use crate::eval::Outcome;
+use crate::eval::Run;
+use crate::eval::State;
+use crate::eval::Value;
+use crate::imp::printExpression;
+use crate::imp::printStatement;
+use crate::imp::Expression;
+use crate::imp::Statement;
+use crate::rational::printRational;
+
+// something a step reads or writes
+#[derive(Clone, PartialEq, Debug)]
+pub enum Place {
+    Variable(String),
+    // a whole heap cell, with all fields or elements
+    Cell(usize),
+    Field(usize, String),
+    Element(usize, usize),
+}
+
+pub fn printPlace(place: &Place) -> String {
+    match place {
+        Place::Variable(x) => format!("variable {}", x),
+        Place::Cell(l) => format!("loc {}", l),
+        Place::Field(l, f) => format!("field {} of loc {}", f, l),
+        Place::Element(l, i) => format!("element {} of loc {}", i, l),
+    }
+}
+
+fn cellOf(place: &Place) -> Option<usize> {
+    match place {
+        Place::Variable(_) => None,
+        Place::Cell(l) | Place::Field(l, _) | Place::Element(l, _) => Some(*l),
+    }
+}
+
+fn overlaps(p1: &Place, p2: &Place) -> bool {
+    match (p1, p2) {
+        (Place::Cell(l), p) | (p, Place::Cell(l)) => cellOf(p) == Some(*l),
+        _ => p1 == p2,
+    }
+}
+
+#[derive(Clone)]
+struct Access {
+    place: Place,
+    write: bool,
+    atomic: bool,
+    statement: String,
+}
+
+enum Task {
+    Run(Statement),
+    // the end of one iteration of a loop and what runs after it
+    Loop(Statement),
+    EndAtomic,
+}
+
+struct Thread {
+    // what is left to run, the next task last
+    tasks: Vec<Task>,
+    // everything this thread and its finished children accessed since it was forked
+    accesses: Vec<Access>,
+    parent: Option<usize>,
+    // forked threads that have not finished yet
+    waiting: usize,
+    // the number of atomic blocks the thread is inside
+    atomic: usize,
+    done: bool,
+}
+
+impl Thread {
+    fn new(st: Statement, parent: Option<usize>) -> Thread {
+        Thread {
+            tasks: vec![Task::Run(st)],
+            accesses: Vec::new(),
+            parent,
+            waiting: 0,
+            atomic: 0,
+            done: false,
+        }
+    }
+}
+
+fn isAncestor(threads: &Vec<Thread>, t: usize, u: usize) -> bool {
+    let mut current = Some(u);
+    while let Some(v) = current {
+        if v == t {
+            return true;
+        }
+        current = threads[v].parent;
+    }
+    false
+}
+
+fn inAtomic(threads: &Vec<Thread>, t: usize) -> bool {
+    threads.iter().enumerate().any(|(u, thread)| thread.atomic > 0 && isAncestor(threads, u, t))
+}
+
+// t may take a step unless it is finished, waits for its children or is locked out by an atomic block
+fn canRun(threads: &Vec<Thread>, t: usize) -> bool {
+    !threads[t].done
+        && threads[t].waiting == 0
+        && threads
+            .iter()
+            .enumerate()
+            .all(|(u, thread)| thread.done || thread.atomic == 0 || isAncestor(threads, u, t))
+}
+
+fn pointerOf(x: &String, state: &State) -> Option<usize> {
+    match state.store.get(x) {
+        Some(Value::Pointer(l)) => Some(*l),
+        _ => None,
+    }
+}
+
+fn readPlaces(exp: &Expression, state: &State, out: &mut Vec<(Place, bool)>) {
+    match exp {
+        Expression::StackVar { x } | Expression::ArrayLength { x } => {
+            out.push((Place::Variable(x.clone()), false))
+        }
+        Expression::HeapRead { x } => {
+            out.push((Place::Variable(x.clone()), false));
+            if let Some(l) = pointerOf(x, state) {
+                out.push((Place::Cell(l), false));
+            }
+        }
+        Expression::FieldRead { x, field } => {
+            out.push((Place::Variable(x.clone()), false));
+            if let Some(l) = pointerOf(x, state) {
+                out.push((Place::Field(l, field.clone()), false));
+            }
+        }
+        Expression::ArrayRead { x, index } => {
+            out.push((Place::Variable(x.clone()), false));
+            readPlaces(index, state, out);
+            if let (Some(l), Ok(Value::Nat(i))) = (pointerOf(x, state), evalExp(index, state)) {
+                out.push((Place::Element(l, i.max(0) as usize), false));
+            }
+        }
+        Expression::NatConstant { .. } | Expression::BoolConstant { .. } => (),
+        Expression::Negation { ex1 } => readPlaces(ex1, state, out),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => {
+            readPlaces(ex1, state, out);
+            readPlaces(ex2, state, out);
+        }
+    }
+}
+
+// every heap cell reachable from location l
+fn reachable(l: usize, state: &State, out: &mut Vec<usize>) {
+    if l >= state.heap.len() || out.contains(&l) {
+        return;
+    }
+    out.push(l);
+    if let HeapCell::Record(fields) = &state.heap[l] {
+        for (_, v) in fields {
+            if let Value::Pointer(next) = v {
+                reachable(*next, state, out);
+            }
+        }
+    }
+}
+
+// the places the basic statement st reads and writes when run in state
+fn stepPlaces(st: &Statement, state: &State) -> Vec<(Place, bool)> {
+    let mut out = Vec::new();
+    match st {
+        Statement::StackAssignment { x, ex1 }
+        | Statement::AnnotatedAssignment { x, ex1, .. }
+        | Statement::HeapNew { x, ex1 } => {
+            readPlaces(ex1, state, &mut out);
+            out.push((Place::Variable(x.clone()), true));
+        }
+        Statement::HeapUpdate { x, ex1 } => {
+            readPlaces(ex1, state, &mut out);
+            out.push((Place::Variable(x.clone()), false));
+            if let Some(l) = pointerOf(x, state) {
+                out.push((Place::Cell(l), true));
+            }
+        }
+        Statement::HeapAlias { x, y } => {
+            out.push((Place::Variable(y.clone()), false));
+            out.push((Place::Variable(x.clone()), true));
+        }
+        Statement::RecordNew { x, fields } => {
+            for (_, e) in fields {
+                readPlaces(e, state, &mut out);
+            }
+            out.push((Place::Variable(x.clone()), true));
+        }
+        Statement::FieldUpdate { x, field, ex1 } => {
+            readPlaces(ex1, state, &mut out);
+            out.push((Place::Variable(x.clone()), false));
+            if let Some(l) = pointerOf(x, state) {
+                out.push((Place::Field(l, field.clone()), true));
+            }
+        }
+        Statement::ArrayNew { x, size, ex1 } => {
+            readPlaces(size, state, &mut out);
+            readPlaces(ex1, state, &mut out);
+            out.push((Place::Variable(x.clone()), true));
+        }
+        Statement::ArrayUpdate { x, index, ex1 } => {
+            readPlaces(index, state, &mut out);
+            readPlaces(ex1, state, &mut out);
+            out.push((Place::Variable(x.clone()), false));
+            if let (Some(l), Ok(Value::Nat(i))) = (pointerOf(x, state), evalExp(index, state)) {
+                out.push((Place::Element(l, i.max(0) as usize), true));
+            }
+        }
+        Statement::Call { x, args, .. } => {
+            let mut cells = Vec::new();
+            for e in args {
+                readPlaces(e, state, &mut out);
+                if let Ok(Value::Pointer(l)) = evalExp(e, state) {
+                    reachable(l, state, &mut cells);
+                }
+            }
+            out.extend(cells.into_iter().map(|l| (Place::Cell(l), true)));
+            if let Some(x) = x {
+                out.push((Place::Variable(x.clone()), true));
+            }
+        }
+        Statement::Assert { condition } | Statement::Assume { condition } => {
+            readPlaces(condition, state, &mut out)
+        }
+        Statement::Print { ex1 } => readPlaces(ex1, state, &mut out),
+        Statement::Read { x, .. } | Statement::Havoc { x } | Statement::Coin { x, .. } => {
+            out.push((Place::Variable(x.clone()), true))
+        }
+        _ => (),
+    }
+    out
+}
+
+// records the accesses of a step of thread t, failing on the first one that races
+fn record(
+    threads: &mut Vec<Thread>,
+    t: usize,
+    places: Vec<(Place, bool)>,
+    statement: String,
+) -> Result<(), String> {
+    let atomic = inAtomic(threads, t);
+    for (place, write) in places {
+        for u in 0..threads.len() {
+            if isAncestor(threads, u, t) || isAncestor(threads, t, u) {
+                continue;
+            }
+            for other in threads[u].accesses.iter() {
+                if overlaps(&place, &other.place) && (write || other.write) && !(atomic && other.atomic) {
+                    return Err(format!(
+                        "data race on {} between `{}` and `{}`",
+                        printPlace(&place),
+                        other.statement,
+                        statement
+                    ));
+                }
+            }
+        }
+        threads[t].accesses.push(Access {
+            place,
+            write,
+            atomic,
+            statement: statement.clone(),
+        });
+    }
+    Ok(())
+}
+
+// a thread without tasks is finished, and the last child to finish wakes up its parent
+fn finish(threads: &mut Vec<Thread>, t: usize, env: &mut Env) -> Result<(), String> {
+    threads[t].done = true;
+    if let Some(p) = threads[t].parent {
+        threads[p].waiting -= 1;
+        if threads[p].waiting == 0 {
+            for c in 0..threads.len() {
+                if threads[c].parent == Some(p) {
+                    let accesses = std::mem::take(&mut threads[c].accesses);
+                    threads[p].accesses.extend(accesses);
+                }
+            }
+            settle(threads, p, env)?;
+        }
+    }
+    Ok(())
+}
+
+// leaves the loop the break or continue in thread t belongs to, returning what runs after it
+fn leaveLoop(threads: &mut Vec<Thread>, t: usize) -> Result<Statement, String> {
+    loop {
+        match threads[t].tasks.pop() {
+            Some(Task::Loop(next)) => return Ok(next),
+            Some(Task::EndAtomic) => threads[t].atomic -= 1,
+            Some(Task::Run(_)) => (),
+            None => return Err(String::from("break or continue outside a loop")),
+        }
+    }
+}
+
+// runs what thread t does before its next step, which forks threads and may finish it
+fn settle(threads: &mut Vec<Thread>, t: usize, env: &mut Env) -> Result<(), String> {
+    loop {
+        let st = match threads[t].tasks.pop() {
+            None => return finish(threads, t, env),
+            Some(Task::EndAtomic) => {
+                threads[t].atomic -= 1;
+                continue;
+            }
+            Some(Task::Loop(next)) => next,
+            Some(Task::Run(st)) => st,
+        };
+        match st {
+            Statement::Sequence { st1, st2 } => {
+                threads[t].tasks.push(Task::Run(*st2));
+                threads[t].tasks.push(Task::Run(*st1));
+            }
+            Statement::Skip | Statement::Procedure { .. } => (),
+            Statement::DoWhile { st: body, condition } => {
+                threads[t].tasks.push(Task::Loop(Statement::While {
+                    condition,
+                    st: body.clone(),
+                }));
+                threads[t].tasks.push(Task::Run(*body));
+            }
+            Statement::Break => {
+                leaveLoop(threads, t)?;
+            }
+            Statement::Continue => {
+                let next = leaveLoop(threads, t)?;
+                threads[t].tasks.push(Task::Run(next));
+            }
+            Statement::Choice { st1, st2 } => {
+                let branch = if env.oracle.choose(2) == 0 { st1 } else { st2 };
+                threads[t].tasks.push(Task::Run(*branch));
+            }
+            Statement::ProbChoice { st1, st2, p } => {
+                if !p.isProbability() {
+                    return Err(format!("probability {} is not between 0 and 1", printRational(p)));
+                }
+                let branch = if env.oracle.flip(&p) { st1 } else { st2 };
+                threads[t].tasks.push(Task::Run(*branch));
+            }
+            Statement::Parallel { st1, st2 } => {
+                threads[t].waiting = 2;
+                for branch in [st1, st2] {
+                    threads.push(Thread::new(*branch, Some(t)));
+                    let child = threads.len() - 1;
+                    settle(threads, child, env)?;
+                }
+                return Ok(());
+            }
+            st => {
+                threads[t].tasks.push(Task::Run(st));
+                return Ok(());
+            }
+        }
+    }
+}
+
+// runs the next step of thread t
+fn step(threads: &mut Vec<Thread>, t: usize, state: &mut State, env: &mut Env) -> Result<Outcome, String> {
+    let st = match threads[t].tasks.pop() {
+        Some(Task::Run(st)) => st,
+        _ => return Err(String::from("thread has no step to take")),
+    };
+    match st {
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            let mut places = Vec::new();
+            readPlaces(&condition, state, &mut places);
+            record(threads, t, places, String::from("if (") + &printExpression(condition.clone()) + ")")?;
+            let branch = match evalExp(&condition, state)? {
+                Value::Bool(true) => then_branch,
+                Value::Bool(false) => else_branch,
+                _ => return Err(format!("expression: {} is not a bool", printExpression(condition))),
+            };
+            threads[t].tasks.push(Task::Run(*branch));
+        }
+        Statement::While { condition, st: body } => {
+            let mut places = Vec::new();
+            readPlaces(&condition, state, &mut places);
+            record(threads, t, places, String::from("while (") + &printExpression(condition.clone()) + ")")?;
+            match evalExp(&condition, state)? {
+                Value::Bool(true) => {
+                    threads[t].tasks.push(Task::Loop(Statement::While {
+                        condition,
+                        st: body.clone(),
+                    }));
+                    threads[t].tasks.push(Task::Run(*body));
+                }
+                Value::Bool(false) => (),
+                _ => return Err(format!("expression: {} is not a bool", printExpression(condition))),
+            }
+        }
+        // the bounds are evaluated once, so later iterations continue with constants
+        Statement::For { x, from, to, st: body } => {
+            let mut places = Vec::new();
+            readPlaces(&from, state, &mut places);
+            readPlaces(&to, state, &mut places);
+            places.push((Place::Variable(x.clone()), true));
+            let text = String::from("for ")
+                + &x
+                + " = "
+                + &printExpression(from.clone())
+                + " to "
+                + &printExpression(to.clone());
+            record(threads, t, places, text)?;
+            let (n1, n2) = match (evalExp(&from, state)?, evalExp(&to, state)?) {
+                (Value::Nat(n1), Value::Nat(n2)) => (n1, n2),
+                _ => return Err(format!("the bounds of the loop over {} are not nats", x)),
+            };
+            if n1 <= n2 {
+                state.store.insert(x.clone(), Value::Nat(n1));
+                let next = if n1 < n2 {
+                    Statement::For {
+                        x,
+                        from: Expression::NatConstant { n: n1 + 1 },
+                        to: Expression::NatConstant { n: n2 },
+                        st: body.clone(),
+                    }
+                } else {
+                    Statement::Skip
+                };
+                threads[t].tasks.push(Task::Loop(next));
+                threads[t].tasks.push(Task::Run(*body));
+            }
+        }
+        Statement::Atomic { st: body } => {
+            threads[t].atomic += 1;
+            threads[t].tasks.push(Task::EndAtomic);
+            threads[t].tasks.push(Task::Run(*body));
+        }
+        st => {
+            record(threads, t, stepPlaces(&st, state), printStatement(st.clone()))?;
+            if executeIn(&st, state, env)? == Outcome::Blocked {
+                return Ok(Outcome::Blocked);
+            }
+        }
+    }
+    settle(threads, t, env)?;
+    Ok(Outcome::Normal)
+}
+
+// runs the parallel composition st to the end of every thread
+pub fn executeParallel(st: &Statement, state: &mut State, env: &mut Env) -> Result<Outcome, String> {
+    let mut threads = vec![Thread::new(st.clone(), None)];
+    settle(&mut threads, 0, env)?;
+    loop {
+        let ready: Vec<usize> = (0..threads.len()).filter(|t| canRun(&threads, *t)).collect();
+        let t = match ready.len() {
+            0 => return Ok(Outcome::Normal),
+            1 => ready[0],
+            n => ready[env.oracle.choose(n)],
+        };
+        if step(&mut threads, t, state, env)? == Outcome::Blocked {
+            return Ok(Outcome::Blocked);
+        }
+    }
+}
+
+// every different way st can end when run from state, each with one run leading there
+pub fn exploreInterleavings(
+    st: &Statement,
+    state: &State,
+    havoc_max: i32,
+    max_runs: usize,
+) -> Result<Vec<Run>, String> {
+    let mut distinct: Vec<Run> = Vec::new();
+    for run in executeAll(st, state, havoc_max, max_runs)? {
+        if !distinct
+            .iter()
+            .any(|other| other.result == run.result && other.state == run.state)
+        {
+            distinct.push(run);
+        }
+    }
+    Ok(distinct)
+}
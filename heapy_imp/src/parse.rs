@@ -0,0 +1,761 @@
+/*
+A parser for the text of Heapy IMP programs.
+
+It reads the syntax printStatement prints, so that a printed program parses back
+to the same program, and also accepts := for assignments, a ; after the last
+statement of a sequence and // comments to the end of a line:
+
+s ::= s; s | skip | break | continue
+    | x = e | x : τ = e | var x : τ | !x = e | x.f = e | x[e] = e
+    | x = new(e) | x = new { f: e, ... } | x = newarray(e, e) | x = coin(p)
+    | f(e, ...) | x = f(e, ...)
+    | proc f(x: τ, ...) { s } | proc f(x: τ, ...): τ { s; return e }
+    | if e then { s } else { s } | while e do { s } | do { s } while e
+    | for x = e to e do { s }
+    | { s } [] { s } | { s } || { s } | { s } [p] { s } | atomic { s }
+    | assert e | assume e | print e | read x | read x : τ | havoc x
+e ::= e implies e | e or e | e and e | not e
+    | e <= e | e < e | e >= e | e > e | e == e | e != e
+    | e + e | e - e | e * e | e / e | e % e
+    | x | !x | x.f | x[e] | len(x) | N | true | false | (e)
+τ ::= nat | bool | ptr nat | ptr nat[] | ptr { f: τ, ... }
+p ::= N | N/N
+
+Operators bind from implies, the loosest, through or, and, the comparisons and
++ and - to * / %, the tightest, and all but implies group to the left.
+
+The text of x = y is the same whether x and y are nats or pointers, so it is
+read as the alias of a pointer when y is a pointer somewhere in the program:
+allocated, declared or taken as a parameter with a pointer type, or aliased
+from another pointer.
+*/
+
+use std::collections::BTreeSet;
+
+use crate::imp::ExType;
+use crate::imp::Expression;
+use crate::imp::Statement;
+use crate::rational::Rational;
+
+struct Token {
+    text: String,
+    line: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token {
+                text: chars[start..i].iter().collect(),
+                line,
+            });
+        } else {
+            let pair: String = [c, next.unwrap_or(' ')].iter().collect();
+            let text = match pair.as_str() {
+                "<=" | ">=" | "==" | "!=" | ":=" | "||" => pair,
+                _ if "(){}[],;:.=!<>+-*/%".contains(c) => c.to_string(),
+                _ => return Err(format!("Unexpected character {} on line {}", c, line)),
+            };
+            i += text.len();
+            tokens.push(Token { text, line });
+        }
+    }
+    Ok(tokens)
+}
+
+const KEYWORDS: [&str; 27] = [
+    "skip", "break", "continue", "if", "then", "else", "while", "do", "for", "to", "var", "proc",
+    "return", "atomic", "assert", "assume", "print", "read", "havoc", "new", "newarray", "coin",
+    "not", "and", "or", "implies", "len",
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &str {
+        self.peekAt(0)
+    }
+
+    fn peekAt(&self, k: usize) -> &str {
+        self.tokens.get(self.at + k).map(|t| t.text.as_str()).unwrap_or("")
+    }
+
+    fn error(&self, expected: &str) -> String {
+        match self.tokens.get(self.at) {
+            Some(t) => format!("Expected {} but found {} on line {}", expected, t.text, t.line),
+            None => format!("Expected {} but the program ends", expected),
+        }
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.peek() == text {
+            self.at += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), String> {
+        if self.eat(text) {
+            Ok(())
+        } else {
+            Err(self.error(text))
+        }
+    }
+
+    fn isName(text: &str) -> bool {
+        text.chars().next().map(|c| c.is_alphabetic() || c == '_').unwrap_or(false) && !KEYWORDS.contains(&text)
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        if Parser::isName(self.peek()) {
+            self.at += 1;
+            Ok(self.tokens[self.at - 1].text.clone())
+        } else {
+            Err(self.error("a name"))
+        }
+    }
+
+    fn number(&mut self) -> Result<i128, String> {
+        match self.peek().parse::<i128>() {
+            Ok(n) => {
+                self.at += 1;
+                Ok(n)
+            }
+            Err(_) => Err(self.error("a number")),
+        }
+    }
+
+    fn assignment(&mut self) -> Result<(), String> {
+        if self.eat("=") || self.eat(":=") {
+            Ok(())
+        } else {
+            Err(self.error("="))
+        }
+    }
+
+    // statements separated by ; up to a }, a return or the end of the program
+    fn sequence(&mut self) -> Result<Statement, String> {
+        if matches!(self.peek(), "}" | "return" | "") {
+            return Ok(Statement::Skip);
+        }
+        let st1 = self.statement()?;
+        if !self.eat(";") || matches!(self.peek(), "}" | "return" | "") {
+            return Ok(st1);
+        }
+        Ok(Statement::Sequence {
+            st1: Box::new(st1),
+            st2: Box::new(self.sequence()?),
+        })
+    }
+
+    fn block(&mut self) -> Result<Statement, String> {
+        self.expect("{")?;
+        let st = self.sequence()?;
+        self.expect("}")?;
+        Ok(st)
+    }
+
+    fn probability(&mut self) -> Result<Rational, String> {
+        let num = self.number()?;
+        let den = if self.eat("/") { self.number()? } else { 1 };
+        Ok(Rational::new(num, den))
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expression>, String> {
+        self.expect("(")?;
+        let mut args = Vec::new();
+        if !self.eat(")") {
+            loop {
+                args.push(self.expression()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        Ok(args)
+    }
+
+    fn typeAnnotation(&mut self) -> Result<ExType, String> {
+        if self.eat("nat") {
+            return Ok(ExType::NatType);
+        }
+        if self.eat("bool") {
+            return Ok(ExType::BoolType);
+        }
+        self.expect("ptr")?;
+        if self.eat("nat") {
+            if self.eat("[") {
+                self.expect("]")?;
+                return Ok(ExType::ArrayType);
+            }
+            return Ok(ExType::PointerType);
+        }
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        loop {
+            let f = self.name()?;
+            self.expect(":")?;
+            fields.push((f, self.typeAnnotation()?));
+            if self.eat("}") {
+                return Ok(ExType::RecordType { fields });
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, String> {
+        match self.peek() {
+            "skip" | "break" | "continue" => {
+                let st = match self.peek() {
+                    "skip" => Statement::Skip,
+                    "break" => Statement::Break,
+                    _ => Statement::Continue,
+                };
+                self.at += 1;
+                Ok(st)
+            }
+            "if" => {
+                self.at += 1;
+                let condition = self.expression()?;
+                self.expect("then")?;
+                let then_branch = self.block()?;
+                self.expect("else")?;
+                Ok(Statement::IfThenElse {
+                    condition,
+                    then_branch: Box::new(then_branch),
+                    else_branch: Box::new(self.block()?),
+                })
+            }
+            "while" => {
+                self.at += 1;
+                let condition = self.expression()?;
+                self.expect("do")?;
+                Ok(Statement::While {
+                    condition,
+                    st: Box::new(self.block()?),
+                })
+            }
+            "do" => {
+                self.at += 1;
+                let st = self.block()?;
+                self.expect("while")?;
+                Ok(Statement::DoWhile {
+                    st: Box::new(st),
+                    condition: self.expression()?,
+                })
+            }
+            "for" => {
+                self.at += 1;
+                let x = self.name()?;
+                self.assignment()?;
+                let from = self.expression()?;
+                self.expect("to")?;
+                let to = self.expression()?;
+                self.expect("do")?;
+                Ok(Statement::For {
+                    x,
+                    from,
+                    to,
+                    st: Box::new(self.block()?),
+                })
+            }
+            "var" => {
+                self.at += 1;
+                let x = self.name()?;
+                self.expect(":")?;
+                Ok(Statement::Declaration {
+                    x,
+                    tp: self.typeAnnotation()?,
+                })
+            }
+            "read" => {
+                self.at += 1;
+                let x = self.name()?;
+                let tp = if self.eat(":") { Some(self.typeAnnotation()?) } else { None };
+                Ok(Statement::Read { x, tp })
+            }
+            "havoc" => {
+                self.at += 1;
+                Ok(Statement::Havoc { x: self.name()? })
+            }
+            "assert" | "assume" | "print" => {
+                let keyword = self.peek().to_string();
+                self.at += 1;
+                let e = self.expression()?;
+                Ok(match keyword.as_str() {
+                    "assert" => Statement::Assert { condition: e },
+                    "assume" => Statement::Assume { condition: e },
+                    _ => Statement::Print { ex1: e },
+                })
+            }
+            "atomic" => {
+                self.at += 1;
+                Ok(Statement::Atomic {
+                    st: Box::new(self.block()?),
+                })
+            }
+            "proc" => {
+                self.at += 1;
+                let name = self.name()?;
+                self.expect("(")?;
+                let mut params = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        let p = self.name()?;
+                        self.expect(":")?;
+                        params.push((p, self.typeAnnotation()?));
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                let tp = if self.eat(":") { Some(self.typeAnnotation()?) } else { None };
+                self.expect("{")?;
+                let body = self.sequence()?;
+                let ret = match tp {
+                    Some(tp) => {
+                        self.expect("return")?;
+                        Some((tp, self.expression()?))
+                    }
+                    None => None,
+                };
+                self.expect("}")?;
+                Ok(Statement::Procedure {
+                    name,
+                    params,
+                    body: Box::new(body),
+                    ret,
+                })
+            }
+            "{" => {
+                let st1 = Box::new(self.block()?);
+                if self.eat("||") {
+                    Ok(Statement::Parallel {
+                        st1,
+                        st2: Box::new(self.block()?),
+                    })
+                } else if self.eat("[") {
+                    if self.eat("]") {
+                        return Ok(Statement::Choice {
+                            st1,
+                            st2: Box::new(self.block()?),
+                        });
+                    }
+                    let p = self.probability()?;
+                    self.expect("]")?;
+                    Ok(Statement::ProbChoice {
+                        st1,
+                        st2: Box::new(self.block()?),
+                        p,
+                    })
+                } else {
+                    Ok(*st1)
+                }
+            }
+            "!" => {
+                self.at += 1;
+                let x = self.name()?;
+                self.assignment()?;
+                Ok(Statement::HeapUpdate {
+                    x,
+                    ex1: self.expression()?,
+                })
+            }
+            _ => {
+                let x = self.name()?;
+                match self.peek() {
+                    "(" => Ok(Statement::Call {
+                        x: None,
+                        name: x,
+                        args: self.arguments()?,
+                    }),
+                    ":" => {
+                        self.at += 1;
+                        let tp = self.typeAnnotation()?;
+                        self.assignment()?;
+                        Ok(Statement::AnnotatedAssignment {
+                            x,
+                            tp,
+                            ex1: self.expression()?,
+                        })
+                    }
+                    "." => {
+                        self.at += 1;
+                        let field = self.name()?;
+                        self.assignment()?;
+                        Ok(Statement::FieldUpdate {
+                            x,
+                            field,
+                            ex1: self.expression()?,
+                        })
+                    }
+                    "[" => {
+                        self.at += 1;
+                        let index = self.expression()?;
+                        self.expect("]")?;
+                        self.assignment()?;
+                        Ok(Statement::ArrayUpdate {
+                            x,
+                            index,
+                            ex1: self.expression()?,
+                        })
+                    }
+                    _ => {
+                        self.assignment()?;
+                        self.assigned(x)
+                    }
+                }
+            }
+        }
+    }
+
+    // what follows x =
+    fn assigned(&mut self, x: String) -> Result<Statement, String> {
+        match (self.peek(), self.peekAt(1)) {
+            ("new", "(") => {
+                self.at += 2;
+                let ex1 = self.expression()?;
+                self.expect(")")?;
+                Ok(Statement::HeapNew { x, ex1 })
+            }
+            ("new", "{") => {
+                self.at += 2;
+                let mut fields = Vec::new();
+                loop {
+                    let f = self.name()?;
+                    self.expect(":")?;
+                    fields.push((f, self.expression()?));
+                    if self.eat("}") {
+                        return Ok(Statement::RecordNew { x, fields });
+                    }
+                    self.expect(",")?;
+                }
+            }
+            ("newarray", "(") => {
+                self.at += 2;
+                let size = self.expression()?;
+                self.expect(",")?;
+                let ex1 = self.expression()?;
+                self.expect(")")?;
+                Ok(Statement::ArrayNew { x, size, ex1 })
+            }
+            ("coin", "(") => {
+                self.at += 2;
+                let p = self.probability()?;
+                self.expect(")")?;
+                Ok(Statement::Coin { x, p })
+            }
+            (name, "(") if Parser::isName(name) => {
+                let name = self.name()?;
+                Ok(Statement::Call {
+                    x: Some(x),
+                    name,
+                    args: self.arguments()?,
+                })
+            }
+            _ => Ok(Statement::StackAssignment {
+                x,
+                ex1: self.expression()?,
+            }),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        let ex1 = self.disjunction()?;
+        if self.eat("implies") {
+            return Ok(Expression::Implication {
+                ex1: Box::new(ex1),
+                ex2: Box::new(self.expression()?),
+            });
+        }
+        Ok(ex1)
+    }
+
+    fn disjunction(&mut self) -> Result<Expression, String> {
+        let mut ex1 = self.conjunction()?;
+        while self.eat("or") {
+            ex1 = Expression::Disjunction {
+                ex1: Box::new(ex1),
+                ex2: Box::new(self.conjunction()?),
+            };
+        }
+        Ok(ex1)
+    }
+
+    fn conjunction(&mut self) -> Result<Expression, String> {
+        let mut ex1 = self.comparison()?;
+        while self.eat("and") {
+            ex1 = Expression::Conjunction {
+                ex1: Box::new(ex1),
+                ex2: Box::new(self.comparison()?),
+            };
+        }
+        Ok(ex1)
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        let mut ex1 = self.sum()?;
+        loop {
+            let op = self.peek().to_string();
+            if !matches!(op.as_str(), "<=" | "<" | ">=" | ">" | "==" | "!=") {
+                return Ok(ex1);
+            }
+            self.at += 1;
+            let (ex1_, ex2) = (Box::new(ex1), Box::new(self.sum()?));
+            ex1 = match op.as_str() {
+                "<=" => Expression::Comparision { ex1: ex1_, ex2 },
+                "<" => Expression::LessThan { ex1: ex1_, ex2 },
+                ">=" => Expression::GreaterEqual { ex1: ex1_, ex2 },
+                ">" => Expression::GreaterThan { ex1: ex1_, ex2 },
+                "==" => Expression::Equal { ex1: ex1_, ex2 },
+                _ => Expression::NotEqual { ex1: ex1_, ex2 },
+            };
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut ex1 = self.product()?;
+        loop {
+            let add = match self.peek() {
+                "+" => true,
+                "-" => false,
+                _ => return Ok(ex1),
+            };
+            self.at += 1;
+            let (ex1_, ex2) = (Box::new(ex1), Box::new(self.product()?));
+            ex1 = if add {
+                Expression::Add { ex1: ex1_, ex2 }
+            } else {
+                Expression::Sub { ex1: ex1_, ex2 }
+            };
+        }
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        let mut ex1 = self.unary()?;
+        loop {
+            let op = self.peek().to_string();
+            if !matches!(op.as_str(), "*" | "/" | "%") {
+                return Ok(ex1);
+            }
+            self.at += 1;
+            let (ex1_, ex2) = (Box::new(ex1), Box::new(self.unary()?));
+            ex1 = match op.as_str() {
+                "*" => Expression::Mul { ex1: ex1_, ex2 },
+                "/" => Expression::Div { ex1: ex1_, ex2 },
+                _ => Expression::Mod { ex1: ex1_, ex2 },
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat("not") {
+            return Ok(Expression::Negation {
+                ex1: Box::new(self.unary()?),
+            });
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            "(" => {
+                self.at += 1;
+                let e = self.expression()?;
+                self.expect(")")?;
+                Ok(e)
+            }
+            "true" | "false" => {
+                let b = self.peek() == "true";
+                self.at += 1;
+                Ok(Expression::BoolConstant { b })
+            }
+            "!" => {
+                self.at += 1;
+                Ok(Expression::HeapRead { x: self.name()? })
+            }
+            "len" => {
+                self.at += 1;
+                self.expect("(")?;
+                let x = self.name()?;
+                self.expect(")")?;
+                Ok(Expression::ArrayLength { x })
+            }
+            text if text.starts_with(|c: char| c.is_ascii_digit()) => match text.parse::<i32>() {
+                Ok(n) => {
+                    self.at += 1;
+                    Ok(Expression::NatConstant { n })
+                }
+                Err(_) => Err(self.error("a nat that fits in 32 bits")),
+            },
+            text if !Parser::isName(text) => Err(self.error("an expression")),
+            _ => {
+                let x = self.name()?;
+                if self.eat(".") {
+                    return Ok(Expression::FieldRead { x, field: self.name()? });
+                }
+                if self.eat("[") {
+                    let index = self.expression()?;
+                    self.expect("]")?;
+                    return Ok(Expression::ArrayRead {
+                        x,
+                        index: Box::new(index),
+                    });
+                }
+                Ok(Expression::StackVar { x })
+            }
+        }
+    }
+}
+
+fn isPointerType(tp: &ExType) -> bool {
+    !matches!(tp, ExType::NatType | ExType::BoolType)
+}
+
+// adds the variables st surely makes pointers, and those it copies from one; true if any was new
+fn collectPointers(st: &Statement, pointers: &mut BTreeSet<String>) -> bool {
+    match st {
+        Statement::HeapNew { x, .. } | Statement::RecordNew { x, .. } | Statement::ArrayNew { x, .. } => {
+            pointers.insert(x.clone())
+        }
+        Statement::HeapAlias { x, y }
+        | Statement::StackAssignment {
+            x,
+            ex1: Expression::StackVar { x: y },
+        } if pointers.contains(y) => pointers.insert(x.clone()),
+        Statement::Declaration { x, tp } | Statement::AnnotatedAssignment { x, tp, .. } if isPointerType(tp) => {
+            pointers.insert(x.clone())
+        }
+        Statement::Procedure { params, body, .. } => {
+            let mut changed = false;
+            for (p, tp) in params {
+                if isPointerType(tp) {
+                    changed |= pointers.insert(p.clone());
+                }
+            }
+            collectPointers(body, pointers) || changed
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::Parallel { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::IfThenElse {
+            then_branch: st1,
+            else_branch: st2,
+            ..
+        } => {
+            let changed = collectPointers(st1, pointers);
+            collectPointers(st2, pointers) || changed
+        }
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => collectPointers(st, pointers),
+        _ => false,
+    }
+}
+
+// st with every x = y from a pointer y made an alias
+fn aliasPointers(st: Statement, pointers: &BTreeSet<String>) -> Statement {
+    let inner = |st: Box<Statement>| Box::new(aliasPointers(*st, pointers));
+    match st {
+        Statement::StackAssignment {
+            x,
+            ex1: Expression::StackVar { x: y },
+        } if pointers.contains(&y) => Statement::HeapAlias { x, y },
+        Statement::Sequence { st1, st2 } => Statement::Sequence {
+            st1: inner(st1),
+            st2: inner(st2),
+        },
+        Statement::Choice { st1, st2 } => Statement::Choice {
+            st1: inner(st1),
+            st2: inner(st2),
+        },
+        Statement::Parallel { st1, st2 } => Statement::Parallel {
+            st1: inner(st1),
+            st2: inner(st2),
+        },
+        Statement::ProbChoice { st1, st2, p } => Statement::ProbChoice {
+            st1: inner(st1),
+            st2: inner(st2),
+            p,
+        },
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => Statement::IfThenElse {
+            condition,
+            then_branch: inner(then_branch),
+            else_branch: inner(else_branch),
+        },
+        Statement::While { condition, st } => Statement::While {
+            condition,
+            st: inner(st),
+        },
+        Statement::DoWhile { st, condition } => Statement::DoWhile {
+            st: inner(st),
+            condition,
+        },
+        Statement::For { x, from, to, st } => Statement::For {
+            x,
+            from,
+            to,
+            st: inner(st),
+        },
+        Statement::Atomic { st } => Statement::Atomic { st: inner(st) },
+        Statement::Procedure {
+            name,
+            params,
+            body,
+            ret,
+        } => Statement::Procedure {
+            name,
+            params,
+            body: inner(body),
+            ret,
+        },
+        st => st,
+    }
+}
+
+// the program text describes, or where it cannot be read
+pub fn parseProgram(text: &str) -> Result<Statement, String> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        at: 0,
+    };
+    let st = parser.sequence()?;
+    if parser.at < parser.tokens.len() {
+        return Err(parser.error("; or the end of the program"));
+    }
+    let mut pointers = BTreeSet::new();
+    while collectPointers(&st, &mut pointers) {}
+    Ok(aliasPointers(st, &pointers))
+}
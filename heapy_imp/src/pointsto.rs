@@ -0,0 +1,301 @@
+/*
+Points-to and alias analysis of pointer variables.
+
+Every statement allocating a cell, x := new(e), x := new { ... } and
+x := newarray(e, e), is an abstract location named by its position in the
+control-flow graph, standing for all the cells it allocates. A forward dataflow
+analysis finds for each pointer variable the locations it may point to, and the
+pairs of variables that surely hold the same pointer:
+
+x := new(e)              x points to the site, and equals no other variable
+x = y, x := y            x points to what y points to, and equals y and what y equals
+x := f(...)              x points to an unknown location, which may be any cell
+x := e.f                 likewise, as a field may hold any pointer
+x := e                   x holds no pointer
+s1 || s2                 what either branch may assign a pointer to may also point to an
+                         unknown location, and equals no other variable
+
+Paths are joined by the union of the locations and the intersection of the
+equalities. Two variables may alias when they may point to a common location,
+or one of them to an unknown one, and must alias when they surely hold the
+same pointer. As a location stands for every cell its site allocates, pointing
+to the same single location is not enough for that.
+
+A variable that is neither allocated nor copied from a pointer points nowhere
+and aliases nothing.
+*/
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::cfg::buildCfg;
+use crate::cfg::Cfg;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Lattice;
+use crate::dataflow::Point;
+use crate::dataflow::Solution;
+use crate::imp::definedVariables;
+use crate::imp::printStatement;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Location {
+    Site(Point),
+    Unknown,
+}
+
+pub fn printLocation(l: &Location) -> String {
+    match l {
+        Location::Site(at) => format!("b{}.{}", at.block, at.index),
+        Location::Unknown => String::from("unknown"),
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Aliases {
+    pub targets: BTreeMap<String, BTreeSet<Location>>,
+    // pairs of variables holding the same pointer, the smaller name first
+    pub equal: BTreeSet<(String, String)>,
+}
+
+fn pair(x: &String, y: &String) -> (String, String) {
+    if x < y {
+        (x.clone(), y.clone())
+    } else {
+        (y.clone(), x.clone())
+    }
+}
+
+impl Aliases {
+    pub fn pointsTo(&self, x: &String) -> BTreeSet<Location> {
+        self.targets.get(x).cloned().unwrap_or_default()
+    }
+
+    pub fn mayAlias(&self, x: &String, y: &String) -> bool {
+        let (tx, ty) = (self.pointsTo(x), self.pointsTo(y));
+        if tx.is_empty() || ty.is_empty() {
+            return false;
+        }
+        tx.contains(&Location::Unknown) || ty.contains(&Location::Unknown) || !tx.is_disjoint(&ty)
+    }
+
+    pub fn mustAlias(&self, x: &String, y: &String) -> bool {
+        !self.pointsTo(x).is_empty() && (x == y || self.equal.contains(&pair(x, y)))
+    }
+
+    // x no longer holds what it held
+    fn kill(&mut self, x: &String) {
+        self.targets.remove(x);
+        self.equal.retain(|(y, z)| y != x && z != x);
+    }
+
+    fn copy(&mut self, x: &String, y: &String) {
+        if x == y {
+            return;
+        }
+        self.kill(x);
+        let targets = self.pointsTo(y);
+        if targets.is_empty() {
+            return;
+        }
+        self.targets.insert(x.clone(), targets);
+        let mut others = Vec::new();
+        for (z1, z2) in self.equal.iter() {
+            if z1 == y {
+                others.push(z2.clone());
+            } else if z2 == y {
+                others.push(z1.clone());
+            }
+        }
+        self.equal.insert(pair(x, y));
+        for z in others {
+            self.equal.insert(pair(x, &z));
+        }
+    }
+}
+
+// None where no path reaches
+#[derive(Clone, PartialEq, Debug)]
+pub struct PointsTo(pub Option<Aliases>);
+
+impl Lattice for PointsTo {
+    fn bottom() -> Self {
+        PointsTo(None)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (&self.0, &other.0) {
+            (Some(a1), Some(a2)) => {
+                let mut targets = a1.targets.clone();
+                for (x, locations) in a2.targets.iter() {
+                    targets.entry(x.clone()).or_default().extend(locations.iter().cloned());
+                }
+                PointsTo(Some(Aliases {
+                    targets,
+                    equal: a1.equal.intersection(&a2.equal).cloned().collect(),
+                }))
+            }
+            (Some(_), None) => self.clone(),
+            (None, _) => other.clone(),
+        }
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(a1), Some(a2)) => {
+                a1.targets.iter().all(|(x, locations)| locations.is_subset(&a2.pointsTo(x)))
+                    && a2.equal.is_subset(&a1.equal)
+            }
+        }
+    }
+}
+
+// the variables st may assign a pointer to
+fn pointerAssignments(st: &Statement, out: &mut Vec<String>) {
+    match st {
+        Statement::HeapNew { x, .. }
+        | Statement::RecordNew { x, .. }
+        | Statement::ArrayNew { x, .. }
+        | Statement::HeapAlias { x, .. }
+        | Statement::Call { x: Some(x), .. } => out.push(x.clone()),
+        Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => {
+            if let Expression::StackVar { .. } | Expression::FieldRead { .. } = ex1 {
+                out.push(x.clone());
+            }
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => {
+            pointerAssignments(st1, out);
+            pointerAssignments(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            pointerAssignments(then_branch, out);
+            pointerAssignments(else_branch, out);
+        }
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => pointerAssignments(st, out),
+        _ => (),
+    }
+}
+
+struct PointsToAnalysis;
+
+impl Analysis for PointsToAnalysis {
+    type Fact = PointsTo;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        PointsTo(Some(Aliases {
+            targets: BTreeMap::new(),
+            equal: BTreeSet::new(),
+        }))
+    }
+
+    fn transfer(&self, st: &Statement, at: Point, fact: &Self::Fact) -> Self::Fact {
+        let mut aliases = match &fact.0 {
+            Some(aliases) => aliases.clone(),
+            None => return PointsTo(None),
+        };
+        match st {
+            Statement::HeapNew { x, .. } | Statement::RecordNew { x, .. } | Statement::ArrayNew { x, .. } => {
+                aliases.kill(x);
+                aliases.targets.insert(x.clone(), BTreeSet::from([Location::Site(at)]));
+            }
+            Statement::HeapAlias { x, y } => aliases.copy(x, y),
+            Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => match ex1 {
+                Expression::StackVar { x: y } => aliases.copy(x, y),
+                Expression::FieldRead { .. } => {
+                    aliases.kill(x);
+                    aliases.targets.insert(x.clone(), BTreeSet::from([Location::Unknown]));
+                }
+                _ => aliases.kill(x),
+            },
+            Statement::Call { x: Some(x), .. } => {
+                aliases.kill(x);
+                aliases.targets.insert(x.clone(), BTreeSet::from([Location::Unknown]));
+            }
+            Statement::Parallel { .. } => {
+                for x in definedVariables(st) {
+                    aliases.equal.retain(|(y, z)| *y != x && *z != x);
+                }
+                let mut pointers = Vec::new();
+                pointerAssignments(st, &mut pointers);
+                for x in pointers {
+                    aliases.targets.entry(x).or_default().insert(Location::Unknown);
+                }
+            }
+            _ => {
+                for x in definedVariables(st) {
+                    aliases.kill(&x);
+                }
+            }
+        }
+        PointsTo(Some(aliases))
+    }
+}
+
+// the locations every pointer variable may point to and the variables that surely alias, at every point of cfg
+pub fn pointsTo(cfg: &Cfg) -> Solution<PointsTo> {
+    solve(&PointsToAnalysis, cfg)
+}
+
+pub fn mayAlias(solution: &Solution<PointsTo>, x: &String, y: &String, at: Point) -> bool {
+    match &solution.at(at.block, at.index).0 {
+        Some(aliases) => aliases.mayAlias(x, y),
+        None => false,
+    }
+}
+
+pub fn mustAlias(solution: &Solution<PointsTo>, x: &String, y: &String, at: Point) -> bool {
+    match &solution.at(at.block, at.index).0 {
+        Some(aliases) => aliases.mustAlias(x, y),
+        None => false,
+    }
+}
+
+pub fn printAliases(fact: &PointsTo) -> String {
+    let aliases = match &fact.0 {
+        Some(aliases) => aliases,
+        None => return String::from("unreachable"),
+    };
+    let mut out = Vec::new();
+    for (x, locations) in aliases.targets.iter() {
+        let locations: Vec<String> = locations.iter().map(printLocation).collect();
+        out.push(format!("{} → {{{}}}", x, locations.join(", ")));
+    }
+    for (x, y) in aliases.equal.iter() {
+        out.push(format!("{} == {}", x, y));
+    }
+    out.join(", ")
+}
+
+// the points-to sets and must-aliases before and after every statement of st, block by block
+pub fn printPointsTo(st: &Statement) -> String {
+    let cfg = buildCfg(st);
+    let solution = pointsTo(&cfg);
+    let mut out = String::new();
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        out += &format!("b{}:\n", b);
+        for (i, st) in block.statements.iter().enumerate() {
+            out += &format!("    {{ {} }}\n    {}\n", printAliases(solution.at(b, i)), printStatement(st.clone()));
+        }
+        out += &format!("    {{ {} }}\n", printAliases(solution.blockExit(b)));
+    }
+    out
+}
@@ -0,0 +1,90 @@
+/*
+Exact fractions for probabilities and expected values.
+
+A Rational is kept in lowest terms with a positive denominator, so two equal
+fractions are also equal as values. Arithmetic is checked and gives None once
+a numerator or denominator no longer fits in an i128.
+*/
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rational {
+    pub num: i128,
+    pub den: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Rational {
+    // num / den in lowest terms, a zero denominator is left as it is
+    pub fn new(num: i128, den: i128) -> Rational {
+        if den == 0 {
+            return Rational { num, den };
+        }
+        let g = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        Rational {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    pub fn integer(n: i128) -> Rational {
+        Rational::new(n, 1)
+    }
+
+    pub fn zero() -> Rational {
+        Rational::integer(0)
+    }
+
+    pub fn one() -> Rational {
+        Rational::integer(1)
+    }
+
+    // whether this is a probability, i.e. between 0 and 1
+    pub fn isProbability(&self) -> bool {
+        self.den > 0 && self.num >= 0 && self.num <= self.den
+    }
+
+    pub fn add(&self, other: &Rational) -> Option<Rational> {
+        let num = self
+            .num
+            .checked_mul(other.den)?
+            .checked_add(other.num.checked_mul(self.den)?)?;
+        Some(Rational::new(num, self.den.checked_mul(other.den)?))
+    }
+
+    pub fn sub(&self, other: &Rational) -> Option<Rational> {
+        self.add(&Rational::new(-other.num, other.den))
+    }
+
+    pub fn mul(&self, other: &Rational) -> Option<Rational> {
+        // cancelling crosswise first keeps the products small
+        let g1 = gcd(self.num, other.den).max(1);
+        let g2 = gcd(other.num, self.den).max(1);
+        Some(Rational::new(
+            (self.num / g1).checked_mul(other.num / g2)?,
+            (self.den / g2).checked_mul(other.den / g1)?,
+        ))
+    }
+
+    pub fn div(&self, other: &Rational) -> Option<Rational> {
+        if other.num == 0 {
+            return None;
+        }
+        self.mul(&Rational::new(other.den, other.num))
+    }
+}
+
+pub fn printRational(r: Rational) -> String {
+    if r.den == 1 {
+        r.num.to_string()
+    } else {
+        r.num.to_string() + "/" + &r.den.to_string()
+    }
+}
@@ -0,0 +1,62 @@
+/*
+Reaching definitions, as a plug-in of the dataflow framework.
+
+A definition is a statement assigning a stack variable, named by its position
+in the control-flow graph. It reaches a program point when some path from it
+to the point does not assign the variable again. The analysis runs forward
+over sets of definitions, starting from the empty set:
+
+out = (in − every definition of x) ∪ { d }     d assigns x
+out = in ∪ { d }                               d is a parallel composition that may assign x
+
+as a parallel composition is a single statement of its block and need not
+assign every variable one of its branches does.
+*/
+
+use std::collections::BTreeSet;
+
+use crate::cfg::Cfg;
+use crate::dataflow::solve;
+use crate::dataflow::Analysis;
+use crate::dataflow::Direction;
+use crate::dataflow::Point;
+use crate::dataflow::Solution;
+use crate::imp::definedVariables;
+use crate::imp::Statement;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Definition {
+    pub x: String,
+    pub at: Point,
+}
+
+struct ReachingDefinitions;
+
+impl Analysis for ReachingDefinitions {
+    type Fact = BTreeSet<Definition>;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, st: &Statement, at: Point, fact: &Self::Fact) -> Self::Fact {
+        let defined = definedVariables(st);
+        let mut out = fact.clone();
+        if let Statement::Parallel { .. } = st {
+        } else {
+            out.retain(|d| !defined.contains(&d.x));
+        }
+        for x in defined {
+            out.insert(Definition { x, at });
+        }
+        out
+    }
+}
+
+pub fn reachingDefinitions(cfg: &Cfg) -> Solution<BTreeSet<Definition>> {
+    solve(&ReachingDefinitions, cfg)
+}
@@ -0,0 +1,549 @@
+/*
+Information-flow security types.
+
+Every store variable carries a label ℓ ∈ {Low, High} with Low ⊑ High, and a
+pointer additionally carries the label of the heap cell it points to (one
+label for all fields of a record and all elements of an array). The
+checker tracks a program-counter label pc, raised by the condition of every
+IfThenElse and While, and enforces (termination-insensitive) noninterference:
+
+pc ⊔ ℓ(e) ⊑ ℓ(x)                         x := e
+pc ⊔ ℓ(x) ⊔ ℓ(e) ⊑ cell(x)               !x := e
+pc ⊑ ℓ(x), pc ⊔ ℓ(e) ⊑ cell(x)           x := new(e)
+pc ⊔ ℓ(y) ⊑ ℓ(x), cell(x) = cell(y)      x = y
+pc ⊔ ℓ(x) ⊔ ℓ(i) ⊔ ℓ(e) ⊑ cell(x)        x[i] := e
+d ⊑ ℓ(x), d ⊑ cell(y) for every y ∈ ys   x := f(e, ...)
+  where d = pc ⊔ ℓ(e) ⊔ ... ⊔ cell(y) ⊔ ... and ys are the pointers passed to f
+
+A variable without a label gets one on its first assignment and keeps it.
+A break or continue under a condition makes everything after it in the loop
+depend on that condition, so the body of a loop is checked with the labels of
+those conditions added to pc.
+Procedure bodies only see their parameters, so a call is summarised without
+looking at the body: anything passed in may end up in the result and in every
+heap cell passed in. The one exception is input and output, which a body can
+reach without being given anything, so a call to a procedure that prints or
+reads, itself or through the procedures it calls, is checked like a print of
+everything passed in:
+
+pc ⊔ d ⊑ Low                             f(e, ...) where f does input or output
+
+Which branch of a nondeterministic or probabilistic choice runs and what havoc
+and coin pick do not depend on the program's data, so all of them are Low:
+
+pc ⊑ ℓ(x)                                havoc x, x := coin(p)
+
+The input and output of print and read are Low, so
+
+pc ⊔ ℓ(e) ⊑ Low                          print e
+pc ⊑ ℓ(x)                                read x
+
+A failing assert or assume stops the run, which like nontermination is not
+considered a leak, so their conditions may depend on High data.
+
+s1 || s2 is checked like s1; s2 and atomic { s } like s. Labels never change
+once given, so the order in which the branches run does not matter, but how
+the scheduler interleaves them is not considered a channel either.
+*/
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::imp::escapesLoop;
+use crate::imp::printStatement;
+use crate::imp::typeCheck;
+use crate::imp::typeCheckExp;
+use crate::imp::typeCheckProgram;
+use crate::imp::CheckContext;
+use crate::imp::ExType;
+use crate::imp::Expression;
+use crate::imp::Statement;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SecLabel {
+    Low,
+    High,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SecType {
+    pub tp: ExType,
+    pub label: SecLabel,
+    // the label of the pointed-to cell, only for pointers
+    pub cell: Option<SecLabel>,
+}
+
+impl SecType {
+    pub fn new(tp: ExType, label: SecLabel) -> SecType {
+        SecType {
+            tp,
+            label,
+            cell: None,
+        }
+    }
+
+    pub fn pointer(label: SecLabel, cell: SecLabel) -> SecType {
+        SecType {
+            tp: ExType::PointerType,
+            label,
+            cell: Some(cell),
+        }
+    }
+}
+
+pub fn join(l1: SecLabel, l2: SecLabel) -> SecLabel {
+    if l1 == SecLabel::High || l2 == SecLabel::High {
+        SecLabel::High
+    } else {
+        SecLabel::Low
+    }
+}
+
+pub fn flowsTo(l1: SecLabel, l2: SecLabel) -> bool {
+    l1 == SecLabel::Low || l2 == SecLabel::High
+}
+
+pub fn printSecLabel(l: SecLabel) -> String {
+    match l {
+        SecLabel::Low => String::from("Low"),
+        SecLabel::High => String::from("High"),
+    }
+}
+
+fn plainContext(ctx: &HashMap<String, SecType>) -> HashMap<String, ExType> {
+    ctx.iter().map(|(x, t)| (x.clone(), t.tp.clone())).collect()
+}
+
+fn lookup(x: &String, ctx: &HashMap<String, SecType>) -> Result<SecType, String> {
+    match ctx.get(x) {
+        Some(t) => Ok(t.clone()),
+        None => Err(format!("undeclared stack variable: {}", x)),
+    }
+}
+
+fn cellOf(x: &String, ctx: &HashMap<String, SecType>) -> Result<SecLabel, String> {
+    match lookup(x, ctx)?.cell {
+        Some(l) => Ok(l),
+        None => Err(format!("variable: {} is not a pointer", x)),
+    }
+}
+
+pub fn securityLabelExp(exp: Expression, ctx: &HashMap<String, SecType>) -> Result<SecLabel, String> {
+    match exp {
+        Expression::StackVar { x } => Ok(lookup(&x, ctx)?.label),
+        // which cell is read depends on the pointer, so both labels flow into the result
+        Expression::HeapRead { x }
+        | Expression::FieldRead { x, .. }
+        | Expression::ArrayLength { x } => Ok(join(lookup(&x, ctx)?.label, cellOf(&x, ctx)?)),
+        Expression::ArrayRead { x, index } => Ok(join(
+            join(lookup(&x, ctx)?.label, cellOf(&x, ctx)?),
+            securityLabelExp(*index, ctx)?,
+        )),
+        Expression::NatConstant { .. } | Expression::BoolConstant { .. } => Ok(SecLabel::Low),
+        Expression::Negation { ex1 } => securityLabelExp(*ex1, ctx),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => Ok(join(
+            securityLabelExp(*ex1, ctx)?,
+            securityLabelExp(*ex2, ctx)?,
+        )),
+    }
+}
+
+// checks that data labelled `data` may be written to something labelled `target` under pc
+fn checkFlow(
+    data: SecLabel,
+    pc: SecLabel,
+    target: SecLabel,
+    what: String,
+    st: &Statement,
+) -> Option<String> {
+    if !flowsTo(data, target) {
+        Some(format!(
+            "Explicit flow: `{}` writes High data into Low {}",
+            printStatement(st.clone()),
+            what
+        ))
+    } else if !flowsTo(pc, target) {
+        Some(format!(
+            "Implicit flow: `{}` writes Low {} under a High condition",
+            printStatement(st.clone()),
+            what
+        ))
+    } else {
+        None
+    }
+}
+
+// type checks st first, then checks its information flow starting from a Low pc
+pub fn securityCheck(st: Statement, ctx: &mut HashMap<String, SecType>) -> Option<String> {
+    let mut program = CheckContext::default();
+    if let Some(e) = typeCheckProgram(st.clone(), &mut plainContext(ctx), &mut program) {
+        return Some(e);
+    }
+    let mut bodies = HashMap::new();
+    procedureBodies(&st, &mut bodies);
+    let io = bodies
+        .iter()
+        .filter(|(_, body)| doesIo(body, &bodies, &mut HashSet::new()))
+        .map(|(name, _)| name.clone())
+        .collect();
+    securityCheckPc(&st, ctx, SecLabel::Low, &program, &io)
+}
+
+fn procedureBodies(st: &Statement, out: &mut HashMap<String, Statement>) {
+    match st {
+        Statement::Procedure { name, body, .. } => {
+            out.insert(name.clone(), (**body).clone());
+            procedureBodies(body, out);
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::Parallel { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => {
+            procedureBodies(st1, out);
+            procedureBodies(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            procedureBodies(then_branch, out);
+            procedureBodies(else_branch, out);
+        }
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => procedureBodies(st, out),
+        _ => (),
+    }
+}
+
+// whether running st may print or read, following calls into the bodies not visited yet
+fn doesIo(st: &Statement, bodies: &HashMap<String, Statement>, visited: &mut HashSet<String>) -> bool {
+    match st {
+        Statement::Print { .. } | Statement::Read { .. } => true,
+        Statement::Call { name, .. } => {
+            visited.insert(name.clone())
+                && bodies.get(name).map(|body| doesIo(body, bodies, visited)).unwrap_or(false)
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::Parallel { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => doesIo(st1, bodies, visited) || doesIo(st2, bodies, visited),
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => doesIo(then_branch, bodies, visited) || doesIo(else_branch, bodies, visited),
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => doesIo(st, bodies, visited),
+        _ => false,
+    }
+}
+
+// the labels of the conditions deciding whether st leaves or restarts its loop early
+fn escapeLabel(st: &Statement, ctx: &HashMap<String, SecType>) -> Result<SecLabel, String> {
+    match st {
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => {
+            Ok(join(escapeLabel(st1, ctx)?, escapeLabel(st2, ctx)?))
+        }
+        Statement::Atomic { st } => escapeLabel(st, ctx),
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } if escapesLoop(st) => Ok(join(
+            securityLabelExp(condition.clone(), ctx)?,
+            join(escapeLabel(then_branch, ctx)?, escapeLabel(else_branch, ctx)?),
+        )),
+        _ => Ok(SecLabel::Low),
+    }
+}
+
+fn securityCheckPc(
+    st: &Statement,
+    ctx: &mut HashMap<String, SecType>,
+    pc: SecLabel,
+    // the declarations and procedures of the whole program
+    program: &CheckContext,
+    // the procedures that print or read
+    io: &HashSet<String>,
+) -> Option<String> {
+    match st {
+        Statement::StackAssignment { x, ex1 } | Statement::AnnotatedAssignment { x, ex1, .. } => {
+            let data = match securityLabelExp(ex1.clone(), ctx) {
+                Ok(l) => l,
+                Err(e) => return Some(e),
+            };
+            match ctx.get(x) {
+                Some(t) => checkFlow(data, pc, t.label, format!("variable {}", x), st),
+                None => {
+                    let tp = match typeCheckExp(ex1.clone(), &plainContext(ctx)) {
+                        Ok(tp) => tp,
+                        Err(e) => return Some(e),
+                    };
+                    ctx.insert(x.clone(), SecType::new(tp, join(pc, data)));
+                    None
+                }
+            }
+        }
+        Statement::HeapUpdate { .. } | Statement::FieldUpdate { .. } | Statement::ArrayUpdate { .. } => {
+            let (x, written) = match st {
+                Statement::HeapUpdate { x, ex1 } | Statement::FieldUpdate { x, ex1, .. } => (x, vec![ex1]),
+                // the index decides which element changes, so it flows into the cell as well
+                Statement::ArrayUpdate { x, index, ex1 } => (x, vec![index, ex1]),
+                _ => return None,
+            };
+            let mut data = SecLabel::Low;
+            for e in written {
+                match securityLabelExp(e.clone(), ctx) {
+                    Ok(l) => data = join(data, l),
+                    Err(e) => return Some(e),
+                }
+            }
+            match (lookup(x, ctx), cellOf(x, ctx)) {
+                (Ok(t), Ok(cell)) => checkFlow(
+                    join(data, t.label),
+                    pc,
+                    cell,
+                    format!("heap cell of {}", x),
+                    st,
+                ),
+                (Err(e), _) | (_, Err(e)) => Some(e),
+            }
+        }
+        Statement::HeapNew { .. } | Statement::RecordNew { .. } | Statement::ArrayNew { .. } => {
+            let (x, initial) = match st {
+                Statement::HeapNew { x, ex1 } => (x, vec![ex1]),
+                Statement::ArrayNew { x, size, ex1 } => (x, vec![size, ex1]),
+                Statement::RecordNew { x, fields } => (x, fields.iter().map(|(_, e)| e).collect()),
+                _ => return None,
+            };
+            let mut data = SecLabel::Low;
+            for e in initial {
+                match securityLabelExp(e.clone(), ctx) {
+                    Ok(l) => data = join(data, l),
+                    Err(e) => return Some(e),
+                }
+            }
+            match ctx.get(x) {
+                Some(t) => match t.cell {
+                    Some(cell) => checkFlow(SecLabel::Low, pc, t.label, format!("pointer {}", x), st)
+                        .or(checkFlow(data, pc, cell, format!("heap cell of {}", x), st)),
+                    None => Some(format!("{} is not PointerType", x)),
+                },
+                None => {
+                    let mut plain = plainContext(ctx);
+                    typeCheck(st.clone(), &mut plain);
+                    let mut t = SecType::pointer(pc, join(pc, data));
+                    t.tp = plain.get(x).cloned().unwrap_or(ExType::PointerType);
+                    ctx.insert(x.clone(), t);
+                    None
+                }
+            }
+        }
+        Statement::HeapAlias { x, y } => {
+            let source = match lookup(y, ctx) {
+                Ok(t) => t,
+                Err(e) => return Some(e),
+            };
+            let source_cell = match source.cell {
+                Some(l) => l,
+                None => return Some(format!("Cannot alias {} since it is not PointerType", y)),
+            };
+            match ctx.get(x) {
+                Some(t) => {
+                    if t.cell != Some(source_cell) {
+                        // otherwise a High cell could be read, or a Low cell written, through x
+                        Some(format!(
+                            "Alias `{}` would launder labels: the cell of {} is {} but the cell of {} is {}",
+                            printStatement(st.clone()),
+                            x,
+                            t.cell.map(printSecLabel).unwrap_or(String::from("not a pointer")),
+                            y,
+                            printSecLabel(source_cell)
+                        ))
+                    } else {
+                        checkFlow(source.label, pc, t.label, format!("pointer {}", x), st)
+                    }
+                }
+                None => {
+                    ctx.insert(
+                        x.clone(),
+                        SecType {
+                            tp: source.tp.clone(),
+                            label: join(pc, source.label),
+                            cell: Some(source_cell),
+                        },
+                    );
+                    None
+                }
+            }
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::Parallel { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. } => match securityCheckPc(st1, ctx, pc, program, io) {
+            Some(e) => Some(e),
+            None => securityCheckPc(st2, ctx, pc, program, io),
+        },
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => match securityLabelExp(condition.clone(), ctx) {
+            Ok(l) => {
+                let branch_pc = join(pc, l);
+                match securityCheckPc(then_branch, ctx, branch_pc, program, io) {
+                    Some(e) => Some(e),
+                    None => securityCheckPc(else_branch, ctx, branch_pc, program, io),
+                }
+            }
+            Err(e) => Some(e),
+        },
+        Statement::Skip | Statement::Declaration { .. } | Statement::Procedure { .. } => None,
+        Statement::Assert { condition } | Statement::Assume { condition } => {
+            securityLabelExp(condition.clone(), ctx).err()
+        }
+        Statement::Print { ex1 } => match securityLabelExp(ex1.clone(), ctx) {
+            Ok(l) => checkFlow(l, pc, SecLabel::Low, String::from("output"), st),
+            Err(e) => Some(e),
+        },
+        // reading under a High pc would reveal the branch taken through what is left of the input
+        Statement::Read { x, tp } => {
+            if let Some(e) = checkFlow(SecLabel::Low, pc, SecLabel::Low, String::from("input"), st) {
+                return Some(e);
+            }
+            if !ctx.contains_key(x) {
+                let tp = tp
+                    .clone()
+                    .or(program.declared.get(x).map(|(tp, _)| tp.clone()))
+                    .unwrap_or(ExType::NatType);
+                ctx.insert(x.clone(), SecType::new(tp, SecLabel::Low));
+            }
+            None
+        }
+        Statement::While { condition, st: body } => {
+            match (securityLabelExp(condition.clone(), ctx), escapeLabel(body, ctx)) {
+                (Ok(l), Ok(escape)) => securityCheckPc(body, ctx, join(pc, join(l, escape)), program, io),
+                (Err(e), _) | (_, Err(e)) => Some(e),
+            }
+        }
+        // how often the body runs depends on the bounds, and x is set from them
+        Statement::For { x, from, to, st: body } => {
+            let (bound, escape) = match (
+                securityLabelExp(from.clone(), ctx),
+                securityLabelExp(to.clone(), ctx),
+                escapeLabel(body, ctx),
+            ) {
+                (Ok(l1), Ok(l2), Ok(escape)) => (join(l1, l2), escape),
+                (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return Some(e),
+            };
+            let loop_pc = join(pc, join(bound, escape));
+            let target = match ctx.get(x) {
+                Some(t) => t.label,
+                None => {
+                    ctx.insert(x.clone(), SecType::new(ExType::NatType, loop_pc));
+                    loop_pc
+                }
+            };
+            match checkFlow(bound, loop_pc, target, format!("variable {}", x), st) {
+                Some(e) => Some(e),
+                None => securityCheckPc(body, ctx, loop_pc, program, io),
+            }
+        }
+        // the first iteration runs under pc, the later ones also depend on the condition
+        Statement::DoWhile { st: body, condition } => {
+            let escape = match escapeLabel(body, ctx) {
+                Ok(l) => l,
+                Err(e) => return Some(e),
+            };
+            if let Some(e) = securityCheckPc(body, ctx, join(pc, escape), program, io) {
+                return Some(e);
+            }
+            match securityLabelExp(condition.clone(), ctx) {
+                Ok(l) => securityCheckPc(body, ctx, join(pc, join(l, escape)), program, io),
+                Err(e) => Some(e),
+            }
+        }
+        Statement::Break | Statement::Continue => None,
+        Statement::Atomic { st } => securityCheckPc(st, ctx, pc, program, io),
+        Statement::Coin { x, .. } => match ctx.get(x) {
+            Some(t) => checkFlow(SecLabel::Low, pc, t.label, format!("variable {}", x), st),
+            None => {
+                ctx.insert(x.clone(), SecType::new(ExType::BoolType, pc));
+                None
+            }
+        },
+        Statement::Havoc { x } => match ctx.get(x) {
+            Some(t) => checkFlow(SecLabel::Low, pc, t.label, format!("variable {}", x), st),
+            None => {
+                let tp = program
+                    .declared
+                    .get(x)
+                    .map(|(tp, _)| tp.clone())
+                    .unwrap_or(ExType::NatType);
+                ctx.insert(x.clone(), SecType::new(tp, pc));
+                None
+            }
+        },
+        Statement::Call { x, name, args } => {
+            let mut data = SecLabel::Low;
+            let mut cells = Vec::new();
+            for e in args {
+                match securityLabelExp(e.clone(), ctx) {
+                    Ok(l) => data = join(data, l),
+                    Err(e) => return Some(e),
+                }
+                // the callee can read and write the cell of every pointer it is given
+                if let Expression::StackVar { x: y } = e {
+                    if let Some(cell) = lookup(y, ctx).ok().and_then(|t| t.cell) {
+                        data = join(data, cell);
+                        cells.push((y, cell));
+                    }
+                }
+            }
+            for (y, cell) in cells {
+                if let Some(e) = checkFlow(data, pc, cell, format!("heap cell of {}", y), st) {
+                    return Some(e);
+                }
+            }
+            if io.contains(name) {
+                if let Some(e) = checkFlow(data, pc, SecLabel::Low, String::from("output"), st) {
+                    return Some(e);
+                }
+            }
+            match x {
+                Some(x) => match ctx.get(x) {
+                    Some(t) => checkFlow(data, pc, t.label, format!("variable {}", x), st),
+                    None => {
+                        let tp = match program.procedures.get(name).and_then(|s| s.ret.clone()) {
+                            Some(tp) => tp,
+                            None => return Some(format!("Undefined procedure {}", name)),
+                        };
+                        ctx.insert(x.clone(), SecType::new(tp, join(pc, data)));
+                        None
+                    }
+                },
+                None => None,
+            }
+        }
+    }
+}
@@ -0,0 +1,437 @@
+/*
+Termination of while and do-while loops by linear ranking functions.
+
+A ranking function of a loop is an expression over nat variables that is at
+least 0 whenever an iteration starts and decreases by at least 1 in every
+iteration, so the loop can only run finitely often. The candidates are
+
+e2 - e1      for every conjunct e1 <= e2 or e1 < e2 of the condition, and the
+             negated ones of a condition that has to fail, with e1 and e2 a
+             variable plus a constant or a constant; it is at least 0 where the
+             conjunct holds
+x            for every variable the body assigns, which is at least 0 as a nat
+
+For a candidate the loop is rewritten to copy each of its variables x to the
+hidden x#old at the start of the body, and the octagon analysis of the
+program, knowing only that those variables are nats at the start, bounds
+x - x#old wherever the next iteration starts. The candidate is a ranking
+function if these bounds add up to a decrease of at least 1.
+
+A loop may not terminate if its condition is always true, or if the body
+assigns none of the variables of its condition and the condition does not read
+the heap, unless the body breaks out of the loop. It terminates if its
+condition is always false. Otherwise it is unknown whether it terminates.
+
+Procedure bodies are checked on their own, and loops within the branches of a
+parallel composition are not checked, as those run interleaved with the other
+branch. Every loop that is not shown to terminate is a lint.
+*/
+
+use crate::cfg::buildCfg;
+use crate::cfg::loopForest;
+use crate::cfg::predecessors;
+use crate::constants::foldExpression;
+use crate::imp::definedVariables;
+use crate::imp::expressionVariables;
+use crate::imp::printExpression;
+use crate::imp::Expression;
+use crate::imp::Statement;
+use crate::octagon::linear;
+use crate::octagon::octagons;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Termination {
+    Terminates(String),
+    MayNotTerminate(String),
+    Unknown,
+}
+
+pub fn printTermination(t: &Termination) -> String {
+    match t {
+        Termination::Terminates(r) => format!("terminates (ranking function {})", r),
+        Termination::MayNotTerminate(reason) => format!("may not terminate ({})", reason),
+        Termination::Unknown => String::from("unknown"),
+    }
+}
+
+// plus - minus + c
+#[derive(Clone, PartialEq, Debug)]
+struct Ranking {
+    plus: Option<String>,
+    minus: Option<String>,
+    c: i64,
+}
+
+fn printRanking(r: &Ranking) -> String {
+    let mut out = match &r.plus {
+        Some(x) => x.clone(),
+        None => r.c.to_string(),
+    };
+    if let Some(x) = &r.minus {
+        out = out + " - " + x;
+    }
+    match (&r.plus, r.c) {
+        (Some(_), c) if c > 0 => out + " + " + &c.to_string(),
+        (Some(_), c) if c < 0 => out + " - " + &(-c).to_string(),
+        _ => out,
+    }
+}
+
+fn old(x: &String) -> String {
+    x.clone() + "#old"
+}
+
+// the candidates from the conjuncts of condition, when it evaluates to holds at every iteration
+fn guardRankings(condition: &Expression, holds: bool, out: &mut Vec<Ranking>) {
+    // e1 <= e2 or e1 < e2
+    let (ex1, ex2) = match (condition, holds) {
+        (Expression::Negation { ex1 }, _) => return guardRankings(ex1, !holds, out),
+        (Expression::Conjunction { ex1, ex2 }, true) | (Expression::Disjunction { ex1, ex2 }, false) => {
+            guardRankings(ex1, holds, out);
+            guardRankings(ex2, holds, out);
+            return;
+        }
+        (Expression::Comparision { ex1, ex2 }, true)
+        | (Expression::LessThan { ex1, ex2 }, true)
+        | (Expression::GreaterEqual { ex2: ex1, ex1: ex2 }, true)
+        | (Expression::GreaterThan { ex2: ex1, ex1: ex2 }, true)
+        | (Expression::Comparision { ex2: ex1, ex1: ex2 }, false)
+        | (Expression::LessThan { ex2: ex1, ex1: ex2 }, false)
+        | (Expression::GreaterEqual { ex1, ex2 }, false)
+        | (Expression::GreaterThan { ex1, ex2 }, false) => (ex1, ex2),
+        _ => return,
+    };
+    if let (Some((minus, c1)), Some((plus, c2))) = (linear(ex1), linear(ex2)) {
+        if plus != minus {
+            out.push(Ranking { plus, minus, c: c2 - c1 });
+        }
+    }
+}
+
+fn readsHeap(exp: &Expression) -> bool {
+    match exp {
+        Expression::HeapRead { .. }
+        | Expression::FieldRead { .. }
+        | Expression::ArrayRead { .. }
+        | Expression::ArrayLength { .. } => true,
+        Expression::StackVar { .. } | Expression::NatConstant { .. } | Expression::BoolConstant { .. } => false,
+        Expression::Negation { ex1 } => readsHeap(ex1),
+        Expression::Add { ex1, ex2 }
+        | Expression::Sub { ex1, ex2 }
+        | Expression::Mul { ex1, ex2 }
+        | Expression::Div { ex1, ex2 }
+        | Expression::Mod { ex1, ex2 }
+        | Expression::Conjunction { ex1, ex2 }
+        | Expression::Disjunction { ex1, ex2 }
+        | Expression::Implication { ex1, ex2 }
+        | Expression::Comparision { ex1, ex2 }
+        | Expression::LessThan { ex1, ex2 }
+        | Expression::GreaterEqual { ex1, ex2 }
+        | Expression::GreaterThan { ex1, ex2 }
+        | Expression::Equal { ex1, ex2 }
+        | Expression::NotEqual { ex1, ex2 } => readsHeap(ex1) || readsHeap(ex2),
+    }
+}
+
+// whether st breaks out of the loop it is the body of
+fn breaksOut(st: &Statement) -> bool {
+    match st {
+        Statement::Break => true,
+        Statement::Sequence { st1, st2 } | Statement::Choice { st1, st2 } | Statement::ProbChoice { st1, st2, .. } => {
+            breaksOut(st1) || breaksOut(st2)
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => breaksOut(then_branch) || breaksOut(else_branch),
+        Statement::Atomic { st } => breaksOut(st),
+        _ => false,
+    }
+}
+
+// the loops of st in order, outside procedures and parallel compositions
+fn collectLoops(st: &Statement, out: &mut Vec<Statement>) {
+    match st {
+        Statement::While { st: body, .. } | Statement::DoWhile { st: body, .. } => {
+            out.push(st.clone());
+            collectLoops(body, out);
+        }
+        Statement::Sequence { st1, st2 } | Statement::Choice { st1, st2 } | Statement::ProbChoice { st1, st2, .. } => {
+            collectLoops(st1, out);
+            collectLoops(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collectLoops(then_branch, out);
+            collectLoops(else_branch, out);
+        }
+        Statement::For { st, .. } | Statement::Atomic { st } => collectLoops(st, out),
+        _ => (),
+    }
+}
+
+// st with ghosts at the start of the body of its loop number target, in the order of collectLoops
+fn instrument(st: &Statement, target: usize, count: &mut usize, ghosts: &Statement) -> Statement {
+    let recurse = |st: &Box<Statement>, count: &mut usize| Box::new(instrument(st, target, count, ghosts));
+    match st {
+        Statement::While { condition, st: body } => {
+            let this = *count == target;
+            *count += 1;
+            let mut body = recurse(body, count);
+            if this {
+                body = Box::new(Statement::Sequence {
+                    st1: Box::new(ghosts.clone()),
+                    st2: body,
+                });
+            }
+            Statement::While {
+                condition: condition.clone(),
+                st: body,
+            }
+        }
+        Statement::DoWhile { st: body, condition } => {
+            let this = *count == target;
+            *count += 1;
+            let mut body = recurse(body, count);
+            if this {
+                body = Box::new(Statement::Sequence {
+                    st1: Box::new(ghosts.clone()),
+                    st2: body,
+                });
+            }
+            Statement::DoWhile {
+                st: body,
+                condition: condition.clone(),
+            }
+        }
+        Statement::Sequence { st1, st2 } => {
+            let st1 = recurse(st1, count);
+            Statement::Sequence {
+                st1,
+                st2: recurse(st2, count),
+            }
+        }
+        Statement::Choice { st1, st2 } => {
+            let st1 = recurse(st1, count);
+            Statement::Choice {
+                st1,
+                st2: recurse(st2, count),
+            }
+        }
+        Statement::ProbChoice { st1, st2, p } => {
+            let st1 = recurse(st1, count);
+            Statement::ProbChoice {
+                st1,
+                st2: recurse(st2, count),
+                p: *p,
+            }
+        }
+        Statement::IfThenElse {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            let then_branch = recurse(then_branch, count);
+            Statement::IfThenElse {
+                condition: condition.clone(),
+                then_branch,
+                else_branch: recurse(else_branch, count),
+            }
+        }
+        Statement::For { x, from, to, st } => Statement::For {
+            x: x.clone(),
+            from: from.clone(),
+            to: to.clone(),
+            st: recurse(st, count),
+        },
+        Statement::Atomic { st } => Statement::Atomic {
+            st: recurse(st, count),
+        },
+        _ => st.clone(),
+    }
+}
+
+// whether r decreases by at least 1 in every iteration of loop number target of program
+fn decreases(program: &Statement, target: usize, r: &Ranking) -> bool {
+    let vars: Vec<&String> = r.plus.iter().chain(r.minus.iter()).collect();
+    let mut ghosts = Statement::Skip;
+    let mut nats = Statement::Skip;
+    for x in vars.iter() {
+        let copy = Statement::StackAssignment {
+            x: old(x),
+            ex1: Expression::StackVar { x: (*x).clone() },
+        };
+        let nat = Statement::Assume {
+            condition: Expression::GreaterEqual {
+                ex1: Box::new(Expression::StackVar { x: (*x).clone() }),
+                ex2: Box::new(Expression::NatConstant { n: 0 }),
+            },
+        };
+        ghosts = Statement::Sequence {
+            st1: Box::new(ghosts),
+            st2: Box::new(copy),
+        };
+        nats = Statement::Sequence {
+            st1: Box::new(nats),
+            st2: Box::new(nat),
+        };
+    }
+    let program = Statement::Sequence {
+        st1: Box::new(nats),
+        st2: Box::new(instrument(program, target, &mut 0, &ghosts)),
+    };
+    let cfg = buildCfg(&program);
+    // the block at the start of the body, and the innermost loop around it
+    let start = cfg.blocks.iter().position(|block| {
+        block.statements.iter().any(|st| match st {
+            Statement::StackAssignment { x, .. } => vars.iter().any(|y| *x == old(y)),
+            _ => false,
+        })
+    });
+    let start = match start {
+        Some(start) => start,
+        None => return false,
+    };
+    let forest = loopForest(&cfg);
+    let l = match forest.iter().filter(|l| l.blocks.contains(&start)).min_by_key(|l| l.blocks.len()) {
+        Some(l) => l,
+        None => return false,
+    };
+    let solution = octagons(&cfg);
+    // no iteration is known to decrease r where none is known to start
+    if solution.blockEntry(l.header).isEmpty() {
+        return false;
+    }
+    let mut latches = predecessors(&cfg)[l.header].clone();
+    latches.retain(|b| l.blocks.contains(b));
+    latches.iter().all(|b| {
+        let oct = solution.blockExit(*b);
+        if oct.isEmpty() {
+            return true;
+        }
+        let mut change = Some(0);
+        if let Some(x) = &r.plus {
+            change = change.zip(oct.differenceBound(x, &old(x))).map(|(c, d)| c + d);
+        }
+        if let Some(x) = &r.minus {
+            change = change.zip(oct.differenceBound(&old(x), x)).map(|(c, d)| c + d);
+        }
+        change.map(|c| c <= -1).unwrap_or(false)
+    })
+}
+
+fn terminates(program: &Statement, target: usize, lp: &Statement) -> Termination {
+    let (condition, body) = match lp {
+        Statement::While { condition, st } | Statement::DoWhile { st, condition } => (condition, st),
+        _ => return Termination::Unknown,
+    };
+    let breaks = breaksOut(body);
+    match foldExpression(condition) {
+        Expression::BoolConstant { b: false } => return Termination::Terminates(String::from("0")),
+        Expression::BoolConstant { b: true } if !breaks => {
+            return Termination::MayNotTerminate(String::from("its condition is always true"))
+        }
+        _ => (),
+    }
+    let mut read = Vec::new();
+    expressionVariables(condition, &mut read);
+    let assigned = definedVariables(body);
+    if !breaks && !readsHeap(condition) && !read.iter().any(|x| assigned.contains(x)) {
+        return Termination::MayNotTerminate(String::from(
+            "the body assigns none of the variables of its condition",
+        ));
+    }
+    let mut candidates = Vec::new();
+    guardRankings(condition, true, &mut candidates);
+    for x in assigned {
+        candidates.push(Ranking {
+            plus: Some(x),
+            minus: None,
+            c: 0,
+        });
+    }
+    for r in candidates.iter() {
+        if decreases(program, target, r) {
+            return Termination::Terminates(printRanking(r));
+        }
+    }
+    Termination::Unknown
+}
+
+fn describe(lp: &Statement) -> String {
+    match lp {
+        Statement::While { condition, .. } => format!("`while {}`", printExpression(condition.clone()).trim_end()),
+        Statement::DoWhile { condition, .. } => {
+            format!("`do ... while {}`", printExpression(condition.clone()).trim_end())
+        }
+        _ => String::new(),
+    }
+}
+
+fn checkLoops(program: &Statement, within: &str, out: &mut Vec<(String, Termination)>) {
+    let mut loops = Vec::new();
+    collectLoops(program, &mut loops);
+    for (k, lp) in loops.iter().enumerate() {
+        out.push((describe(lp) + within, terminates(program, k, lp)));
+    }
+}
+
+fn checkProcedures(st: &Statement, out: &mut Vec<(String, Termination)>) {
+    match st {
+        Statement::Procedure { name, body, .. } => {
+            checkLoops(body, &format!(" in procedure {}", name), out);
+            checkProcedures(body, out);
+        }
+        Statement::Sequence { st1, st2 }
+        | Statement::Choice { st1, st2 }
+        | Statement::ProbChoice { st1, st2, .. }
+        | Statement::Parallel { st1, st2 } => {
+            checkProcedures(st1, out);
+            checkProcedures(st2, out);
+        }
+        Statement::IfThenElse {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            checkProcedures(then_branch, out);
+            checkProcedures(else_branch, out);
+        }
+        Statement::While { st, .. }
+        | Statement::For { st, .. }
+        | Statement::DoWhile { st, .. }
+        | Statement::Atomic { st } => checkProcedures(st, out),
+        _ => (),
+    }
+}
+
+// every loop of st with what is known about its termination
+pub fn loopTermination(st: &Statement) -> Vec<(String, Termination)> {
+    let mut out = Vec::new();
+    checkLoops(st, "", &mut out);
+    checkProcedures(st, &mut out);
+    out
+}
+
+pub fn terminationReport(st: &Statement) -> Vec<String> {
+    loopTermination(st)
+        .iter()
+        .map(|(lp, t)| format!("Loop {}: {}", lp, printTermination(t)))
+        .collect()
+}
+
+// the loops of st that are not shown to terminate
+pub fn checkTermination(st: &Statement) -> Vec<String> {
+    loopTermination(st)
+        .iter()
+        .filter_map(|(lp, t)| match t {
+            Termination::Terminates(_) => None,
+            Termination::MayNotTerminate(_) => Some(format!("Loop {} {}", lp, printTermination(t))),
+            Termination::Unknown => Some(format!("Loop {} has no ranking function that shows it terminates", lp)),
+        })
+        .collect()
+}